@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE blacklisted_users
+    ADD CONSTRAINT UQ_blacklisted_user UNIQUE(discord_user_id)
@@ -0,0 +1,22 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS lobbies (
+    id SERIAL PRIMARY KEY NOT NULL,
+    organizer_discord_id BIGINT NOT NULL REFERENCES users (discord_id),
+    discord_channel_id BIGINT NOT NULL,
+    summary_message_id BIGINT,
+    target_size INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    filled_at BIGINT
+);
+
+CREATE TABLE IF NOT EXISTS lobby_members (
+    id SERIAL PRIMARY KEY NOT NULL,
+    lobby_id INTEGER NOT NULL REFERENCES lobbies (id),
+    discord_user_id BIGINT NOT NULL REFERENCES users (discord_id),
+    joined BOOLEAN NOT NULL,
+    updated_at BIGINT NOT NULL,
+    CONSTRAINT UQ_lobby_member UNIQUE(lobby_id, discord_user_id)
+);
+
+ALTER TABLE IF EXISTS message_children ADD COLUMN lobby_id INTEGER REFERENCES lobbies (id);
@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS channel_settings (
+    id SERIAL PRIMARY KEY NOT NULL,
+    discord_channel_id BIGINT NOT NULL UNIQUE,
+    notification_cooldown_seconds BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS user_settings (
+    id SERIAL PRIMARY KEY NOT NULL,
+    discord_user_id BIGINT NOT NULL UNIQUE REFERENCES users (discord_id),
+    min_notification_gap_seconds BIGINT
+);
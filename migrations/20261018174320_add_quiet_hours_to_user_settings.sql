@@ -0,0 +1,5 @@
+-- Add migration script here
+-- quiet hours are stored as minutes after midnight in the user's timezone, the window wraps around midnight when start > end
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS timezone TEXT;
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS quiet_start_minutes INTEGER;
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS quiet_end_minutes INTEGER;
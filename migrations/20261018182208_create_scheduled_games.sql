@@ -0,0 +1,24 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS scheduled_games (
+    id SERIAL PRIMARY KEY NOT NULL,
+    organizer_discord_id BIGINT NOT NULL REFERENCES users (discord_id),
+    discord_channel_id BIGINT NOT NULL,
+    announcement_message_id BIGINT,
+    description TEXT NOT NULL,
+    scheduled_for BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    reminded_at BIGINT,
+    started_at BIGINT,
+    cancelled_at BIGINT
+);
+
+CREATE TABLE IF NOT EXISTS scheduled_game_rsvps (
+    id SERIAL PRIMARY KEY NOT NULL,
+    scheduled_game_id INTEGER NOT NULL REFERENCES scheduled_games (id),
+    discord_user_id BIGINT NOT NULL REFERENCES users (discord_id),
+    accepted BOOLEAN NOT NULL,
+    updated_at BIGINT NOT NULL,
+    CONSTRAINT UQ_scheduled_game_rsvp UNIQUE(scheduled_game_id, discord_user_id)
+);
+
+ALTER TABLE IF EXISTS message_children ADD COLUMN scheduled_game_id INTEGER REFERENCES scheduled_games (id);
@@ -0,0 +1,6 @@
+-- Add migration script here
+-- nothing stopped the same user from being added twice before
+DELETE FROM admins a USING admins b WHERE a.discord_user_id = b.discord_user_id AND a.id > b.id;
+
+ALTER TABLE admins
+    ADD CONSTRAINT UQ_admin UNIQUE(discord_user_id)
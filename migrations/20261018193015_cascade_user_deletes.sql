@@ -0,0 +1,28 @@
+-- Add migration script here
+-- deleting a user deletes everything that belongs to them, including lobbies and games they organized
+ALTER TABLE ping_list DROP CONSTRAINT ping_list_discord_user_id_fkey,
+    ADD CONSTRAINT ping_list_discord_user_id_fkey FOREIGN KEY (discord_user_id) REFERENCES users (discord_id) ON DELETE CASCADE;
+ALTER TABLE admins DROP CONSTRAINT admins_discord_user_id_fkey,
+    ADD CONSTRAINT admins_discord_user_id_fkey FOREIGN KEY (discord_user_id) REFERENCES users (discord_id) ON DELETE CASCADE;
+ALTER TABLE blacklisted_users DROP CONSTRAINT blacklisted_users_discord_user_id_fkey,
+    ADD CONSTRAINT blacklisted_users_discord_user_id_fkey FOREIGN KEY (discord_user_id) REFERENCES users (discord_id) ON DELETE CASCADE;
+ALTER TABLE user_settings DROP CONSTRAINT user_settings_discord_user_id_fkey,
+    ADD CONSTRAINT user_settings_discord_user_id_fkey FOREIGN KEY (discord_user_id) REFERENCES users (discord_id) ON DELETE CASCADE;
+
+ALTER TABLE lobbies DROP CONSTRAINT lobbies_organizer_discord_id_fkey,
+    ADD CONSTRAINT lobbies_organizer_discord_id_fkey FOREIGN KEY (organizer_discord_id) REFERENCES users (discord_id) ON DELETE CASCADE;
+ALTER TABLE lobby_members DROP CONSTRAINT lobby_members_discord_user_id_fkey,
+    ADD CONSTRAINT lobby_members_discord_user_id_fkey FOREIGN KEY (discord_user_id) REFERENCES users (discord_id) ON DELETE CASCADE;
+ALTER TABLE lobby_members DROP CONSTRAINT lobby_members_lobby_id_fkey,
+    ADD CONSTRAINT lobby_members_lobby_id_fkey FOREIGN KEY (lobby_id) REFERENCES lobbies (id) ON DELETE CASCADE;
+ALTER TABLE message_children DROP CONSTRAINT message_children_lobby_id_fkey,
+    ADD CONSTRAINT message_children_lobby_id_fkey FOREIGN KEY (lobby_id) REFERENCES lobbies (id) ON DELETE CASCADE;
+
+ALTER TABLE scheduled_games DROP CONSTRAINT scheduled_games_organizer_discord_id_fkey,
+    ADD CONSTRAINT scheduled_games_organizer_discord_id_fkey FOREIGN KEY (organizer_discord_id) REFERENCES users (discord_id) ON DELETE CASCADE;
+ALTER TABLE scheduled_game_rsvps DROP CONSTRAINT scheduled_game_rsvps_discord_user_id_fkey,
+    ADD CONSTRAINT scheduled_game_rsvps_discord_user_id_fkey FOREIGN KEY (discord_user_id) REFERENCES users (discord_id) ON DELETE CASCADE;
+ALTER TABLE scheduled_game_rsvps DROP CONSTRAINT scheduled_game_rsvps_scheduled_game_id_fkey,
+    ADD CONSTRAINT scheduled_game_rsvps_scheduled_game_id_fkey FOREIGN KEY (scheduled_game_id) REFERENCES scheduled_games (id) ON DELETE CASCADE;
+ALTER TABLE message_children DROP CONSTRAINT message_children_scheduled_game_id_fkey,
+    ADD CONSTRAINT message_children_scheduled_game_id_fkey FOREIGN KEY (scheduled_game_id) REFERENCES scheduled_games (id) ON DELETE CASCADE;
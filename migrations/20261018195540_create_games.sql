@@ -0,0 +1,28 @@
+-- Add migration script here
+-- names and aliases are stored lowercase so they can be matched against what people type
+CREATE TABLE IF NOT EXISTS games (
+    id SERIAL PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL,
+    aliases TEXT[] NOT NULL DEFAULT '{}',
+    default_party_size INTEGER NOT NULL,
+    emoji TEXT,
+    created_at BIGINT NOT NULL,
+    CONSTRAINT UQ_game_name UNIQUE(name)
+);
+
+-- every stack so far was for dota
+INSERT INTO games (name, aliases, default_party_size, created_at)
+    VALUES ('dota', '{dota2}', 5, EXTRACT(EPOCH FROM NOW())::BIGINT)
+    ON CONFLICT DO NOTHING;
+
+-- subscriptions without a game are for every game in the channel, which is what all the existing ones meant
+ALTER TABLE ping_list ADD COLUMN IF NOT EXISTS game_id INTEGER REFERENCES games (id) ON DELETE CASCADE;
+
+-- nothing stopped the same user from subscribing to a channel twice before
+DELETE FROM ping_list a USING ping_list b
+    WHERE a.discord_user_id = b.discord_user_id AND a.discord_channel_id = b.discord_channel_id AND a.id > b.id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS UQ_ping ON ping_list (discord_user_id, discord_channel_id, COALESCE(game_id, 0));
+
+ALTER TABLE lobbies ADD COLUMN IF NOT EXISTS game_id INTEGER REFERENCES games (id) ON DELETE SET NULL;
+ALTER TABLE scheduled_games ADD COLUMN IF NOT EXISTS game_id INTEGER REFERENCES games (id) ON DELETE SET NULL;
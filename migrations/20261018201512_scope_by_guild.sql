@@ -0,0 +1,32 @@
+-- Add migration script here
+-- only discord knows which guild a channel is in, so the bot fills in guild_id for existing rows when it starts.
+-- users and user_settings stay global since they are about the person, not the server
+ALTER TABLE ping_list ADD COLUMN IF NOT EXISTS guild_id BIGINT;
+ALTER TABLE admins ADD COLUMN IF NOT EXISTS guild_id BIGINT;
+ALTER TABLE blacklisted_users ADD COLUMN IF NOT EXISTS guild_id BIGINT;
+ALTER TABLE games ADD COLUMN IF NOT EXISTS guild_id BIGINT;
+ALTER TABLE lobbies ADD COLUMN IF NOT EXISTS guild_id BIGINT;
+ALTER TABLE scheduled_games ADD COLUMN IF NOT EXISTS guild_id BIGINT;
+ALTER TABLE channel_settings ADD COLUMN IF NOT EXISTS guild_id BIGINT;
+ALTER TABLE message_children ADD COLUMN IF NOT EXISTS guild_id BIGINT;
+
+-- being an admin or blacklisted in one guild says nothing about the others
+ALTER TABLE admins DROP CONSTRAINT IF EXISTS UQ_admin,
+    ADD CONSTRAINT UQ_admin UNIQUE(guild_id, discord_user_id);
+ALTER TABLE blacklisted_users DROP CONSTRAINT IF EXISTS UQ_blacklisted_user,
+    ADD CONSTRAINT UQ_blacklisted_user UNIQUE(guild_id, discord_user_id);
+
+-- games without a guild are shared by every guild
+ALTER TABLE games DROP CONSTRAINT IF EXISTS UQ_game_name;
+CREATE UNIQUE INDEX IF NOT EXISTS UQ_game_name ON games (COALESCE(guild_id, 0), name);
+
+CREATE INDEX IF NOT EXISTS IX_ping_list_guild_id ON ping_list (guild_id);
+
+CREATE TABLE IF NOT EXISTS guild_settings (
+    id SERIAL PRIMARY KEY NOT NULL,
+    guild_id BIGINT NOT NULL UNIQUE,
+    -- shown above the list of commands in !help
+    help_text TEXT,
+    -- used by channels that don't have their own cooldown
+    notification_cooldown_seconds BIGINT
+);
@@ -0,0 +1,5 @@
+-- Add migration script here
+-- NULL means the defaults: ! as the prefix and english. a user's locale wins over their guild's
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS command_prefix TEXT;
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS locale TEXT;
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS locale TEXT;
@@ -0,0 +1,15 @@
+-- Add migration script here
+-- timestamps were stored as seconds since the epoch, to_timestamp keeps them as they were
+ALTER TABLE users ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at);
+ALTER TABLE ping_list ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at);
+ALTER TABLE ping_list ALTER COLUMN last_notified TYPE TIMESTAMPTZ USING to_timestamp(last_notified);
+ALTER TABLE games ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at);
+ALTER TABLE lobbies ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at);
+ALTER TABLE lobbies ALTER COLUMN filled_at TYPE TIMESTAMPTZ USING to_timestamp(filled_at);
+ALTER TABLE lobby_members ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING to_timestamp(updated_at);
+ALTER TABLE scheduled_games ALTER COLUMN scheduled_for TYPE TIMESTAMPTZ USING to_timestamp(scheduled_for);
+ALTER TABLE scheduled_games ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at);
+ALTER TABLE scheduled_games ALTER COLUMN reminded_at TYPE TIMESTAMPTZ USING to_timestamp(reminded_at);
+ALTER TABLE scheduled_games ALTER COLUMN started_at TYPE TIMESTAMPTZ USING to_timestamp(started_at);
+ALTER TABLE scheduled_games ALTER COLUMN cancelled_at TYPE TIMESTAMPTZ USING to_timestamp(cancelled_at);
+ALTER TABLE scheduled_game_rsvps ALTER COLUMN updated_at TYPE TIMESTAMPTZ USING to_timestamp(updated_at);
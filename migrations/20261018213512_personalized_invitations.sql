@@ -0,0 +1,13 @@
+-- Add migration script here
+-- off until an admin turns it on, the theme is free text like "an age of empires monk"
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS personalized_invitations BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS invitation_theme TEXT;
+
+-- tokens the language model used for each guild, per utc day, so a guild can't go over its daily budget
+CREATE TABLE IF NOT EXISTS language_model_usage (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    day DATE NOT NULL,
+    tokens BIGINT NOT NULL DEFAULT 0,
+    UNIQUE (guild_id, day)
+);
@@ -0,0 +1,16 @@
+-- Add migration script here
+-- who did what to whom, kept when the users involved unregister so it can't be erased by the person it is about
+CREATE TABLE IF NOT EXISTS audit_log (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    discord_channel_id BIGINT,
+    actor_discord_id BIGINT NOT NULL,
+    action TEXT NOT NULL,
+    target_discord_id BIGINT,
+    details TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS audit_log_guild_id_created_at ON audit_log (guild_id, created_at DESC);
+
+-- where every new entry is also posted, e.g. a #mod-log channel
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS mod_log_channel_id BIGINT;
@@ -0,0 +1,15 @@
+-- Add migration script here
+-- every notification dm, so nobody gets more than a few per hour however many channels they are subscribed in
+CREATE TABLE IF NOT EXISTS notification_dms (
+    id SERIAL PRIMARY KEY,
+    recipient_discord_id BIGINT NOT NULL REFERENCES users (discord_id) ON DELETE CASCADE,
+    guild_id BIGINT NOT NULL,
+    discord_channel_id BIGINT NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS notification_dms_recipient_discord_id_sent_at ON notification_dms (recipient_discord_id, sent_at);
+
+-- how many stacks someone can start per window in the guild, the default applies when they're NULL
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS call_limit INTEGER;
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS call_window_seconds BIGINT;
+CREATE INDEX IF NOT EXISTS lobbies_guild_id_organizer_discord_id_created_at ON lobbies (guild_id, organizer_discord_id, created_at);
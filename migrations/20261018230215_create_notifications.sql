@@ -0,0 +1,21 @@
+-- Add migration script here
+-- every stack and scheduled game people were notified about, kept after the lobby or game is gone
+CREATE TABLE IF NOT EXISTS notifications (
+    id SERIAL PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    discord_channel_id BIGINT NOT NULL,
+    organizer_discord_id BIGINT NOT NULL REFERENCES users (discord_id) ON DELETE CASCADE,
+    lobby_id INTEGER,
+    scheduled_game_id INTEGER,
+    description TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS notifications_guild_id_created_at ON notifications (guild_id, created_at);
+CREATE INDEX IF NOT EXISTS notifications_lobby_id ON notifications (lobby_id);
+CREATE INDEX IF NOT EXISTS notifications_scheduled_game_id ON notifications (scheduled_game_id);
+
+-- the dms sent for it and how each recipient answered, NULL for dms sent before the history was kept
+ALTER TABLE notification_dms ADD COLUMN IF NOT EXISTS notification_id INTEGER REFERENCES notifications (id) ON DELETE CASCADE;
+ALTER TABLE notification_dms ADD COLUMN IF NOT EXISTS responded_at TIMESTAMPTZ;
+ALTER TABLE notification_dms ADD COLUMN IF NOT EXISTS accepted BOOLEAN;
+CREATE INDEX IF NOT EXISTS notification_dms_notification_id ON notification_dms (notification_id);
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- scheduled games count towards the call limit along with stacks
+CREATE INDEX IF NOT EXISTS scheduled_games_guild_id_organizer_discord_id_created_at ON scheduled_games (guild_id, organizer_discord_id, created_at);
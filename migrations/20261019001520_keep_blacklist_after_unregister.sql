@@ -0,0 +1,3 @@
+-- Add migration script here
+-- blacklisted users can unregister, the blacklist keeps them from registering again
+ALTER TABLE blacklisted_users DROP CONSTRAINT IF EXISTS blacklisted_users_discord_user_id_fkey;
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- off until an admin turns it on, every message in a channel with subscribers is sent to the language model
+ALTER TABLE guild_settings ADD COLUMN IF NOT EXISTS game_call_offers BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,98 @@
+use serenity::all::{ChannelId, CreateAllowedMentions, CreateMessage, UserId};
+use tracing::error;
+use crate::chat::ChatBackend;
+use crate::commands::Invocation;
+use crate::messages::{get_guild_locale, AuditLine, Messages};
+use crate::store::Store;
+use crate::structs::AuditEntry;
+
+pub(crate) const DEFAULT_AUDIT_ENTRIES: i64 = 10;
+// a reply has to fit in one discord message
+pub(crate) const MAX_AUDIT_ENTRIES: i64 = 25;
+// the help message can be long, the log only needs to show what it was changed to
+const MAX_DETAILS_LENGTH: usize = 200;
+
+/// What an admin did, saved in the audit log by its code
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AuditAction {
+    AdminAdded,
+    AdminRemoved,
+    Blacklisted,
+    Unblacklisted,
+    // the details are the setting and its new value, e.g. "prefix ?"
+    SettingChanged,
+    // the details are the name of the game
+    GameAdded,
+    GameRemoved,
+}
+
+impl AuditAction {
+    const ALL: [AuditAction; 7] = [AuditAction::AdminAdded, AuditAction::AdminRemoved, AuditAction::Blacklisted, AuditAction::Unblacklisted, AuditAction::SettingChanged, AuditAction::GameAdded, AuditAction::GameRemoved];
+
+    pub(crate) fn code(self) -> &'static str {
+        match self {
+            AuditAction::AdminAdded => "admin-added",
+            AuditAction::AdminRemoved => "admin-removed",
+            AuditAction::Blacklisted => "blacklisted",
+            AuditAction::Unblacklisted => "unblacklisted",
+            AuditAction::SettingChanged => "setting-changed",
+            AuditAction::GameAdded => "game-added",
+            AuditAction::GameRemoved => "game-removed",
+        }
+    }
+
+    pub(crate) fn parse(code: &str) -> Option<Self> {
+        AuditAction::ALL.into_iter().find(|action| action.code() == code)
+    }
+}
+
+/// Saves what the author of the command did and posts it in the guild's mod log channel if it has one. The action
+/// already happened, so failing to do either is only logged
+pub(crate) async fn record(chat: &dyn ChatBackend, database: &dyn Store, invocation: &Invocation<'_>, action: AuditAction, target: Option<UserId>, details: Option<&str>) {
+    let Some(guild_id) = invocation.guild_id.map(|guild_id| guild_id.get()) else {
+        return;
+    };
+    let details = details.map(|details| details.chars().take(MAX_DETAILS_LENGTH).collect::<String>());
+    let entry = match database.create_audit_entry(guild_id, Some(invocation.channel_id.get()), invocation.author.id.get(), action.code(), target.map(UserId::get), details.as_deref()).await {
+        Ok(entry) => entry,
+        Err(error) => {
+            error!("Unable to save audit log entry {} by {} in guild {}: {:?}", action.code(), invocation.author.id.get(), guild_id, error);
+            return;
+        }
+    };
+    let mod_log_channel_id = match database.get_guild_settings(guild_id).await {
+        Ok(settings) => settings.and_then(|settings| settings.mod_log_channel_id),
+        Err(error) => {
+            error!("Unable to get settings for guild {}: {:?}", guild_id, error);
+            None
+        }
+    };
+    let Some(mod_log_channel_id) = mod_log_channel_id else {
+        return;
+    };
+    let messages = get_guild_locale(database, Some(guild_id)).await.messages();
+    let Some(line) = describe(messages, &entry) else {
+        return;
+    };
+    // the log is a record, nobody needs to be pinged by it
+    let message = CreateMessage::new().content(line).allowed_mentions(CreateAllowedMentions::new());
+    if let Err(e) = chat.send_message(ChannelId::new(mod_log_channel_id as u64), message).await {
+        error!("Error sending message to mod log channel {}: {:?}", mod_log_channel_id, e);
+    }
+}
+
+/// One line of the log, e.g. "<t:...:f> <@1> made <@2> an admin in <#3>", None for an action this version doesn't know
+pub(crate) fn describe(messages: &dyn Messages, entry: &AuditEntry) -> Option<String> {
+    let action = AuditAction::parse(&entry.action)?;
+    let target = entry.target_discord_id.map(|target_discord_id| format!("<@{target_discord_id}>"));
+    let channel = entry.discord_channel_id.map(|discord_channel_id| format!("<#{discord_channel_id}>"));
+    let line = AuditLine {
+        when: &format!("<t:{}:f>", entry.created_at.timestamp()),
+        actor: &format!("<@{}>", entry.actor_discord_id),
+        action,
+        target: target.as_deref(),
+        details: entry.details.as_deref(),
+        channel: channel.as_deref()
+    };
+    Some(messages.audit_entry(&line))
+}
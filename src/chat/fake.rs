@@ -0,0 +1,282 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use serenity::all::{ChannelId, CreateInteractionResponse, CreateInteractionResponseFollowup, CreateMessage, EditInteractionResponse, EditMessage, GuildId, InteractionId, Message, MessageId, ModelError, RoleId, UserId};
+use serenity::async_trait;
+use serenity::prelude::*;
+use crate::chat::ChatBackend;
+use crate::constants;
+use crate::guilds::CommandPrefixes;
+use crate::permissions::Permissions;
+
+/// A message the bot sent, with its content as it is after every edit
+#[derive(Clone, Debug)]
+pub(crate) struct SentMessage {
+    pub(crate) id: MessageId,
+    pub(crate) channel_id: ChannelId,
+    // set for dms
+    pub(crate) recipient: Option<UserId>,
+    pub(crate) content: String,
+    pub(crate) replied_to: Option<MessageId>,
+    pub(crate) edits: usize,
+}
+
+#[derive(Default)]
+struct FakeState {
+    next_id: u64,
+    sent: Vec<SentMessage>,
+    // what the tests said to the bot, it can fetch those like on discord
+    received: Vec<Message>,
+    dm_channels: HashMap<UserId, ChannelId>,
+    channel_names: HashMap<ChannelId, String>,
+    user_names: HashMap<UserId, String>,
+    // users who don't accept dms from the bot
+    closed_dms: HashSet<UserId>,
+    member_roles: HashMap<UserId, Vec<RoleId>>,
+    // what the bot answered to interactions, only the user who clicked or used the command sees it
+    interaction_replies: Vec<String>,
+}
+
+impl FakeState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+/// Keeps everything the bot sends in memory instead of talking to discord
+pub(crate) struct FakeChat {
+    data: Arc<RwLock<TypeMap>>,
+    state: Mutex<FakeState>,
+}
+
+/// The builders don't expose what is in them, but they serialize to the json discord would get
+fn field(builder: &impl Serialize, name: &str) -> Option<serde_json::Value> {
+    serde_json::to_value(builder).ok().and_then(|value| value.get(name).cloned())
+}
+
+fn content(builder: &impl Serialize) -> Option<String> {
+    field(builder, "content").and_then(|content| content.as_str().map(str::to_string))
+}
+
+/// Discord refuses messages over its length limit, so the tests notice when the bot would send one
+fn check_length(content: &str) -> Result<(), ModelError> {
+    let length = content.chars().count();
+    if length > constants::MAX_MESSAGE_LENGTH {
+        return Err(ModelError::MessageTooLong(length - constants::MAX_MESSAGE_LENGTH));
+    }
+    Ok(())
+}
+
+fn replied_to(builder: &CreateMessage) -> Option<MessageId> {
+    let message_id = field(builder, "message_reference")?.get("message_id")?.clone();
+    serde_json::from_value(message_id).ok()
+}
+
+impl FakeChat {
+    pub(crate) fn new(permissions: Permissions) -> Self {
+        let mut data = TypeMap::new();
+        data.insert::<Permissions>(Arc::new(permissions));
+        data.insert::<CommandPrefixes>(Arc::default());
+        FakeChat {
+            data: Arc::new(RwLock::new(data)),
+            // ids start high so they don't collide with the ids tests pick for users, guilds and channels
+            state: Mutex::new(FakeState { next_id: 1_000_000, ..FakeState::default() }),
+        }
+    }
+
+    pub(crate) fn set_channel_name(&self, channel_id: ChannelId, name: &str) {
+        self.state.lock().unwrap().channel_names.insert(channel_id, name.to_string());
+    }
+
+    pub(crate) fn set_user_name(&self, user_id: UserId, name: &str) {
+        self.state.lock().unwrap().user_names.insert(user_id, name.to_string());
+    }
+
+    /// Replaces the owner and admin roles the bot was started with
+    pub(crate) async fn set_permissions(&self, permissions: Permissions) {
+        self.data.write().await.insert::<Permissions>(Arc::new(permissions));
+    }
+
+    pub(crate) fn give_role(&self, user_id: UserId, role_id: RoleId) {
+        self.state.lock().unwrap().member_roles.entry(user_id).or_default().push(role_id);
+    }
+
+    /// Dms to the user fail from now on, like when they only accept dms from friends
+    pub(crate) fn close_dms(&self, user_id: UserId) {
+        self.state.lock().unwrap().closed_dms.insert(user_id);
+    }
+
+    pub(crate) fn interaction_replies(&self) -> Vec<String> {
+        self.state.lock().unwrap().interaction_replies.clone()
+    }
+
+    fn record_interaction_reply(&self, content: Option<String>) -> Result<(), ModelError> {
+        if let Some(content) = content {
+            check_length(&content)?;
+            self.state.lock().unwrap().interaction_replies.push(content);
+        }
+        Ok(())
+    }
+
+    /// For messages the tests send to the bot
+    pub(crate) fn next_message_id(&self) -> MessageId {
+        MessageId::new(self.state.lock().unwrap().next_id())
+    }
+
+    pub(crate) fn receive(&self, message: &Message) {
+        self.state.lock().unwrap().received.push(message.clone());
+    }
+
+    /// Everything sent so far, oldest first
+    pub(crate) fn sent(&self) -> Vec<SentMessage> {
+        self.state.lock().unwrap().sent.clone()
+    }
+
+    pub(crate) fn sent_in(&self, channel_id: ChannelId) -> Vec<SentMessage> {
+        self.sent().into_iter().filter(|message| message.channel_id == channel_id && message.recipient.is_none()).collect()
+    }
+
+    pub(crate) fn dms_to(&self, user_id: UserId) -> Vec<SentMessage> {
+        self.sent().into_iter().filter(|message| message.recipient == Some(user_id)).collect()
+    }
+
+    pub(crate) fn message(&self, message_id: MessageId) -> Option<SentMessage> {
+        self.sent().into_iter().find(|message| message.id == message_id)
+    }
+
+    fn record(&self, channel_id: ChannelId, recipient: Option<UserId>, content: String, replied_to: Option<MessageId>) -> Result<Message, ModelError> {
+        check_length(&content)?;
+        let mut state = self.state.lock().unwrap();
+        let id = MessageId::new(state.next_id());
+        state.sent.push(SentMessage { id, channel_id, recipient, content: content.clone(), replied_to, edits: 0 });
+        let mut message = Message::default();
+        message.id = id;
+        message.channel_id = channel_id;
+        message.content = content;
+        Ok(message)
+    }
+
+    fn dm_channel_for(&self, user_id: UserId) -> ChannelId {
+        let mut state = self.state.lock().unwrap();
+        if let Some(channel_id) = state.dm_channels.get(&user_id) {
+            return *channel_id;
+        }
+        let channel_id = ChannelId::new(state.next_id());
+        state.dm_channels.insert(user_id, channel_id);
+        channel_id
+    }
+}
+
+#[async_trait]
+impl ChatBackend for FakeChat {
+    fn data(&self) -> &Arc<RwLock<TypeMap>> {
+        &self.data
+    }
+
+    async fn send_message(&self, channel_id: ChannelId, builder: CreateMessage) -> serenity::Result<Message> {
+        Ok(self.record(channel_id, None, content(&builder).unwrap_or_default(), replied_to(&builder))?)
+    }
+
+    async fn edit_message(&self, channel_id: ChannelId, message_id: MessageId, builder: EditMessage) -> serenity::Result<Message> {
+        let mut state = self.state.lock().unwrap();
+        let Some(sent) = state.sent.iter_mut().find(|sent| sent.id == message_id && sent.channel_id == channel_id) else {
+            return Err(serenity::Error::Other("unknown message"));
+        };
+        if let Some(content) = content(&builder) {
+            check_length(&content)?;
+            sent.content = content;
+        }
+        sent.edits += 1;
+        let mut message = Message::default();
+        message.id = message_id;
+        message.channel_id = channel_id;
+        message.content = sent.content.clone();
+        Ok(message)
+    }
+
+    async fn send_dm(&self, user_id: UserId, builder: CreateMessage) -> serenity::Result<Message> {
+        if self.state.lock().unwrap().closed_dms.contains(&user_id) {
+            return Err(serenity::Error::Other("Cannot send messages to this user"));
+        }
+        let channel_id = self.dm_channel_for(user_id);
+        Ok(self.record(channel_id, Some(user_id), content(&builder).unwrap_or_default(), replied_to(&builder))?)
+    }
+
+    async fn dm_channel(&self, user_id: UserId) -> serenity::Result<ChannelId> {
+        Ok(self.dm_channel_for(user_id))
+    }
+
+    async fn fetch_message(&self, channel_id: ChannelId, message_id: MessageId) -> serenity::Result<Message> {
+        let received = self.state.lock().unwrap().received.iter().find(|message| message.id == message_id && message.channel_id == channel_id).cloned();
+        if let Some(message) = received {
+            return Ok(message);
+        }
+        let sent = self.message(message_id).filter(|sent| sent.channel_id == channel_id).ok_or(serenity::Error::Other("unknown message"))?;
+        let mut message = Message::default();
+        message.id = sent.id;
+        message.channel_id = sent.channel_id;
+        message.content = sent.content;
+        Ok(message)
+    }
+
+    async fn reply(&self, message: &Message, content: String) -> serenity::Result<Message> {
+        let recipient = self.state.lock().unwrap().dm_channels.iter().find(|(_, channel_id)| **channel_id == message.channel_id).map(|(user_id, _)| *user_id);
+        Ok(self.record(message.channel_id, recipient, content, Some(message.id))?)
+    }
+
+    async fn channel_name(&self, channel_id: ChannelId) -> serenity::Result<String> {
+        Ok(self.state.lock().unwrap().channel_names.get(&channel_id).cloned().unwrap_or_else(|| format!("channel-{}", channel_id.get())))
+    }
+
+    async fn channel_guild(&self, _channel_id: ChannelId) -> serenity::Result<Option<GuildId>> {
+        Ok(None)
+    }
+
+    async fn create_thread(&self, _channel_id: ChannelId, message_id: MessageId, name: String) -> serenity::Result<ChannelId> {
+        // like on discord, the thread has the id of the message it was started from
+        let thread_id = ChannelId::new(message_id.get());
+        let mut state = self.state.lock().unwrap();
+        if state.channel_names.contains_key(&thread_id) {
+            return Err(serenity::Error::Other("thread already exists"));
+        }
+        state.channel_names.insert(thread_id, name);
+        Ok(thread_id)
+    }
+
+    async fn user_name(&self, user_id: UserId) -> serenity::Result<String> {
+        self.state.lock().unwrap().user_names.get(&user_id).cloned().ok_or(serenity::Error::Other("unknown user"))
+    }
+
+    async fn guild_name(&self, guild_id: GuildId) -> serenity::Result<String> {
+        Ok(format!("guild-{}", guild_id.get()))
+    }
+
+    async fn guild_channel_names(&self, _guild_id: GuildId) -> serenity::Result<HashMap<ChannelId, String>> {
+        Ok(self.state.lock().unwrap().channel_names.clone())
+    }
+
+    async fn member_roles(&self, _guild_id: GuildId, user_id: UserId) -> serenity::Result<Vec<RoleId>> {
+        Ok(self.state.lock().unwrap().member_roles.get(&user_id).cloned().unwrap_or_default())
+    }
+
+    async fn role_names(&self, _guild_id: GuildId) -> serenity::Result<HashMap<RoleId, String>> {
+        Ok(HashMap::new())
+    }
+
+    async fn respond_to_interaction(&self, _interaction_id: InteractionId, _token: &str, response: CreateInteractionResponse) -> serenity::Result<()> {
+        Ok(self.record_interaction_reply(field(&response, "data").as_ref().and_then(content))?)
+    }
+
+    async fn edit_interaction_response(&self, _token: &str, builder: EditInteractionResponse) -> serenity::Result<()> {
+        Ok(self.record_interaction_reply(content(&builder))?)
+    }
+
+    async fn send_followup(&self, _token: &str, builder: CreateInteractionResponseFollowup) -> serenity::Result<()> {
+        Ok(self.record_interaction_reply(content(&builder))?)
+    }
+
+    async fn delete_interaction_response(&self, _token: &str) -> serenity::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+pub(crate) mod fake;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use serenity::all::{Builder, Channel, ChannelId, CreateInteractionResponse, CreateInteractionResponseFollowup, CreateMessage, CreateThread, EditInteractionResponse, EditMessage, GuildId, Http, InteractionId, Message, MessageId, RoleId, UserId};
+use serenity::async_trait;
+use serenity::prelude::*;
+
+/// Everything the bot sends to or asks of discord, so the handlers can be run against a fake in tests
+#[async_trait]
+pub(crate) trait ChatBackend: Send + Sync {
+    /// Shared state of the bot, e.g. the permissions and the cached command prefixes
+    fn data(&self) -> &Arc<RwLock<TypeMap>>;
+
+    async fn send_message(&self, channel_id: ChannelId, builder: CreateMessage) -> serenity::Result<Message>;
+
+    async fn edit_message(&self, channel_id: ChannelId, message_id: MessageId, builder: EditMessage) -> serenity::Result<Message>;
+
+    async fn send_dm(&self, user_id: UserId, builder: CreateMessage) -> serenity::Result<Message>;
+
+    /// Opens the dm channel with the user if there isn't one yet
+    async fn dm_channel(&self, user_id: UserId) -> serenity::Result<ChannelId>;
+
+    async fn fetch_message(&self, channel_id: ChannelId, message_id: MessageId) -> serenity::Result<Message>;
+
+    /// Discord's inline reply, without pinging the author of the message
+    async fn reply(&self, message: &Message, content: String) -> serenity::Result<Message>;
+
+    async fn channel_name(&self, channel_id: ChannelId) -> serenity::Result<String>;
+
+    /// None for dm channels
+    async fn channel_guild(&self, channel_id: ChannelId) -> serenity::Result<Option<GuildId>>;
+
+    /// Starts a thread from the message and returns its id
+    async fn create_thread(&self, channel_id: ChannelId, message_id: MessageId, name: String) -> serenity::Result<ChannelId>;
+
+    async fn user_name(&self, user_id: UserId) -> serenity::Result<String>;
+
+    async fn guild_name(&self, guild_id: GuildId) -> serenity::Result<String>;
+
+    async fn guild_channel_names(&self, guild_id: GuildId) -> serenity::Result<HashMap<ChannelId, String>>;
+
+    async fn member_roles(&self, guild_id: GuildId, user_id: UserId) -> serenity::Result<Vec<RoleId>>;
+
+    async fn role_names(&self, guild_id: GuildId) -> serenity::Result<HashMap<RoleId, String>>;
+
+    async fn respond_to_interaction(&self, interaction_id: InteractionId, token: &str, response: CreateInteractionResponse) -> serenity::Result<()>;
+
+    async fn edit_interaction_response(&self, token: &str, builder: EditInteractionResponse) -> serenity::Result<()>;
+
+    async fn send_followup(&self, token: &str, builder: CreateInteractionResponseFollowup) -> serenity::Result<()>;
+
+    async fn delete_interaction_response(&self, token: &str) -> serenity::Result<()>;
+}
+
+/// Talks to discord through serenity's http client
+#[derive(Clone)]
+pub(crate) struct SerenityBackend {
+    http: Arc<Http>,
+    data: Arc<RwLock<TypeMap>>,
+}
+
+impl SerenityBackend {
+    pub(crate) fn new(ctx: &Context) -> Self {
+        SerenityBackend {
+            http: ctx.http.clone(),
+            data: ctx.data.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl ChatBackend for SerenityBackend {
+    fn data(&self) -> &Arc<RwLock<TypeMap>> {
+        &self.data
+    }
+
+    async fn send_message(&self, channel_id: ChannelId, builder: CreateMessage) -> serenity::Result<Message> {
+        channel_id.send_message(&self.http, builder).await
+    }
+
+    async fn edit_message(&self, channel_id: ChannelId, message_id: MessageId, builder: EditMessage) -> serenity::Result<Message> {
+        channel_id.edit_message(&self.http, message_id, builder).await
+    }
+
+    async fn send_dm(&self, user_id: UserId, builder: CreateMessage) -> serenity::Result<Message> {
+        user_id.direct_message(&self.http, builder).await
+    }
+
+    async fn dm_channel(&self, user_id: UserId) -> serenity::Result<ChannelId> {
+        user_id.create_dm_channel(&self.http).await.map(|dm_channel| dm_channel.id)
+    }
+
+    async fn fetch_message(&self, channel_id: ChannelId, message_id: MessageId) -> serenity::Result<Message> {
+        self.http.get_message(channel_id, message_id).await
+    }
+
+    async fn reply(&self, message: &Message, content: String) -> serenity::Result<Message> {
+        message.reply(&self.http, content).await
+    }
+
+    async fn channel_name(&self, channel_id: ChannelId) -> serenity::Result<String> {
+        channel_id.name(&self.http).await
+    }
+
+    async fn channel_guild(&self, channel_id: ChannelId) -> serenity::Result<Option<GuildId>> {
+        match channel_id.to_channel(&self.http).await? {
+            Channel::Guild(channel) => Ok(Some(channel.guild_id)),
+            _ => Ok(None)
+        }
+    }
+
+    async fn create_thread(&self, channel_id: ChannelId, message_id: MessageId, name: String) -> serenity::Result<ChannelId> {
+        channel_id.create_thread_from_message(&self.http, message_id, CreateThread::new(name)).await.map(|thread| thread.id)
+    }
+
+    async fn user_name(&self, user_id: UserId) -> serenity::Result<String> {
+        user_id.to_user(&self.http).await.map(|user| user.name)
+    }
+
+    async fn guild_name(&self, guild_id: GuildId) -> serenity::Result<String> {
+        guild_id.to_partial_guild(&self.http).await.map(|guild| guild.name)
+    }
+
+    async fn guild_channel_names(&self, guild_id: GuildId) -> serenity::Result<HashMap<ChannelId, String>> {
+        let channels = guild_id.channels(&self.http).await?;
+        Ok(channels.into_iter().map(|(channel_id, channel)| (channel_id, channel.name)).collect())
+    }
+
+    async fn member_roles(&self, guild_id: GuildId, user_id: UserId) -> serenity::Result<Vec<RoleId>> {
+        guild_id.member(&self.http, user_id).await.map(|member| member.roles)
+    }
+
+    async fn role_names(&self, guild_id: GuildId) -> serenity::Result<HashMap<RoleId, String>> {
+        let roles = guild_id.roles(&self.http).await?;
+        Ok(roles.into_iter().map(|(role_id, role)| (role_id, role.name)).collect())
+    }
+
+    async fn respond_to_interaction(&self, interaction_id: InteractionId, token: &str, response: CreateInteractionResponse) -> serenity::Result<()> {
+        response.execute(&self.http, (interaction_id, token)).await
+    }
+
+    async fn edit_interaction_response(&self, token: &str, builder: EditInteractionResponse) -> serenity::Result<()> {
+        builder.execute(&self.http, token).await.map(|_| ())
+    }
+
+    async fn send_followup(&self, token: &str, builder: CreateInteractionResponseFollowup) -> serenity::Result<()> {
+        builder.execute(&self.http, (None, token)).await.map(|_| ())
+    }
+
+    async fn delete_interaction_response(&self, token: &str) -> serenity::Result<()> {
+        self.http.delete_original_interaction_response(token).await
+    }
+}
@@ -0,0 +1,1239 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use serenity::all::{ChannelId, CommandInteraction, ComponentInteraction, CreateActionRow, CreateAllowedMentions, CreateInteractionResponseFollowup, CreateMessage, EditInteractionResponse, GuildId, Message, ResolvedOption, ResolvedValue, RoleId, User};
+use regex::Regex;
+use tracing::{error, info};
+use crate::chat::ChatBackend;
+use crate::{audit, constants, direct_messages, game_calls, games, invitations, lobbies, scheduled_games, slash_commands, stats};
+use crate::lobbies::CallLimit;
+use crate::audit::AuditAction;
+use crate::game_calls::GameCalls;
+use crate::invitations::{InvitationRequest, Invitations};
+use crate::notifications::{get_notification_cooldown_for_channel, notify_subscribers, NotificationParent};
+use crate::errors::WololoError;
+use crate::guilds::{cache_command_prefix, get_command_prefix};
+use crate::messages::{get_guild_locale, get_locale, with_prefix, Commands, Locale, Messages, Notification, ServerSettings, Stats};
+use crate::permissions::{get_access_level, get_permissions, AccessLevel};
+use crate::parsing::{format_duration, format_minutes_after_midnight, minutes_after_midnight, parse_channel_mention, parse_duration, parse_time_window, parse_timezone, parse_when, split_first_word, split_message};
+use crate::scheduled_games::discord_timestamp;
+use crate::subscriptions::{self, describe_subscriptions};
+use crate::store::Store;
+use crate::structs::{Game, ScheduledGame, UserSettings, WololoUser};
+
+enum Source<'a> {
+    Message,
+    // slash command replies are ephemeral, the first one edits the deferred response and the rest are followups. Holds
+    // the interaction's token
+    Interaction(&'a str, AtomicBool),
+}
+
+/// A command invoked either with a prefixed message or with a slash command
+pub(crate) struct Invocation<'a> {
+    pub(crate) author: &'a User,
+    pub(crate) channel_id: ChannelId,
+    pub(crate) guild_id: Option<GuildId>,
+    // the author's roles in the guild, if discord sent them along
+    member_roles: Option<Vec<RoleId>>,
+    pub(crate) rest_of_command: Option<String>,
+    pub(crate) mentions: Vec<User>,
+    // the guild's command prefix, commands mentioned in replies use it
+    pub(crate) prefix: String,
+    pub(crate) locale: Locale,
+    source: Source<'a>,
+}
+
+impl<'a> Invocation<'a> {
+    pub(crate) fn from_message(msg: &'a Message, rest_of_command: Option<&str>, prefix: String, locale: Locale) -> Self {
+        Invocation {
+            author: &msg.author,
+            channel_id: msg.channel_id,
+            guild_id: msg.guild_id,
+            member_roles: msg.member.as_ref().map(|member| member.roles.clone()),
+            rest_of_command: rest_of_command.map(str::to_string),
+            mentions: msg.mentions.clone(),
+            prefix,
+            locale,
+            source: Source::Message,
+        }
+    }
+
+    /// String options become the rest of the command (in the order they are defined), integer options become
+    /// `name=value` words and user options become mentions, so slash commands can be handled exactly like their `!`
+    /// equivalents
+    pub(crate) fn from_interaction(interaction: &'a CommandInteraction, prefix: String, locale: Locale) -> Self {
+        let mut words = Vec::new();
+        let mut mentions = Vec::new();
+        collect_options(interaction.data.options(), &mut words, &mut mentions);
+        Invocation {
+            author: &interaction.user,
+            channel_id: interaction.channel_id,
+            guild_id: interaction.guild_id,
+            member_roles: interaction.member.as_ref().map(|member| member.roles.clone()),
+            rest_of_command: Some(words.join(" ")),
+            mentions,
+            prefix,
+            locale,
+            source: Source::Interaction(&interaction.token, AtomicBool::new(false)),
+        }
+    }
+
+    /// A button that runs a command for whoever clicked it, the first reply replaces the message the button was on
+    pub(crate) fn from_component(interaction: &'a ComponentInteraction, rest_of_command: &str, prefix: String, locale: Locale) -> Self {
+        Invocation {
+            author: &interaction.user,
+            channel_id: interaction.channel_id,
+            guild_id: interaction.guild_id,
+            member_roles: interaction.member.as_ref().map(|member| member.roles.clone()),
+            rest_of_command: Some(rest_of_command.to_string()),
+            mentions: Vec::new(),
+            prefix,
+            locale,
+            source: Source::Interaction(&interaction.token, AtomicBool::new(false)),
+        }
+    }
+
+    pub(crate) fn messages(&self) -> &'static dyn Messages {
+        self.locale.messages()
+    }
+
+    /// The text command the way it has to be typed where the command was used, e.g. "?help"
+    pub(crate) fn command(&self, command: &str) -> String {
+        with_prefix(command, &self.prefix)
+    }
+
+    /// Replies with the author's name in front, which is how the bot starts every answer to a command
+    pub(crate) async fn reply_to_author(&self, chat: &dyn ChatBackend, content: impl Into<String>) {
+        self.reply(chat, format!("@{} {}", self.author.name, content.into())).await;
+    }
+
+    pub(crate) async fn reply(&self, chat: &dyn ChatBackend, content: impl Into<String>) {
+        self.reply_with_components(chat, content, Vec::new()).await;
+    }
+
+    pub(crate) async fn reply_with_components(&self, chat: &dyn ChatBackend, content: impl Into<String>, components: Vec<CreateActionRow>) {
+        let content = content.into();
+        let result = match &self.source {
+            Source::Message => chat.send_message(self.channel_id, CreateMessage::new().content(content).components(components).allowed_mentions(CreateAllowedMentions::new())).await.map(|_| ()),
+            Source::Interaction(token, responded) => {
+                if responded.swap(true, Ordering::SeqCst) {
+                    chat.send_followup(token, CreateInteractionResponseFollowup::new().content(content).components(components).ephemeral(true)).await
+                }
+                else {
+                    chat.edit_interaction_response(token, EditInteractionResponse::new().content(content).components(components)).await
+                }
+            }
+        };
+        if let Err(e) = result {
+            error!("Error sending message: {:?}", e);
+        }
+    }
+
+    /// Logs the error, the user only needs to know it wasn't their fault
+    pub(crate) async fn reply_database_trouble(&self, chat: &dyn ChatBackend, error: WololoError) {
+        error!("Database error while handling a command from user {}: {}", self.author.id.get(), error);
+        self.reply_to_author(chat, self.messages().database_trouble()).await;
+    }
+
+    pub(crate) async fn access_level(&self, chat: &dyn ChatBackend, database: &dyn Store) -> Result<AccessLevel, WololoError> {
+        get_access_level(chat, database, self.guild_id, self.author.id, self.member_roles.as_deref()).await
+    }
+
+    /// Access level of a mentioned user in the guild the command was used in
+    pub(crate) async fn access_level_of(&self, chat: &dyn ChatBackend, database: &dyn Store, user: &User) -> Result<AccessLevel, WololoError> {
+        get_access_level(chat, database, self.guild_id, user.id, None).await
+    }
+
+    /// Replies and returns false unless the author is registered
+    pub(crate) async fn require_registration(&self, chat: &dyn ChatBackend, database: &dyn Store) -> bool {
+        match database.get_user(self.author.id.get()).await {
+            Ok(_) => true,
+            Err(WololoError::NotFound) => {
+                self.reply_to_author(chat, self.messages().not_registered(&self.command(constants::REGISTER_CMD))).await;
+                false
+            }
+            Err(error) => {
+                self.reply_database_trouble(chat, error).await;
+                false
+            }
+        }
+    }
+
+    /// Replies and returns false unless the author is an admin
+    pub(crate) async fn require_admin(&self, chat: &dyn ChatBackend, database: &dyn Store) -> bool {
+        match self.access_level(chat, database).await {
+            Ok(access_level) if access_level.is_admin() => true,
+            Ok(_) => {
+                self.reply_to_author(chat, self.messages().not_an_admin()).await;
+                false
+            }
+            Err(error) => {
+                self.reply_database_trouble(chat, error).await;
+                false
+            }
+        }
+    }
+
+    /// Replies and returns false when the author started as many stacks or scheduled as many games in the guild as its
+    /// limit allows lately. Admins have no limit
+    pub(crate) async fn require_under_call_limit(&self, chat: &dyn ChatBackend, database: &dyn Store) -> bool {
+        let guild_discord_id = self.guild_id.map(GuildId::get).unwrap_or_default();
+        let user_discord_id = self.author.id.get();
+        let wait = match self.access_level(chat, database).await {
+            Ok(access_level) if access_level.is_admin() => return true,
+            Ok(_) => match CallLimit::for_guild(database, guild_discord_id).await {
+                Ok(call_limit) => call_limit.wait_seconds(database, guild_discord_id, user_discord_id, chrono::offset::Utc::now()).await.map(|wait_seconds| wait_seconds.map(|wait_seconds| (call_limit, wait_seconds))),
+                Err(error) => Err(error)
+            },
+            Err(error) => Err(error)
+        };
+        match wait {
+            Ok(None) => true,
+            Ok(Some((call_limit, wait_seconds))) => {
+                info!("Discord user {} started {} stacks in guild {} in the last {}s", user_discord_id, call_limit.limit, guild_discord_id, call_limit.window_seconds);
+                self.reply_to_author(chat, self.messages().call_limit_reached(call_limit.limit, &format_duration(call_limit.window_seconds), &format_duration(wait_seconds))).await;
+                false
+            }
+            Err(error) => {
+                self.reply_database_trouble(chat, error).await;
+                false
+            }
+        }
+    }
+
+    /// The game named by the first word of the command, `Some(None)` when no game was named. Replies and returns None
+    /// when the game doesn't exist
+    pub(crate) async fn require_known_game(&self, chat: &dyn ChatBackend, database: &dyn Store) -> Option<Option<Game>> {
+        let (name, _) = split_first_word(self.rest_of_command.as_deref().unwrap_or(""));
+        if name.is_empty() {
+            return Some(None);
+        }
+        let guild_discord_id = self.guild_id.map(GuildId::get).unwrap_or_default();
+        match database.get_game_by_name(guild_discord_id, name).await {
+            Ok(game) => return Some(Some(game)),
+            Err(WololoError::NotFound) => match games::known_games(self.messages(), database, guild_discord_id).await {
+                Ok(known_games) => self.reply_to_author(chat, format!("{}. {}", self.messages().unknown_game(name), known_games)).await,
+                Err(error) => self.reply_database_trouble(chat, error).await
+            },
+            Err(error) => self.reply_database_trouble(chat, error).await
+        }
+        None
+    }
+
+    /// Removes the "thinking..." placeholder of a slash command that never got a reply
+    pub(crate) async fn finish(&self, chat: &dyn ChatBackend) {
+        if let Source::Interaction(token, responded) = &self.source {
+            if !responded.load(Ordering::SeqCst) {
+                if let Err(e) = chat.delete_interaction_response(token).await {
+                    error!("Error deleting interaction response: {:?}", e);
+                }
+            }
+        }
+    }
+}
+
+fn collect_options(options: Vec<ResolvedOption>, words: &mut Vec<String>, mentions: &mut Vec<User>) {
+    for option in options {
+        match option.value {
+            ResolvedValue::SubCommand(sub_options) | ResolvedValue::SubCommandGroup(sub_options) => {
+                words.push(option.name.to_string());
+                collect_options(sub_options, words, mentions);
+            }
+            ResolvedValue::String(value) if option.name == slash_commands::GAME_OPTION => words.insert(0, value.to_string()),
+            ResolvedValue::String(value) => words.push(value.to_string()),
+            ResolvedValue::Integer(value) => words.push(format!("{}={}", option.name, value)),
+            ResolvedValue::User(user, _) => mentions.push(user.clone()),
+            ResolvedValue::Channel(channel) => words.push(format!("<#{}>", channel.id.get())),
+            _ => {}
+        }
+    }
+}
+
+/// Passes a button click to the module its custom id belongs to, the lobby buttons are the only ones without a prefix
+pub(crate) async fn route_component(chat: &dyn ChatBackend, interaction: &ComponentInteraction, database: &dyn Store) {
+    let custom_id = interaction.data.custom_id.as_str();
+    if custom_id.starts_with(game_calls::BUTTON_PREFIX) {
+        game_calls::handle_component(chat, interaction, database).await;
+    }
+    else if custom_id.starts_with(scheduled_games::BUTTON_PREFIX) {
+        scheduled_games::handle_component(chat, interaction, database).await;
+    }
+    else if custom_id.starts_with(subscriptions::BUTTON_PREFIX) {
+        subscriptions::handle_component(chat, interaction, database).await;
+    }
+    else {
+        lobbies::handle_component(chat, interaction, database).await;
+    }
+}
+
+/// Runs the command in the message, anything else sent in a dm is a reply to a notification
+pub(crate) async fn handle_message(chat: &dyn ChatBackend, database: &dyn Store, msg: &Message) {
+    let stripped_content = msg.content.trim();
+
+    // don't respond to bots, this includes the notification dms the bot sends itself
+    if msg.author.bot {
+        return;
+    }
+    let user_discord_id = msg.author.id.get();
+    let discord_channel_id = msg.channel_id.get();
+    info!("Received message from discord user {} in channel {} of guild {:?}", user_discord_id, discord_channel_id, msg.guild_id.map(|guild_id| guild_id.get()));
+    // check commands first, guilds can pick their own prefix so the bot doesn't clash with other bots
+    let prefix = get_command_prefix(chat, database, msg.guild_id).await;
+    let command_regex = Regex::new(&format!(r"^{}(\S*)(.*)", regex::escape(&prefix))).unwrap();
+    if let Some(captures) = command_regex.captures(stripped_content) {
+        if let Some(command) = captures.get(1) {
+            let mut rest_of_command = None;
+            // if they have other text after the command we may want it, so get it
+            if let Some(rest_of_command_match) = captures.get(2) {
+                rest_of_command = Some(rest_of_command_match.as_str().trim())
+            }
+
+            // commands are handled by their default `!` name whatever the prefix is
+            let command = format!("!{}", command.as_str());
+            let locale = get_locale(database, msg.guild_id.map(|guild_id| guild_id.get()), user_discord_id).await;
+            let invocation = Invocation::from_message(msg, rest_of_command, prefix, locale);
+            handle_command(&command, &invocation, chat, database).await;
+        }
+    }
+    // anything else in a dm is a reply to a notification
+    else if msg.guild_id.is_none() {
+        direct_messages::handle_direct_message(chat, database, msg).await;
+    }
+    else {
+        game_calls::handle_guild_message(chat, database, msg).await;
+    }
+}
+
+pub(crate) async fn handle_command(command: &str, invocation: &Invocation<'_>, chat: &dyn ChatBackend, database: &dyn Store) {
+    let author = invocation.author;
+    let user_discord_id = author.id.get();
+    let discord_channel_id = invocation.channel_id.get();
+    // everything that isn't about the user themselves belongs to a guild
+    let messages = invocation.messages();
+    if invocation.guild_id.is_none() && slash_commands::is_command(command) && !constants::DM_CMDS.contains(&command) {
+        invocation.reply_to_author(chat, messages.only_in_a_server(&invocation.command(command))).await;
+        return;
+    }
+    // 0 for the commands that work in dms, they never look at it
+    let guild_discord_id = invocation.guild_id.map(GuildId::get).unwrap_or_default();
+    // a mention still shows the channel when its name can't be fetched
+    let discord_channel_name = match chat.channel_name(invocation.channel_id).await {
+        Ok(discord_channel_name) => discord_channel_name,
+        Err(e) => {
+            error!("Unable to get the name of channel {}: {:?}", discord_channel_id, e);
+            format!("<#{}>", discord_channel_id)
+        }
+    };
+    if constants::BLACKLISTABLE_CMDS.contains(&command) {
+        // when we can't tell, they don't get to use the command
+        match database.is_user_blacklisted(invocation.guild_id.map(GuildId::get), user_discord_id).await {
+            Ok(false) => {}
+            Ok(true) => {
+                info!("Ignoring {} from blacklisted user {}", command, user_discord_id);
+                return;
+            }
+            Err(error) => {
+                invocation.reply_database_trouble(chat, error).await;
+                return;
+            }
+        }
+    }
+    // require_registration and require_admin reply when they fail, so commands guarded by them fall through to `_`
+    match command {
+        constants::HELP_CMD => {
+            let help_text = messages.help_text(&Commands::new(&invocation.prefix));
+            let asked_about = invocation.rest_of_command.as_deref().unwrap_or("").trim();
+            let asked_about = asked_about.strip_prefix(invocation.prefix.as_str()).unwrap_or(asked_about).trim_start_matches('!');
+            let command_help = help_text.lines().find(|line| !asked_about.is_empty() && line.starts_with(&format!("{}{asked_about}:", invocation.prefix)));
+            // servers can put their own text above the commands
+            let guild_help_text = match invocation.guild_id {
+                Some(guild_id) => database.get_guild_settings(guild_id.get()).await.ok().flatten().and_then(|settings| settings.help_text),
+                None => None
+            };
+            let help_text = match (command_help, guild_help_text) {
+                (Some(command_help), _) => command_help.to_string(),
+                (None, Some(guild_help_text)) => format!("{guild_help_text}\n\n{help_text}"),
+                (None, None) => help_text
+            };
+            // every command together is longer than a single discord message
+            for part in split_message(&help_text, constants::MAX_MESSAGE_LENGTH) {
+                invocation.reply(chat, part).await;
+            }
+        }
+        constants::REGISTER_CMD =>   {
+            let user = get_or_create_user(database, user_discord_id).await;
+            match user {
+                Ok(_) => invocation.reply_to_author(chat, messages.registered()).await,
+                Err(error) => invocation.reply_database_trouble(chat, error).await
+            }
+        }
+        constants::UNREGISTER_CMD if invocation.require_registration(chat, database).await => {
+            // the dm channel is needed to find the notification dms we sent them
+            let dm_channel_id = match chat.dm_channel(author.id).await {
+                Ok(dm_channel_id) => Some(dm_channel_id.get()),
+                Err(e) => {
+                    error!("Unable to open dm channel with user {}: {:?}", user_discord_id, e);
+                    None
+                }
+            };
+            match database.delete_user(user_discord_id, dm_channel_id).await {
+                Ok(_) => {
+                    info!("User {} unregistered and all of their data was deleted", user_discord_id);
+                    let builder = CreateMessage::new().content(messages.unregistered_dm(&invocation.command(constants::REGISTER_CMD)));
+                    match chat.send_dm(author.id, builder).await {
+                        Ok(_) => invocation.reply_to_author(chat, messages.unregistered_check_dms()).await,
+                        Err(e) => {
+                            error!("Error sending dm to user {}: {:?}", user_discord_id, e);
+                            invocation.reply_to_author(chat, messages.unregistered()).await;
+                        }
+                    }
+                }
+                Err(error) => invocation.reply_database_trouble(chat, error).await
+            }
+        }
+        constants::MY_SUBSCRIPTIONS_CMD => {
+            match describe_subscriptions(chat, database, messages, &invocation.prefix, user_discord_id).await {
+                Ok((content, components)) => invocation.reply_with_components(chat, format!("@{} {}", author.name, content), components).await,
+                Err(error) => invocation.reply_database_trouble(chat, error).await
+            }
+        }
+        // ping_list references users, so they have to register first
+        constants::GAME_NOTIFICATION_ON_CMD if invocation.require_registration(chat, database).await => {
+            let Some(game) = invocation.require_known_game(chat, database).await else {
+                return;
+            };
+            let game_id = game.as_ref().map(|game| game.id);
+            let notifications = messages.notifications_for(game.as_ref().map(|game| game.name.as_str()), &discord_channel_name);
+            let ping = database.get_ping(user_discord_id, discord_channel_id, game_id).await;
+            match ping {
+                Ok(_) => invocation.reply_to_author(chat, messages.already_signed_up(&notifications)).await,
+                Err(WololoError::NotFound) => {
+                    let new_ping = database.create_ping(guild_discord_id, user_discord_id, discord_channel_id, game_id).await;
+                    match new_ping {
+                        Ok(_) => invocation.reply_to_author(chat, messages.signed_up(&notifications)).await,
+                        Err(WololoError::Conflict(_)) => invocation.reply_to_author(chat, messages.already_signed_up(&notifications)).await,
+                        Err(error) => invocation.reply_database_trouble(chat, error).await
+                    }
+                }
+                Err(error) => invocation.reply_database_trouble(chat, error).await
+            }
+        }
+        constants::GAME_NOTIFICATION_OFF_CMD => {
+            let Some(game) = invocation.require_known_game(chat, database).await else {
+                return;
+            };
+            let notifications = messages.notifications_for(game.as_ref().map(|game| game.name.as_str()), &discord_channel_name);
+            // without a game they are removed from every game in the channel
+            let deleted = match &game {
+                Some(game) => match database.get_ping(user_discord_id, discord_channel_id, Some(game.id)).await {
+                    Ok(ping) => database.delete_ping(ping).await,
+                    Err(WololoError::NotFound) => Ok(false),
+                    Err(error) => Err(error)
+                },
+                None => database.delete_pings_for_channel(user_discord_id, discord_channel_id).await.map(|count| count > 0)
+            };
+            match deleted {
+                Ok(true) => invocation.reply_to_author(chat, messages.removed_from(&notifications)).await,
+                Ok(false) if game.is_some() && database.get_ping(user_discord_id, discord_channel_id, None).await.is_ok() => {
+                    invocation.reply_to_author(chat, messages.signed_up_for_every_game(&discord_channel_name, &invocation.command(constants::GAME_NOTIFICATION_OFF_CMD))).await;
+                }
+                Ok(false) => invocation.reply_to_author(chat, messages.not_signed_up(&notifications)).await,
+                Err(error) => invocation.reply_database_trouble(chat, error).await
+            }
+        }
+        constants::ANY_GAMERS_CMD if invocation.require_registration(chat, database).await && invocation.require_under_call_limit(chat, database).await => {
+            let (game, rest_of_command) = match games::split_game(database, guild_discord_id, invocation.rest_of_command.as_deref().unwrap_or("")).await {
+                Ok(split) => split,
+                Err(error) => {
+                    invocation.reply_database_trouble(chat, error).await;
+                    return;
+                }
+            };
+            let default_size = game.as_ref().map(|game| game.default_party_size).unwrap_or(constants::DEFAULT_LOBBY_SIZE);
+            let (target_size, description) = parse_lobby_size(&rest_of_command, default_size);
+            let lobby = match database.create_lobby(guild_discord_id, user_discord_id, discord_channel_id, target_size, game.as_ref(), &description).await {
+                Ok(lobby) => lobby,
+                Err(error) => {
+                    error!("Unable to create lobby for user {} in channel {}: {:?}", user_discord_id, discord_channel_id, error);
+                    invocation.reply_to_author(chat, messages.stack_failed(&discord_channel_name)).await;
+                    return;
+                }
+            };
+            // the summary message is the parent of every dm, it gets edited as people join
+            let guild_locale = get_guild_locale(database, invocation.guild_id.map(GuildId::get)).await;
+            let parent_message_id = match lobbies::post_summary(chat, guild_locale.messages(), &lobby).await {
+                Ok(summary_message) => summary_message.id,
+                Err(e) => {
+                    error!("Error sending message: {:?}", e);
+                    invocation.reply_to_author(chat, messages.stack_failed(&discord_channel_name)).await;
+                    return;
+                }
+            };
+            if let Err(error) = database.update_summary_message_for_lobby(lobby.id, parent_message_id.get()).await {
+                error!("Unable to save summary message {} for lobby {}: {:?}", parent_message_id.get(), lobby.id, error);
+            }
+            let parent = NotificationParent {
+                guild_id: guild_discord_id,
+                message_id: parent_message_id,
+                lobby_id: Some(lobby.id),
+                scheduled_game_id: None,
+                description: description.clone()
+            };
+            let game_label = game.as_ref().map(games::game_label);
+            let invitation_request = InvitationRequest {
+                organizer: &author.name,
+                game: game.as_ref().map(|game| game.name.as_str()),
+                description: &description,
+                when: None,
+                roster: &[author.name.as_str()],
+                target_size: Some(target_size),
+                locale: guild_locale,
+                theme: None
+            };
+            let invitation = invitations::write_invitation(chat, database, guild_discord_id, invitation_request).await;
+            let builder = |locale: Locale| {
+                let messages = locale.messages();
+                let notification = Notification {
+                    organizer: &author.name,
+                    game: game_label.as_deref(),
+                    channel_name: &discord_channel_name,
+                    description: &description,
+                    // the invitation is written in the guild's language, everyone else gets the usual text
+                    invitation: invitation.as_deref().filter(|_| locale == guild_locale),
+                    reply_hint: &direct_messages::reply_hint(messages, &discord_channel_name),
+                    decline_emoji: lobbies::DECLINE_EMOJIS[0]
+                };
+                CreateMessage::new()
+                    .content(messages.stack_notification(&notification, target_size))
+                    .components(lobbies::lobby_buttons(messages, lobby.id))
+            };
+            match notify_subscribers(chat, database, user_discord_id, invocation.channel_id, game.as_ref().map(|game| game.id), &parent, builder).await {
+                Ok(summary) => invocation.reply_to_author(chat, summary.describe(messages)).await,
+                Err(error) => invocation.reply_database_trouble(chat, error).await
+            }
+        }
+        constants::ADD_ADMINS_CMD if invocation.require_registration(chat, database).await => {
+            let access_level = match invocation.access_level(chat, database).await {
+                Ok(access_level) if access_level.is_admin() => access_level,
+                Ok(_) => {
+                    invocation.reply_to_author(chat, messages.not_an_admin()).await;
+                    return;
+                }
+                Err(error) => {
+                    invocation.reply_database_trouble(chat, error).await;
+                    return;
+                }
+            };
+            let (subcommand, _) = split_first_word(invocation.rest_of_command.as_deref().unwrap_or(""));
+            if subcommand.eq_ignore_ascii_case("list") {
+                let permissions = get_permissions(chat).await;
+                let owner = permissions.owner_discord_id.map(|owner_discord_id| format!("<@{owner_discord_id}>"));
+                match database.get_admin_users(guild_discord_id).await {
+                    Ok(admins) => {
+                        let mentions = admins.iter()
+                            .filter(|admin| permissions.owner_discord_id != Some(admin.discord_id as u64))
+                            .map(|admin| format!("<@{}>", admin.discord_id))
+                            .collect::<Vec<_>>();
+                        invocation.reply_to_author(chat, messages.admin_list(owner.as_deref(), &mentions, &permissions.admin_roles)).await;
+                    }
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+            else if subcommand.eq_ignore_ascii_case("remove") {
+                for mentioned_user in &invocation.mentions {
+                    match invocation.access_level_of(chat, database, mentioned_user).await {
+                        Err(error) => invocation.reply_database_trouble(chat, error).await,
+                        Ok(AccessLevel::Member) => invocation.reply_to_author(chat, messages.isnt_an_admin(&mentioned_user.name)).await,
+                        Ok(AccessLevel::RoleAdmin) => invocation.reply_to_author(chat, messages.role_admin(&mentioned_user.name)).await,
+                        Ok(target_level) if target_level >= access_level => invocation.reply_to_author(chat, messages.cant_remove_admin(&mentioned_user.name)).await,
+                        _ => match database.delete_admin_user(guild_discord_id, mentioned_user.id.get()).await {
+                            Ok(_) => {
+                                info!("User {} was removed as an admin by {}", mentioned_user.id.get(), user_discord_id);
+                                audit::record(chat, database, invocation, AuditAction::AdminRemoved, Some(mentioned_user.id), None).await;
+                                invocation.reply_to_author(chat, messages.admin_removed(&mentioned_user.name)).await;
+                            }
+                            Err(error) => invocation.reply_database_trouble(chat, error).await
+                        }
+                    }
+                }
+            }
+            else if access_level < AccessLevel::Admin {
+                // admins can't be above whoever made them, so role admins can't make any
+                invocation.reply_to_author(chat, messages.cant_add_admin()).await;
+            }
+            else {
+                // caller is an admin, lets add the
+                for mentioned_user in &invocation.mentions {
+                    // admins references users, so make sure the target exists first
+                    let result = match get_or_create_user(database, mentioned_user.id.get()).await {
+                        Ok(_) => database.create_admin_user(guild_discord_id, mentioned_user.id.get()).await,
+                        Err(error) => Err(error)
+                    };
+                    match result {
+                        Ok(admin) => {
+                            info!("User {} was made an admin by {}", admin.discord_id, user_discord_id);
+                            audit::record(chat, database, invocation, AuditAction::AdminAdded, Some(mentioned_user.id), None).await;
+                            invocation.reply_to_author(chat, messages.admin_added(&mentioned_user.name)).await;
+                        }
+                        Err(WololoError::Conflict(_)) => invocation.reply_to_author(chat, messages.already_an_admin(&mentioned_user.name)).await,
+                        Err(error) => invocation.reply_database_trouble(chat, error).await
+                    }
+                }
+            }
+        }
+        constants::BLACKLIST_CMD if invocation.require_admin(chat, database).await => {
+            for mentioned_user in &invocation.mentions {
+                match invocation.access_level_of(chat, database, mentioned_user).await {
+                    Ok(access_level) if access_level.is_admin() => {
+                        invocation.reply_to_author(chat, messages.admin_cant_be_blacklisted(&mentioned_user.name)).await;
+                        continue;
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        invocation.reply_database_trouble(chat, error).await;
+                        continue;
+                    }
+                }
+                match database.create_blacklisted_user(guild_discord_id, mentioned_user.id.get()).await {
+                    Ok(blacklisted) => {
+                        info!("User {} was blacklisted by {}", blacklisted.discord_id, user_discord_id);
+                        audit::record(chat, database, invocation, AuditAction::Blacklisted, Some(mentioned_user.id), None).await;
+                        invocation.reply_to_author(chat, messages.blacklisted(&mentioned_user.name)).await;
+                    }
+                    Err(WololoError::Conflict(_)) => invocation.reply_to_author(chat, messages.already_blacklisted(&mentioned_user.name)).await,
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+        }
+        constants::UNBLACKLIST_CMD if invocation.require_admin(chat, database).await => {
+            for mentioned_user in &invocation.mentions {
+                match database.delete_blacklisted_user(guild_discord_id, mentioned_user.id.get()).await {
+                    Ok(true) => {
+                        info!("User {} was removed from the blacklist by {}", mentioned_user.id.get(), user_discord_id);
+                        audit::record(chat, database, invocation, AuditAction::Unblacklisted, Some(mentioned_user.id), None).await;
+                        invocation.reply_to_author(chat, messages.unblacklisted(&mentioned_user.name)).await;
+                    }
+                    Ok(false) => invocation.reply_to_author(chat, messages.not_blacklisted(&mentioned_user.name)).await,
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+        }
+        constants::CHANNEL_COOLDOWN_CMD => {
+            let duration_text = invocation.rest_of_command.as_deref().unwrap_or("").trim();
+            if duration_text.is_empty() {
+                let channel_cooldown_seconds = get_notification_cooldown_for_channel(database, guild_discord_id, discord_channel_id).await;
+                invocation.reply_to_author(chat, messages.channel_cooldown(&discord_channel_name, &format_duration(channel_cooldown_seconds))).await;
+            }
+            else if !invocation.require_admin(chat, database).await {
+                // require_admin already replied
+            }
+            else if let Some(cooldown) = parse_duration(duration_text) {
+                match database.upsert_notification_cooldown_for_channel(guild_discord_id, discord_channel_id, cooldown.num_seconds()).await {
+                    Ok(settings) => {
+                        info!("Notification cooldown for channel {} was set to {}s by {}", discord_channel_id, settings.notification_cooldown_seconds, user_discord_id);
+                        let details = format!("channel-cooldown {}", format_duration(settings.notification_cooldown_seconds));
+                        audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                        invocation.reply_to_author(chat, messages.channel_cooldown_changed(&discord_channel_name, &format_duration(settings.notification_cooldown_seconds))).await;
+                    }
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+            else {
+                invocation.reply_to_author(chat, messages.unknown_duration(duration_text)).await;
+            }
+        }
+        constants::SERVER_SETTINGS_CMD => {
+            let (setting, value) = split_first_word(invocation.rest_of_command.as_deref().unwrap_or(""));
+            let value = value.trim();
+            let turn_off = value.eq_ignore_ascii_case("off");
+            // what ends up in the audit log, e.g. "prefix ?"
+            let details = format!("{setting} {value}");
+            match setting {
+                "" | "show" => match database.get_guild_settings(guild_discord_id).await {
+                    Ok(settings) => {
+                        let settings = settings.unwrap_or_default();
+                        let cooldown = format_duration(settings.notification_cooldown_seconds.unwrap_or(constants::DEFAULT_NOTIFICATION_COOLDOWN_SECONDS));
+                        let prefix = settings.command_prefix.as_deref().unwrap_or(constants::DEFAULT_COMMAND_PREFIX);
+                        let locale = settings.locale.as_deref().and_then(Locale::parse).unwrap_or_default();
+                        let mod_log = settings.mod_log_channel_id.map(|mod_log_channel_id| format!("<#{mod_log_channel_id}>"));
+                        let server_settings = ServerSettings {
+                            help_text: settings.help_text.as_deref(),
+                            cooldown: &cooldown,
+                            prefix,
+                            language: locale.name(),
+                            invitations: settings.personalized_invitations,
+                            invitation_theme: settings.invitation_theme.as_deref(),
+                            game_calls: settings.game_call_offers,
+                            mod_log: mod_log.as_deref(),
+                            call_limit: settings.call_limit.unwrap_or(constants::DEFAULT_CALL_LIMIT),
+                            call_window: &format_duration(settings.call_window_seconds.unwrap_or(constants::DEFAULT_CALL_WINDOW_SECONDS))
+                        };
+                        invocation.reply_to_author(chat, messages.server_settings(&server_settings)).await;
+                    }
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                },
+                "help-message" | "cooldown" | "prefix" | "language" | "invitations" | "theme" | "game-calls" | "mod-log" | "call-limit" if !invocation.require_admin(chat, database).await => {
+                    // require_admin already replied
+                }
+                "help-message" if value.is_empty() => invocation.reply_to_author(chat, messages.help_message_missing()).await,
+                "help-message" => match database.upsert_help_text_for_guild(guild_discord_id, (!turn_off).then_some(value)).await {
+                    Ok(_) => {
+                        info!("Help message for guild {} was changed by {}", guild_discord_id, user_discord_id);
+                        audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                        invocation.reply_to_author(chat, messages.help_message_changed(turn_off)).await;
+                    }
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                },
+                "cooldown" => {
+                    let cooldown = if turn_off { Some(None) } else { parse_duration(value).map(|cooldown| Some(cooldown.num_seconds())) };
+                    match cooldown {
+                        Some(cooldown) => match database.upsert_notification_cooldown_for_guild(guild_discord_id, cooldown).await {
+                            Ok(settings) => {
+                                let cooldown = settings.notification_cooldown_seconds.unwrap_or(constants::DEFAULT_NOTIFICATION_COOLDOWN_SECONDS);
+                                info!("Notification cooldown for guild {} was set to {}s by {}", guild_discord_id, cooldown, user_discord_id);
+                                audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                                invocation.reply_to_author(chat, messages.server_cooldown_changed(&format_duration(cooldown))).await;
+                            }
+                            Err(error) => invocation.reply_database_trouble(chat, error).await
+                        },
+                        None => invocation.reply_to_author(chat, messages.unknown_duration(value)).await
+                    }
+                }
+                "prefix" if !turn_off && !is_valid_prefix(value) => invocation.reply_to_author(chat, messages.invalid_prefix(value)).await,
+                "prefix" => match database.upsert_command_prefix_for_guild(guild_discord_id, (!turn_off).then_some(value)).await {
+                    Ok(settings) => {
+                        let prefix = settings.command_prefix.unwrap_or_else(|| constants::DEFAULT_COMMAND_PREFIX.to_string());
+                        info!("Command prefix for guild {} was set to {} by {}", guild_discord_id, prefix, user_discord_id);
+                        audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                        if let Some(guild_id) = invocation.guild_id {
+                            cache_command_prefix(chat, guild_id, &prefix).await;
+                        }
+                        invocation.reply_to_author(chat, messages.prefix_changed(&with_prefix(constants::HELP_CMD, &prefix))).await;
+                    }
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                },
+                "language" => {
+                    let locale = if turn_off { Some(None) } else { Locale::parse(value).map(Some) };
+                    match locale {
+                        Some(locale) => match database.upsert_locale_for_guild(guild_discord_id, locale.map(Locale::code)).await {
+                            Ok(_) => {
+                                let locale = locale.unwrap_or_default();
+                                info!("Language for guild {} was set to {} by {}", guild_discord_id, locale.code(), user_discord_id);
+                                audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                                // answered in the new language so they can see what it looks like
+                                invocation.reply_to_author(chat, locale.messages().server_language_changed(locale.name())).await;
+                            }
+                            Err(error) => invocation.reply_database_trouble(chat, error).await
+                        },
+                        None => invocation.reply_to_author(chat, messages.unknown_language(value, &language_names())).await
+                    }
+                }
+                "invitations" => {
+                    let on = match value.to_lowercase().as_str() {
+                        "on" => true,
+                        "off" => false,
+                        _ => {
+                            invocation.reply_to_author(chat, messages.on_or_off(setting)).await;
+                            return;
+                        }
+                    };
+                    if on && !chat.data().read().await.contains_key::<Invitations>() {
+                        invocation.reply_to_author(chat, messages.invitations_unavailable()).await;
+                        return;
+                    }
+                    match database.upsert_personalized_invitations_for_guild(guild_discord_id, on).await {
+                        Ok(_) => {
+                            info!("Personalized invitations for guild {} were turned {} by {}", guild_discord_id, if on { "on" } else { "off" }, user_discord_id);
+                            audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                            invocation.reply_to_author(chat, messages.invitations_changed(on)).await;
+                        }
+                        Err(error) => invocation.reply_database_trouble(chat, error).await
+                    }
+                }
+                "theme" if value.is_empty() => invocation.reply_to_author(chat, messages.invitation_theme_missing()).await,
+                "theme" => match database.upsert_invitation_theme_for_guild(guild_discord_id, (!turn_off).then_some(value)).await {
+                    Ok(_) => {
+                        info!("Invitation theme for guild {} was changed by {}", guild_discord_id, user_discord_id);
+                        audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                        invocation.reply_to_author(chat, messages.invitation_theme_changed((!turn_off).then_some(value))).await;
+                    }
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                },
+                "game-calls" => {
+                    let on = match value.to_lowercase().as_str() {
+                        "on" => true,
+                        "off" => false,
+                        _ => {
+                            invocation.reply_to_author(chat, messages.on_or_off(setting)).await;
+                            return;
+                        }
+                    };
+                    if on && !chat.data().read().await.contains_key::<GameCalls>() {
+                        invocation.reply_to_author(chat, messages.game_calls_unavailable()).await;
+                        return;
+                    }
+                    match database.upsert_game_call_offers_for_guild(guild_discord_id, on).await {
+                        Ok(_) => {
+                            info!("Game call offers for guild {} were turned {} by {}", guild_discord_id, if on { "on" } else { "off" }, user_discord_id);
+                            audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                            invocation.reply_to_author(chat, messages.game_calls_changed(on)).await;
+                        }
+                        Err(error) => invocation.reply_database_trouble(chat, error).await
+                    }
+                }
+                "mod-log" => {
+                    // the slash command leaves the channel out to stop
+                    let mod_log_channel_id = if turn_off || value.is_empty() { Some(None) } else { parse_channel_mention(value).map(Some) };
+                    match mod_log_channel_id {
+                        Some(mod_log_channel_id) => match database.upsert_mod_log_channel_for_guild(guild_discord_id, mod_log_channel_id).await {
+                            Ok(_) => {
+                                info!("Mod log channel for guild {} was set to {:?} by {}", guild_discord_id, mod_log_channel_id, user_discord_id);
+                                let channel = mod_log_channel_id.map(|mod_log_channel_id| format!("<#{mod_log_channel_id}>"));
+                                let details = format!("{setting} {}", channel.as_deref().unwrap_or("off"));
+                                audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                                invocation.reply_to_author(chat, messages.mod_log_changed(channel.as_deref())).await;
+                            }
+                            Err(error) => invocation.reply_database_trouble(chat, error).await
+                        },
+                        None => invocation.reply_to_author(chat, messages.unknown_channel(value)).await
+                    }
+                }
+                "call-limit" => {
+                    let (limit_text, window_text) = split_first_word(value);
+                    let call_limit = if turn_off {
+                        Some(None)
+                    }
+                    else {
+                        match (limit_text.parse::<i32>(), parse_duration(window_text)) {
+                            (Ok(limit), Some(window)) if limit > 0 && (1..=constants::MAX_CALL_WINDOW_SECONDS).contains(&window.num_seconds()) => Some(Some((limit, window.num_seconds()))),
+                            _ => None
+                        }
+                    };
+                    match call_limit {
+                        Some(call_limit) => match database.upsert_call_limit_for_guild(guild_discord_id, call_limit).await {
+                            Ok(_) => {
+                                let (limit, window_seconds) = call_limit.unwrap_or((constants::DEFAULT_CALL_LIMIT, constants::DEFAULT_CALL_WINDOW_SECONDS));
+                                info!("Call limit for guild {} was set to {} per {}s by {}", guild_discord_id, limit, window_seconds, user_discord_id);
+                                audit::record(chat, database, invocation, AuditAction::SettingChanged, None, Some(&details)).await;
+                                invocation.reply_to_author(chat, messages.call_limit_changed(limit, &format_duration(window_seconds))).await;
+                            }
+                            Err(error) => invocation.reply_database_trouble(chat, error).await
+                        },
+                        None => invocation.reply_to_author(chat, messages.invalid_call_limit(value, &format_duration(constants::MAX_CALL_WINDOW_SECONDS))).await
+                    }
+                }
+                _ => invocation.reply_to_author(chat, messages.unknown_setting(setting)).await
+            }
+        }
+        constants::AUDIT_CMD if invocation.require_admin(chat, database).await => {
+            // the slash command sends count=<n>
+            let count_text = invocation.rest_of_command.as_deref().unwrap_or("").trim();
+            let count = count_text.trim_start_matches("count=").parse::<i64>().unwrap_or(audit::DEFAULT_AUDIT_ENTRIES).clamp(1, audit::MAX_AUDIT_ENTRIES);
+            match database.get_audit_entries(guild_discord_id, count).await {
+                Ok(entries) if entries.is_empty() => invocation.reply_to_author(chat, messages.no_audit_entries()).await,
+                Ok(entries) => {
+                    let lines = entries.iter().filter_map(|entry| audit::describe(messages, entry)).collect::<Vec<_>>();
+                    invocation.reply_to_author(chat, messages.audit_log(&lines)).await;
+                }
+                Err(error) => invocation.reply_database_trouble(chat, error).await
+            }
+        }
+        constants::NOTIFICATION_GAP_CMD if invocation.require_registration(chat, database).await => {
+            let gap_text = invocation.rest_of_command.as_deref().unwrap_or("").trim();
+            if gap_text.is_empty() {
+                let gap = database.get_user_settings(user_discord_id).await.map(|settings| settings.and_then(|settings| settings.min_notification_gap_seconds));
+                match gap {
+                    Ok(Some(gap)) => invocation.reply_to_author(chat, messages.notification_gap(&format_duration(gap))).await,
+                    Ok(None) => invocation.reply_to_author(chat, messages.no_notification_gap()).await,
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+            else {
+                let gap = if gap_text.eq_ignore_ascii_case("off") { Some(None) } else { parse_duration(gap_text).map(|gap| Some(gap.num_seconds())) };
+                match gap {
+                    Some(gap) => match database.upsert_notification_gap_for_user(user_discord_id, gap).await {
+                        Ok(UserSettings { min_notification_gap_seconds: Some(gap), .. }) => invocation.reply_to_author(chat, messages.notification_gap_changed(&format_duration(gap))).await,
+                        Ok(UserSettings { min_notification_gap_seconds: None, .. }) => invocation.reply_to_author(chat, messages.notification_gap_off()).await,
+                        Err(error) => invocation.reply_database_trouble(chat, error).await
+                    },
+                    None => invocation.reply_to_author(chat, messages.unknown_duration_or_off(gap_text)).await
+                }
+            }
+        }
+        constants::TIMEZONE_CMD if invocation.require_registration(chat, database).await => {
+            let timezone_text = invocation.rest_of_command.as_deref().unwrap_or("").trim();
+            if timezone_text.is_empty() {
+                match database.get_user_settings(user_discord_id).await.map(|settings| settings.and_then(|settings| settings.timezone)) {
+                    Ok(Some(timezone)) => invocation.reply_to_author(chat, messages.timezone(&timezone)).await,
+                    Ok(None) => invocation.reply_to_author(chat, messages.no_timezone(&invocation.command(constants::TIMEZONE_CMD))).await,
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+            else if let Some(timezone) = parse_timezone(timezone_text) {
+                match database.upsert_timezone_for_user(user_discord_id, timezone.name()).await {
+                    Ok(_) => invocation.reply_to_author(chat, messages.timezone_changed(timezone.name())).await,
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+            else {
+                invocation.reply_to_author(chat, messages.unknown_timezone(timezone_text)).await;
+            }
+        }
+        constants::QUIET_HOURS_CMD if invocation.require_registration(chat, database).await => {
+            let quiet_text = invocation.rest_of_command.as_deref().unwrap_or("").trim();
+            let settings = match database.get_user_settings(user_discord_id).await {
+                Ok(settings) => settings,
+                Err(error) => {
+                    invocation.reply_database_trouble(chat, error).await;
+                    return;
+                }
+            };
+            if quiet_text.is_empty() {
+                match settings {
+                    Some(UserSettings { timezone: Some(timezone), quiet_start_minutes: Some(start), quiet_end_minutes: Some(end), .. }) => invocation.reply_to_author(chat, messages.quiet_hours(&format_minutes_after_midnight(start), &format_minutes_after_midnight(end), &timezone)).await,
+                    _ => invocation.reply_to_author(chat, messages.no_quiet_hours(&invocation.command(constants::QUIET_HOURS_CMD))).await
+                }
+            }
+            else if quiet_text.eq_ignore_ascii_case("off") {
+                match database.upsert_quiet_hours_for_user(user_discord_id, None).await {
+                    Ok(_) => invocation.reply_to_author(chat, messages.quiet_hours_off()).await,
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+            else {
+                let (window_text, timezone_text) = quiet_text.split_once(char::is_whitespace).unwrap_or((quiet_text, ""));
+                let window = parse_time_window(window_text);
+                let timezone = parse_timezone(timezone_text);
+                let has_timezone = timezone.is_some() || settings.and_then(|settings| settings.timezone).is_some();
+                match window {
+                    None => invocation.reply_to_author(chat, messages.unknown_time_window(window_text)).await,
+                    Some(_) if !timezone_text.trim().is_empty() && timezone.is_none() => {
+                        invocation.reply_to_author(chat, messages.unknown_timezone(timezone_text.trim())).await;
+                    }
+                    Some(_) if !has_timezone => {
+                        invocation.reply_to_author(chat, messages.quiet_hours_need_timezone(&invocation.command(constants::QUIET_HOURS_CMD), window_text)).await;
+                    }
+                    Some((start, end)) => {
+                        let result = match timezone {
+                            Some(timezone) => database.upsert_timezone_for_user(user_discord_id, timezone.name()).await.map(|_| ()),
+                            None => Ok(())
+                        };
+                        // the quiet hours are only saved along with the timezone they are in
+                        let result = match result {
+                            Ok(()) => database.upsert_quiet_hours_for_user(user_discord_id, Some((minutes_after_midnight(start), minutes_after_midnight(end)))).await,
+                            Err(error) => Err(error)
+                        };
+                        match result {
+                            Ok(settings) => invocation.reply_to_author(chat, messages.quiet_hours_changed(&start.format("%H:%M").to_string(), &end.format("%H:%M").to_string(), &settings.timezone.unwrap_or_default())).await,
+                            Err(error) => invocation.reply_database_trouble(chat, error).await
+                        }
+                    }
+                }
+            }
+        }
+        constants::LANGUAGE_CMD if invocation.require_registration(chat, database).await => {
+            let language_text = invocation.rest_of_command.as_deref().unwrap_or("").trim();
+            if language_text.is_empty() {
+                match database.get_user_settings(user_discord_id).await.map(|settings| settings.and_then(|settings| settings.locale)) {
+                    Ok(Some(_)) => invocation.reply_to_author(chat, messages.language(invocation.locale.name())).await,
+                    Ok(None) => invocation.reply_to_author(chat, messages.no_language(invocation.locale.name())).await,
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+                return;
+            }
+            let locale = if language_text.eq_ignore_ascii_case("off") { Some(None) } else { Locale::parse(language_text).map(Some) };
+            match locale {
+                Some(locale) => match database.upsert_locale_for_user(user_discord_id, locale.map(Locale::code)).await {
+                    // answered in the language they will get from now on
+                    Ok(_) => match locale {
+                        Some(locale) => invocation.reply_to_author(chat, locale.messages().language_changed(locale.name())).await,
+                        None => {
+                            let guild_locale = get_guild_locale(database, invocation.guild_id.map(GuildId::get)).await;
+                            invocation.reply_to_author(chat, guild_locale.messages().language_off(guild_locale.name())).await;
+                        }
+                    },
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                },
+                None => invocation.reply_to_author(chat, messages.unknown_language(language_text, &language_names())).await
+            }
+        }
+        constants::SCHEDULE_CMD if invocation.require_registration(chat, database).await && invocation.require_under_call_limit(chat, database).await => {
+            let (game, schedule_text) = match games::split_game(database, guild_discord_id, invocation.rest_of_command.as_deref().unwrap_or("").trim()).await {
+                Ok(split) => split,
+                Err(error) => {
+                    invocation.reply_database_trouble(chat, error).await;
+                    return;
+                }
+            };
+            // times without an explicit offset are in the organizer's timezone
+            let timezone = match database.get_user_settings(user_discord_id).await {
+                Ok(settings) => settings.and_then(|settings| settings.timezone).and_then(|timezone| parse_timezone(&timezone)),
+                Err(error) => {
+                    invocation.reply_database_trouble(chat, error).await;
+                    return;
+                }
+            };
+            let now = chrono::offset::Utc::now();
+            let (scheduled_for, description) = match parse_when(&schedule_text, now, timezone.unwrap_or(chrono_tz::UTC)) {
+                Some((scheduled_for, _)) if scheduled_for <= now => {
+                    invocation.reply_to_author(chat, messages.in_the_past(&discord_timestamp(scheduled_for))).await;
+                    return;
+                }
+                Some((scheduled_for, description)) => (scheduled_for, description.trim()),
+                None => {
+                    invocation.reply_to_author(chat, messages.unknown_when(&schedule_text, &invocation.command(constants::SCHEDULE_CMD))).await;
+                    return;
+                }
+            };
+            let scheduled_game = match database.create_scheduled_game(guild_discord_id, user_discord_id, discord_channel_id, game.as_ref(), description, scheduled_for).await {
+                Ok(scheduled_game) => scheduled_game,
+                Err(error) => {
+                    error!("Unable to create scheduled game for user {} in channel {}: {:?}", user_discord_id, discord_channel_id, error);
+                    invocation.reply_to_author(chat, messages.schedule_failed(&discord_channel_name)).await;
+                    return;
+                }
+            };
+            // the announcement is the parent of every dm, it gets edited as people rsvp
+            let guild_locale = get_guild_locale(database, invocation.guild_id.map(GuildId::get)).await;
+            let parent_message_id = match scheduled_games::post_announcement(chat, guild_locale.messages(), &scheduled_game).await {
+                Ok(announcement_message) => announcement_message.id,
+                Err(e) => {
+                    error!("Error sending message: {:?}", e);
+                    invocation.reply_to_author(chat, messages.schedule_failed(&discord_channel_name)).await;
+                    return;
+                }
+            };
+            if let Err(error) = database.update_announcement_message_for_scheduled_game(scheduled_game.id, parent_message_id.get()).await {
+                error!("Unable to save announcement message {} for scheduled game {}: {:?}", parent_message_id.get(), scheduled_game.id, error);
+            }
+            let parent = NotificationParent {
+                guild_id: guild_discord_id,
+                message_id: parent_message_id,
+                lobby_id: None,
+                scheduled_game_id: Some(scheduled_game.id),
+                description: description.to_string()
+            };
+            let game_label = game.as_ref().map(games::game_label);
+            let invitation_request = InvitationRequest {
+                organizer: &author.name,
+                game: game.as_ref().map(|game| game.name.as_str()),
+                description,
+                when: Some(&discord_timestamp(scheduled_for)),
+                roster: &[author.name.as_str()],
+                target_size: None,
+                locale: guild_locale,
+                theme: None
+            };
+            let invitation = invitations::write_invitation(chat, database, guild_discord_id, invitation_request).await;
+            let builder = |locale: Locale| {
+                let messages = locale.messages();
+                let notification = Notification {
+                    organizer: &author.name,
+                    game: game_label.as_deref(),
+                    channel_name: &discord_channel_name,
+                    description,
+                    invitation: invitation.as_deref().filter(|_| locale == guild_locale),
+                    reply_hint: &direct_messages::reply_hint(messages, &discord_channel_name),
+                    decline_emoji: lobbies::DECLINE_EMOJIS[0]
+                };
+                CreateMessage::new()
+                    .content(messages.schedule_notification(&notification, &discord_timestamp(scheduled_for)))
+                    .components(scheduled_games::rsvp_buttons(messages, scheduled_game.id))
+            };
+            let summary = match notify_subscribers(chat, database, user_discord_id, invocation.channel_id, game.as_ref().map(|game| game.id), &parent, builder).await {
+                Ok(summary) => summary,
+                Err(error) => {
+                    invocation.reply_database_trouble(chat, error).await;
+                    return;
+                }
+            };
+            let mut reply = messages.scheduled(scheduled_game.id, &discord_timestamp(scheduled_for), &summary.describe(messages));
+            if timezone.is_none() {
+                reply.push_str(&format!("\n{}", messages.assumed_utc(&invocation.command(constants::TIMEZONE_CMD))));
+            }
+            invocation.reply_to_author(chat, reply).await;
+        }
+        constants::SCHEDULED_CMD => {
+            match database.get_upcoming_scheduled_games_for_channel(discord_channel_id).await {
+                Ok(scheduled_games) if scheduled_games.is_empty() => {
+                    invocation.reply_to_author(chat, messages.no_scheduled_games(&discord_channel_name, &invocation.command(constants::SCHEDULE_CMD))).await;
+                }
+                Ok(scheduled_games) => {
+                    let lines = scheduled_games.iter()
+                        .map(|scheduled_game| messages.scheduled_game_line(scheduled_game.id, scheduled_game.game_name.as_deref(), &discord_timestamp(scheduled_game.scheduled_for), &format!("<@{}>", scheduled_game.organizer_discord_id), &scheduled_game.description))
+                        .collect::<Vec<_>>();
+                    invocation.reply_to_author(chat, messages.scheduled_games(&discord_channel_name, &lines)).await;
+                }
+                Err(error) => invocation.reply_database_trouble(chat, error).await
+            }
+        }
+        constants::STATS_CMD => {
+            let since = chrono::offset::Utc::now() - chrono::Duration::days(stats::STATS_DAYS);
+            let history = async {
+                let notifications = database.get_notifications_for_guild(guild_discord_id, since).await?;
+                let dms = database.get_notification_dms_for_guild(guild_discord_id, since).await?;
+                let settings = database.get_user_settings(user_discord_id).await?;
+                Ok::<_, WololoError>((notifications, dms, settings))
+            };
+            let (notifications, dms, settings) = match history.await {
+                Ok(history) => history,
+                Err(error) => {
+                    invocation.reply_database_trouble(chat, error).await;
+                    return;
+                }
+            };
+            if notifications.is_empty() {
+                invocation.reply_to_author(chat, messages.no_stats(stats::STATS_DAYS)).await;
+                return;
+            }
+            // the hours are in the caller's timezone
+            let timezone = settings.and_then(|settings| settings.timezone).and_then(|timezone| parse_timezone(&timezone)).unwrap_or(chrono_tz::UTC);
+            let guild_stats = stats::compute(&notifications, &dms, user_discord_id as i64, timezone);
+            let organizers = guild_stats.organizers.iter().map(|(organizer, calls)| (format!("<@{organizer}>"), *calls)).collect::<Vec<_>>();
+            let players = guild_stats.players.iter().map(|(player, joined, received)| (format!("<@{player}>"), *joined, *received)).collect::<Vec<_>>();
+            let busiest_hours = guild_stats.busiest_hours.iter().map(|(channel, hour, calls)| (format!("<#{channel}>"), format_minutes_after_midnight(*hour as i32 * 60), *calls)).collect::<Vec<_>>();
+            let stats = Stats {
+                days: stats::STATS_DAYS,
+                organizers: &organizers,
+                players: &players,
+                busiest_hours: &busiest_hours,
+                timezone: timezone.name(),
+                own_response: guild_stats.own_response
+            };
+            invocation.reply_to_author(chat, messages.stats(&stats)).await;
+        }
+        constants::CANCEL_GAME_CMD => {
+            let id_text = invocation.rest_of_command.as_deref().unwrap_or("").trim();
+            let Ok(scheduled_game_id) = id_text.trim_start_matches("id=").trim_start_matches('#').parse::<i32>() else {
+                invocation.reply_to_author(chat, messages.which_game(&invocation.command(constants::SCHEDULED_CMD))).await;
+                return;
+            };
+            let scheduled_game = match database.get_scheduled_game(scheduled_game_id).await {
+                // admins of one server can't see the games of another
+                Ok(scheduled_game) if scheduled_game.guild_id == Some(guild_discord_id as i64) => scheduled_game,
+                Ok(_) | Err(WololoError::NotFound) => {
+                    invocation.reply_to_author(chat, messages.game_number_not_found(scheduled_game_id)).await;
+                    return;
+                }
+                Err(error) => {
+                    invocation.reply_database_trouble(chat, error).await;
+                    return;
+                }
+            };
+            let is_organizer = scheduled_game.organizer_discord_id == user_discord_id as i64;
+            match invocation.access_level(chat, database).await {
+                Ok(access_level) if !is_organizer && !access_level.is_admin() => {
+                    invocation.reply_to_author(chat, messages.cancel_not_allowed(scheduled_game_id)).await;
+                }
+                Err(error) => invocation.reply_database_trouble(chat, error).await,
+                Ok(_) => match database.update_cancelled_at_for_scheduled_game(scheduled_game_id).await {
+                    Ok(true) => {
+                        info!("Scheduled game {} was cancelled by {}", scheduled_game_id, user_discord_id);
+                        let scheduled_game = ScheduledGame { cancelled_at: Some(chrono::offset::Utc::now()), ..scheduled_game };
+                        scheduled_games::announce_cancellation(chat, database, &scheduled_game).await;
+                        invocation.reply_to_author(chat, messages.cancelled(scheduled_game_id)).await;
+                    }
+                    Ok(false) => invocation.reply_to_author(chat, messages.already_over(scheduled_game_id)).await,
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+        }
+        constants::GAMES_CMD => {
+            let (subcommand, rest) = split_first_word(invocation.rest_of_command.as_deref().unwrap_or(""));
+            match subcommand {
+                "add" if invocation.require_admin(chat, database).await => {
+                    let Some(options) = games::parse_game_options(rest) else {
+                        invocation.reply_to_author(chat, messages.game_options_help(&invocation.command(constants::GAMES_CMD))).await;
+                        return;
+                    };
+                    // names and aliases are looked up together, so they can't be shared between games
+                    for name in std::iter::once(&options.name).chain(&options.aliases) {
+                        match database.get_game_by_name(guild_discord_id, name).await {
+                            Ok(game) => {
+                                invocation.reply_to_author(chat, messages.game_name_taken(name, &game.name)).await;
+                                return;
+                            }
+                            Err(WololoError::NotFound) => {}
+                            Err(error) => {
+                                invocation.reply_database_trouble(chat, error).await;
+                                return;
+                            }
+                        }
+                    }
+                    match database.create_game(guild_discord_id, &options.name, &options.aliases, options.default_party_size, options.emoji.as_deref()).await {
+                        Ok(game) => {
+                            info!("User {} added the game {}", user_discord_id, game.name);
+                            audit::record(chat, database, invocation, AuditAction::GameAdded, None, Some(&game.name)).await;
+                            invocation.reply_to_author(chat, messages.game_added(&games::describe_game(messages, &game), &format!("{} {}", invocation.command(constants::GAME_NOTIFICATION_ON_CMD), game.name))).await;
+                        }
+                        Err(WololoError::Conflict(_)) => invocation.reply_to_author(chat, messages.game_exists(&options.name)).await,
+                        Err(error) => invocation.reply_database_trouble(chat, error).await
+                    }
+                }
+                "remove" if invocation.require_admin(chat, database).await => {
+                    let (name, _) = split_first_word(rest);
+                    match database.get_game_by_name(guild_discord_id, name).await {
+                        // games shared by every server can only be removed by the owner
+                        Ok(game) if game.guild_id.is_none() && !matches!(invocation.access_level(chat, database).await, Ok(AccessLevel::Owner)) => {
+                            invocation.reply_to_author(chat, messages.shared_game(&game.name)).await;
+                        }
+                        Ok(game) => match database.delete_game(game.id).await {
+                            Ok(_) => {
+                                info!("User {} removed the game {}", user_discord_id, game.name);
+                                audit::record(chat, database, invocation, AuditAction::GameRemoved, None, Some(&game.name)).await;
+                                invocation.reply_to_author(chat, messages.game_removed(&game.name)).await;
+                            }
+                            Err(error) => invocation.reply_database_trouble(chat, error).await
+                        },
+                        Err(WololoError::NotFound) => invocation.reply_to_author(chat, messages.unknown_game(name)).await,
+                        Err(error) => invocation.reply_database_trouble(chat, error).await
+                    }
+                }
+                // require_admin already replied
+                "add" | "remove" => {}
+                _ => match database.get_games(guild_discord_id).await {
+                    Ok(games) if games.is_empty() => invocation.reply_to_author(chat, messages.no_games()).await,
+                    Ok(games) => {
+                        let lines = games.iter().map(|game| games::describe_game(messages, game)).collect::<Vec<_>>();
+                        invocation.reply_to_author(chat, messages.games_list(&lines)).await;
+                    }
+                    Err(error) => invocation.reply_database_trouble(chat, error).await
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Pulls a `size=<n>` option out of the text after `!any-gamers`, the rest is passed along as the description
+fn parse_lobby_size(rest_of_command: &str, default_size: i32) -> (i32, String) {
+    let mut target_size = default_size;
+    let mut description = Vec::new();
+    for word in rest_of_command.split_whitespace() {
+        match word.strip_prefix("size=").and_then(|size| size.parse::<i32>().ok()) {
+            Some(size) if (2..=constants::MAX_LOBBY_SIZE).contains(&size) => target_size = size,
+            _ => description.push(word)
+        }
+    }
+    (target_size, description.join(" "))
+}
+
+/// Prefixes can't have spaces since the command is the first word, and can't start with `/` so they don't get mixed up
+/// with slash commands
+fn is_valid_prefix(prefix: &str) -> bool {
+    (1..=constants::MAX_COMMAND_PREFIX_LEN).contains(&prefix.chars().count()) && !prefix.contains(char::is_whitespace) && !prefix.starts_with('/')
+}
+
+/// e.g. "en (English), fr (Français)"
+fn language_names() -> String {
+    Locale::ALL.iter().map(|locale| format!("{} ({})", locale.code(), locale.name())).collect::<Vec<_>>().join(", ")
+}
+
+pub(crate) async fn get_or_create_user(database: &dyn Store, discord_id: u64) -> Result<WololoUser, WololoError> {
+    match database.get_user(discord_id).await {
+        Err(WololoError::NotFound) => match database.create_user(discord_id).await {
+            // someone else registered them in the meantime
+            Err(WololoError::Conflict(_)) => database.get_user(discord_id).await,
+            result => result
+        },
+        result => result
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::Context as _;
+use crate::{invitations, openai};
+use crate::permissions::Permissions;
+
+/// What the bot needs to start. On shuttle the settings come from `Secrets.toml`, self-hosted from environment
+/// variables or a TOML file with the same keys
+pub(crate) struct Config {
+    pub(crate) discord_token: String,
+    pub(crate) permissions: Permissions,
+    // applies the embedded migrations when the bot starts, on unless RUN_MIGRATIONS is "false"
+    pub(crate) run_migrations: bool,
+    // keeps everything in memory when EPHEMERAL is "true", e.g. to try the bot out without touching the real data
+    pub(crate) ephemeral: bool,
+    // the model of the features that need one, they are off when the standalone bot has no OPENAI_API_KEY
+    pub(crate) openai_model: String,
+    // tokens each guild can spend on the language model per utc day, game calls and invitations together
+    pub(crate) invitation_token_budget: i64,
+}
+
+impl Config {
+    /// `get` looks up a setting by its key, e.g. `DISCORD_TOKEN`
+    pub(crate) fn from_settings(get: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let discord_token = get("DISCORD_TOKEN").context("'DISCORD_TOKEN' was not found")?;
+        // the owner is always an admin, so there is someone who can use the admin commands on a fresh database
+        let owner_discord_id = match get("OWNER_DISCORD_ID") {
+            Some(owner_discord_id) => Some(owner_discord_id.trim().parse::<u64>().context("'OWNER_DISCORD_ID' is not a discord user id")?),
+            None => None
+        };
+        let run_migrations = match get("RUN_MIGRATIONS") {
+            Some(run_migrations) => run_migrations.trim().parse::<bool>().context("'RUN_MIGRATIONS' should be true or false")?,
+            None => true
+        };
+        let ephemeral = match get("EPHEMERAL") {
+            Some(ephemeral) => ephemeral.trim().parse::<bool>().context("'EPHEMERAL' should be true or false")?,
+            None => false
+        };
+        let invitation_token_budget = match get("INVITATION_TOKEN_BUDGET") {
+            Some(budget) => budget.trim().parse::<i64>().context("'INVITATION_TOKEN_BUDGET' should be a number of tokens")?,
+            None => invitations::DEFAULT_DAILY_TOKEN_BUDGET
+        };
+        Ok(Config {
+            discord_token,
+            permissions: Permissions::new(owner_discord_id, get("ADMIN_ROLES").as_deref()),
+            run_migrations,
+            ephemeral,
+            openai_model: get("OPENAI_MODEL").unwrap_or_else(|| openai::DEFAULT_MODEL.to_string()),
+            invitation_token_budget,
+        })
+    }
+}
+
+/// Settings of the standalone binary. Environment variables win over the TOML file, which is the one passed with
+/// `--config <path>` or `wololo.toml` in the working directory if it exists
+#[cfg(not(feature = "shuttle"))]
+pub(crate) struct Settings {
+    file: std::collections::HashMap<String, String>,
+}
+
+#[cfg(not(feature = "shuttle"))]
+impl Settings {
+    pub(crate) fn load() -> anyhow::Result<Self> {
+        let args = std::env::args().collect::<Vec<_>>();
+        let path = match args.iter().position(|arg| arg == "--config") {
+            Some(index) => Some(args.get(index + 1).context("--config needs the path of a TOML file")?.clone()),
+            None => Some("wololo.toml".to_string()).filter(|path| std::path::Path::new(path).exists())
+        };
+        let Some(path) = path else {
+            return Ok(Settings { file: Default::default() });
+        };
+        let content = std::fs::read_to_string(&path).with_context(|| format!("Unable to read {}", path))?;
+        let table = content.parse::<toml::Table>().with_context(|| format!("{} isn't valid TOML", path))?;
+        let file = table.into_iter()
+            .map(|(key, value)| match value {
+                toml::Value::String(value) => (key, value),
+                // e.g. OWNER_DISCORD_ID = 123456789012345678
+                value => (key, value.to_string())
+            })
+            .collect();
+        Ok(Settings { file })
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<String> {
+        std::env::var(key).ok().or_else(|| self.file.get(key).cloned())
+    }
+}
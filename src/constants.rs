@@ -3,17 +3,58 @@ pub(crate) const HELP_CMD: &str = "!help";
 
 pub(crate) const ANY_GAMERS_CMD: &str = "!any-gamers";
 pub(crate) const REGISTER_CMD: &str = "!register";
+pub(crate) const UNREGISTER_CMD: &str = "!unregister";
 pub(crate) const GAME_NOTIFICATION_ON_CMD: &str = "!game-notification-on";
 pub(crate) const GAME_NOTIFICATION_OFF_CMD: &str = "!game-notification-off";
+pub(crate) const MY_SUBSCRIPTIONS_CMD: &str = "!my-subscriptions";
 pub(crate) const ADD_ADMINS_CMD: &str = "!admin";
+pub(crate) const BLACKLIST_CMD: &str = "!blacklist";
+pub(crate) const UNBLACKLIST_CMD: &str = "!unblacklist";
+pub(crate) const CHANNEL_COOLDOWN_CMD: &str = "!channel-cooldown";
+pub(crate) const NOTIFICATION_GAP_CMD: &str = "!notification-gap";
+pub(crate) const TIMEZONE_CMD: &str = "!timezone";
+pub(crate) const QUIET_HOURS_CMD: &str = "!quiet";
+pub(crate) const SCHEDULE_CMD: &str = "!schedule";
+pub(crate) const SCHEDULED_CMD: &str = "!scheduled";
+pub(crate) const CANCEL_GAME_CMD: &str = "!cancel-game";
+pub(crate) const GAMES_CMD: &str = "!games";
+pub(crate) const SERVER_SETTINGS_CMD: &str = "!server-settings";
+pub(crate) const LANGUAGE_CMD: &str = "!language";
+pub(crate) const AUDIT_CMD: &str = "!audit";
+pub(crate) const STATS_CMD: &str = "!stats";
 
-pub(crate) fn help_text() -> String {
-    format!("Here are my commands:
-{HELP_CMD}: show this message
-{REGISTER_CMD}: add yourself to the list of users I interact with
-{GAME_NOTIFICATION_ON_CMD}: enable notifications in the current channel when another registered user invokes the {ADD_ADMINS_CMD} command
-{GAME_NOTIFICATION_OFF_CMD}: disable game search notifications in the current channel
-{ANY_GAMERS_CMD}: send a dm to all registered users who have enabled game notifications in the current channel
------------ADMIN ONLY------------
-{ADD_ADMINS_CMD}: adds all mentioned users as admins. For example, '{ADD_ADMINS_CMD} @<some guy> would add <some guy> as an admin")
-}
+// what commands start with in dms and in guilds where an admin hasn't picked another prefix
+pub(crate) const DEFAULT_COMMAND_PREFIX: &str = "!";
+pub(crate) const MAX_COMMAND_PREFIX_LEN: usize = 5;
+
+// discord rejects longer messages
+pub(crate) const MAX_MESSAGE_LENGTH: usize = 2000;
+
+// longer cooldowns, gaps, call windows and schedule times are mistakes, and would overflow when added to a date
+pub(crate) const MAX_DURATION_SECONDS: i64 = 60 * 60 * 24 * 365;  // 365 DAYS
+
+// used for channels where an admin hasn't set a cooldown
+pub(crate) const DEFAULT_NOTIFICATION_COOLDOWN_SECONDS: i64 = 60 * 2;  // 2 MIN
+// how many notification dms are in flight at once, serenity queues whatever discord's rate limits don't allow yet
+pub(crate) const NOTIFICATION_CONCURRENCY: usize = 8;
+
+// how many stacks someone can start in a guild per window unless an admin set another limit, admins have none
+pub(crate) const DEFAULT_CALL_LIMIT: i32 = 3;
+pub(crate) const DEFAULT_CALL_WINDOW_SECONDS: i64 = 60 * 30;  // 30 MIN
+pub(crate) const MAX_CALL_WINDOW_SECONDS: i64 = 60 * 60 * 24 * 7;  // 7 DAYS
+// notification dms anyone gets per hour, across every channel and guild
+pub(crate) const MAX_DMS_PER_RECIPIENT_PER_HOUR: i64 = 6;
+
+// the organizer counts towards the size of a stack, 5 is a full dota team
+pub(crate) const DEFAULT_LOBBY_SIZE: i32 = 5;
+pub(crate) const MAX_LOBBY_SIZE: i32 = 40;
+
+// commands that blacklisted users are not allowed to use, anyone can still unregister since the blacklist outlives them
+pub(crate) const BLACKLISTABLE_CMDS: [&str; 4] = [ANY_GAMERS_CMD, REGISTER_CMD, GAME_NOTIFICATION_ON_CMD, SCHEDULE_CMD];
+
+// commands that are about the user rather than a server, the others only work in a server
+pub(crate) const DM_CMDS: [&str; 8] = [HELP_CMD, REGISTER_CMD, UNREGISTER_CMD, MY_SUBSCRIPTIONS_CMD, NOTIFICATION_GAP_CMD, TIMEZONE_CMD, QUIET_HOURS_CMD, LANGUAGE_CMD];
+
+// replies to a notification dm that unsubscribe instead of being passed along to the organizer
+pub(crate) const STOP_REPLY: &str = "stop";
+pub(crate) const STOP_ALL_REPLY: &str = "stop all";
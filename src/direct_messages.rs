@@ -0,0 +1,151 @@
+use serenity::all::{ChannelId, CreateAllowedMentions, CreateMessage, Message, MessageId, UserId};
+use tracing::{error, info};
+use crate::chat::ChatBackend;
+use crate::constants;
+use crate::errors::WololoError;
+use crate::messages::{get_guild_locale, get_locale, Messages};
+use crate::store::Store;
+use crate::structs::ParentMessageChildMessage;
+
+enum DirectMessageReply<'a> {
+    Stop,
+    StopAll,
+    Relay(&'a str),
+}
+
+fn parse_reply(content: &str) -> DirectMessageReply<'_> {
+    if content.eq_ignore_ascii_case(constants::STOP_REPLY) {
+        DirectMessageReply::Stop
+    }
+    else if content.eq_ignore_ascii_case(constants::STOP_ALL_REPLY) {
+        DirectMessageReply::StopAll
+    }
+    else {
+        DirectMessageReply::Relay(content)
+    }
+}
+
+/// The part of a notification dm that explains what replying to it does
+pub(crate) fn reply_hint(messages: &dyn Messages, discord_channel_name: &str) -> String {
+    messages.reply_hint(discord_channel_name, constants::STOP_REPLY, constants::STOP_ALL_REPLY)
+}
+
+/// The notification a dm is answering, either the one they used discord's reply on or the last one sent to them
+async fn get_replied_notification(database: &dyn Store, msg: &Message) -> Result<ParentMessageChildMessage, WololoError> {
+    match &msg.referenced_message {
+        Some(referenced_message) => database.get_parent_message_id_for_child_message_id(referenced_message.id.get()).await,
+        None => database.get_latest_child_for_channel(msg.channel_id.get()).await
+    }
+}
+
+/// Handles text sent to the bot in dms that isn't a command
+pub(crate) async fn handle_direct_message(chat: &dyn ChatBackend, database: &dyn Store, msg: &Message) {
+    let content = msg.content.trim();
+    if content.is_empty() {
+        return;
+    }
+    let user_discord_id = msg.author.id.get();
+    // there is no guild in a dm, so only their own language counts
+    let messages = get_locale(database, None, user_discord_id).await.messages();
+    let response = match parse_reply(content) {
+        DirectMessageReply::StopAll => match database.delete_pings_for_user(user_discord_id).await {
+            Ok(0) => messages.not_subscribed_anywhere(),
+            Ok(count) => {
+                info!("User {} unsubscribed from {} channels from a dm", user_discord_id, count);
+                messages.unsubscribed_everywhere(count)
+            }
+            Err(error) => {
+                error!("Unable to unsubscribe user {} from every channel: {}", user_discord_id, error);
+                messages.database_trouble()
+            }
+        },
+        reply => match get_replied_notification(database, msg).await {
+            Ok(notification) => match reply {
+                DirectMessageReply::Relay(text) => relay(chat, database, messages, msg, &notification, text).await,
+                _ => unsubscribe(database, messages, user_discord_id, &notification).await
+            },
+            Err(WololoError::NotFound) => messages.unknown_notification(constants::STOP_ALL_REPLY),
+            Err(error) => {
+                error!("Unable to find the notification user {} replied to: {}", user_discord_id, error);
+                messages.database_trouble()
+            }
+        }
+    };
+    if let Err(e) = chat.reply(msg, response).await {
+        error!("Error sending dm reply: {:?}", e);
+    }
+}
+
+/// Unsubscribes them from every game in the channel the notification came from
+async fn unsubscribe(database: &dyn Store, messages: &dyn Messages, user_discord_id: u64, notification: &ParentMessageChildMessage) -> String {
+    let channel = format!("<#{}>", notification.parent_channel_id);
+    match database.delete_pings_for_channel(user_discord_id, notification.parent_channel_id as u64).await {
+        Ok(count) if count > 0 => {
+            info!("User {} unsubscribed from channel {} from a dm", user_discord_id, notification.parent_channel_id);
+            messages.unsubscribed_from_channel(&channel)
+        }
+        Ok(_) => messages.already_unsubscribed_from_channel(&channel),
+        Err(error) => {
+            error!("Unable to unsubscribe user {} from channel {}: {}", user_discord_id, notification.parent_channel_id, error);
+            messages.database_trouble()
+        }
+    }
+}
+
+/// Who started the stack or scheduled the game, dms sent before lobbies existed don't know
+async fn get_organizer(database: &dyn Store, notification: &ParentMessageChildMessage) -> Option<UserId> {
+    let organizer_discord_id = match (notification.lobby_id, notification.scheduled_game_id) {
+        (Some(lobby_id), _) => database.get_lobby(lobby_id).await.map(|lobby| lobby.organizer_discord_id),
+        (_, Some(scheduled_game_id)) => database.get_scheduled_game(scheduled_game_id).await.map(|scheduled_game| scheduled_game.organizer_discord_id),
+        _ => return None
+    };
+    match organizer_discord_id {
+        Ok(organizer_discord_id) => Some(UserId::new(organizer_discord_id as u64)),
+        Err(error) => {
+            error!("Unable to get the organizer for message {}: {}", notification.parent, error);
+            None
+        }
+    }
+}
+
+/// Posts the reply in a thread on the channel message the notification belongs to, the thread is created the first
+/// time someone replies. What is posted in the thread is in the guild's language, the reply to them in their own
+async fn relay(chat: &dyn ChatBackend, database: &dyn Store, messages: &dyn Messages, msg: &Message, notification: &ParentMessageChildMessage, text: &str) -> String {
+    let user_discord_id = msg.author.id.get();
+    let guild_id = notification.guild_id.map(|guild_id| guild_id as u64);
+    match database.is_user_blacklisted(guild_id, user_discord_id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            info!("Not relaying dm from blacklisted user {}", user_discord_id);
+            return messages.not_allowed();
+        }
+        Err(error) => {
+            error!("Unable to check whether user {} is blacklisted, not relaying their dm: {}", user_discord_id, error);
+            return messages.database_trouble();
+        }
+    }
+    let guild_messages = get_guild_locale(database, guild_id).await.messages();
+    let channel_id = ChannelId::new(notification.parent_channel_id as u64);
+    let parent_message_id = MessageId::new(notification.parent as u64);
+    // a thread started from a message has the same id as the message, so this fails once the thread exists
+    let thread_id = match chat.create_thread(channel_id, parent_message_id, guild_messages.relay_thread_name()).await {
+        Ok(thread_id) => thread_id,
+        Err(_) => ChannelId::new(parent_message_id.get())
+    };
+    let organizer = get_organizer(database, notification).await;
+    let mention = organizer.map(|organizer| format!("<@{}> ", organizer.get())).unwrap_or_default();
+    let builder = CreateMessage::new()
+        .content(guild_messages.relayed_reply(&mention, &format!("<@{}>", user_discord_id), text))
+        // only the organizer should be pinged, whatever they wrote shouldn't ping anyone
+        .allowed_mentions(CreateAllowedMentions::new().users(organizer));
+    match chat.send_message(thread_id, builder).await {
+        Ok(_) => {
+            info!("Relayed dm from user {} to thread {}", user_discord_id, thread_id.get());
+            messages.relayed(&format!("<#{}>", thread_id.get()))
+        }
+        Err(e) => {
+            error!("Unable to relay dm from user {} to thread {}: {:?}", user_discord_id, thread_id.get(), e);
+            messages.relay_failed(&format!("<#{}>", notification.parent_channel_id))
+        }
+    }
+}
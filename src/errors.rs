@@ -0,0 +1,37 @@
+use std::fmt;
+
+/// What went wrong in the queries and writes modules, so handlers can tell "you aren't registered" apart from the
+/// database being down
+#[derive(Debug)]
+pub(crate) enum WololoError {
+    /// The row that was looked up doesn't exist
+    NotFound,
+    /// A unique constraint was violated, usually because the row already exists
+    Conflict(String),
+    /// Anything else, e.g. the database can't be reached
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for WololoError {
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::RowNotFound => WololoError::NotFound,
+            sqlx::Error::Database(database_error) if database_error.is_unique_violation() => {
+                WololoError::Conflict(database_error.constraint().unwrap_or_default().to_string())
+            }
+            error => WololoError::Database(error)
+        }
+    }
+}
+
+impl fmt::Display for WololoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WololoError::NotFound => write!(f, "row not found"),
+            WololoError::Conflict(constraint) => write!(f, "row already exists ({constraint})"),
+            WololoError::Database(error) => write!(f, "database error: {error}")
+        }
+    }
+}
+
+impl std::error::Error for WololoError {}
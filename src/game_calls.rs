@@ -0,0 +1,236 @@
+use std::sync::Arc;
+use std::time::Duration;
+use serenity::all::{ButtonStyle, ComponentInteraction, CreateActionRow, CreateAllowedMentions, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, GuildId, Message, MessageId};
+use serenity::async_trait;
+use serenity::prelude::*;
+use tracing::{error, info};
+use crate::chat::ChatBackend;
+use crate::commands::{handle_command, Invocation};
+use crate::constants;
+use crate::guilds::get_command_prefix;
+use crate::messages::{get_locale, with_prefix};
+use crate::store::Store;
+
+pub(crate) const BUTTON_PREFIX: &str = "game-call:";
+
+// longer messages are rarely someone looking for players, and they cost more to classify
+const MAX_MESSAGE_LENGTH: usize = 300;
+// every message in a channel with subscribers is classified, a slow model shouldn't pile them up
+const CLASSIFY_TIMEOUT: Duration = Duration::from_secs(4);
+// the longest answer the model can give, a request that didn't come back is charged as much
+pub(crate) const MAX_CLASSIFY_TOKENS: u32 = 10;
+
+/// A message from someone looking for people to play with
+pub(crate) struct GameCall {
+    // one of the guild's games, None when they didn't name one
+    pub(crate) game: Option<String>,
+}
+
+pub(crate) struct Classification {
+    // None when the message isn't a game call
+    pub(crate) game_call: Option<GameCall>,
+    // what classifying it cost, counted against the guild's daily budget
+    pub(crate) tokens: i64,
+}
+
+/// Decides whether a message is someone looking for people to play with, e.g. "anyone up for dota in 20?"
+#[async_trait]
+pub(crate) trait GameCallClassifier: Send + Sync {
+    /// `games` are the names of the guild's games, the game of the call is one of them
+    async fn classify(&self, message: &str, games: &[String]) -> anyhow::Result<Classification>;
+}
+
+/// The classifier in the client's data, there is none when no language model is configured
+pub(crate) struct GameCalls {
+    pub(crate) classifier: Arc<dyn GameCallClassifier>,
+    // per guild and per utc day, shared with the invitations
+    pub(crate) daily_token_budget: i64,
+}
+
+impl TypeMapKey for GameCalls {
+    type Value = Arc<GameCalls>;
+}
+
+/// Offers to notify the channel's subscribers when a message that isn't a command looks like someone looking for
+/// players. That's only once the guild turned game call offers on, and only registered users who could use the command
+/// themselves get the offer. The model is only asked when someone is subscribed in the channel and the guild has
+/// tokens left for the day
+pub(crate) async fn handle_guild_message(chat: &dyn ChatBackend, database: &dyn Store, msg: &Message) {
+    let Some(guild_id) = msg.guild_id else {
+        return;
+    };
+    let content = msg.content.trim();
+    if content.is_empty() || content.chars().count() > MAX_MESSAGE_LENGTH {
+        return;
+    }
+    let Some(game_calls) = chat.data().read().await.get::<GameCalls>().cloned() else {
+        return;
+    };
+    match database.get_guild_settings(guild_id.get()).await {
+        Ok(Some(settings)) if settings.game_call_offers => {}
+        Ok(_) => return,
+        Err(error) => {
+            error!("Unable to get settings for guild {}: {:?}", guild_id.get(), error);
+            return;
+        }
+    }
+    let user_discord_id = msg.author.id.get();
+    let discord_channel_id = msg.channel_id.get();
+    match database.get_all_pings_except_for_user(user_discord_id, discord_channel_id, None).await {
+        Ok(pings) if !pings.is_empty() => {}
+        Ok(_) => return,
+        Err(error) => {
+            error!("Unable to get the subscribers of channel {}: {:?}", discord_channel_id, error);
+            return;
+        }
+    }
+    if database.get_user(user_discord_id).await.is_err() || database.is_user_blacklisted(Some(guild_id.get()), user_discord_id).await.unwrap_or(true) {
+        return;
+    }
+    // a guild's own game can have the same name as a shared one
+    let mut game_names = match database.get_games(guild_id.get()).await {
+        Ok(games) => games.into_iter().map(|game| game.name).collect::<Vec<_>>(),
+        Err(error) => {
+            error!("Unable to get the games of guild {}: {:?}", guild_id.get(), error);
+            return;
+        }
+    };
+    game_names.dedup();
+    let today = chrono::offset::Utc::now().date_naive();
+    match database.get_language_model_tokens(guild_id.get(), today).await {
+        Ok(used) if used >= game_calls.daily_token_budget => {
+            info!("Guild {} used its {} tokens for today, message {} isn't classified", guild_id.get(), game_calls.daily_token_budget, msg.id.get());
+            return;
+        }
+        Ok(_) => {}
+        Err(error) => {
+            error!("Unable to get the tokens guild {} used today: {:?}", guild_id.get(), error);
+            return;
+        }
+    }
+    // the model may still have answered a request that failed or timed out, and that gets billed all the same
+    let (game_call, tokens) = match tokio::time::timeout(CLASSIFY_TIMEOUT, game_calls.classifier.classify(content, &game_names)).await {
+        Ok(Ok(classification)) => (classification.game_call, classification.tokens),
+        Ok(Err(error)) => {
+            error!("Unable to classify message {}: {:?}", msg.id.get(), error);
+            (None, MAX_CLASSIFY_TOKENS as i64)
+        }
+        Err(_) => {
+            error!("Classifying message {} took longer than {:?}", msg.id.get(), CLASSIFY_TIMEOUT);
+            (None, MAX_CLASSIFY_TOKENS as i64)
+        }
+    };
+    if let Err(error) = database.add_language_model_tokens(guild_id.get(), today, tokens).await {
+        error!("Unable to count the {} tokens guild {} used: {:?}", tokens, guild_id.get(), error);
+    }
+    let Some(game_call) = game_call else {
+        return;
+    };
+    let game = match game_call.game {
+        Some(name) => match database.get_game_by_name(guild_id.get(), &name).await {
+            Ok(game) => Some(game),
+            Err(error) => {
+                error!("Unable to get game {} of guild {}: {:?}", name, guild_id.get(), error);
+                return;
+            }
+        },
+        None => None
+    };
+    let subscribers = match database.get_all_pings_except_for_user(user_discord_id, discord_channel_id, game.as_ref().map(|game| game.id)).await {
+        Ok(pings) if !pings.is_empty() => pings.len(),
+        Ok(_) => return,
+        Err(error) => {
+            error!("Unable to get the subscribers of channel {}: {:?}", discord_channel_id, error);
+            return;
+        }
+    };
+    info!("Message {} from discord user {} looks like a game call", msg.id.get(), user_discord_id);
+    let messages = get_locale(database, Some(guild_id.get()), user_discord_id).await.messages();
+    let prefix = get_command_prefix(chat, database, Some(guild_id)).await;
+    let any_gamers_command = match &game {
+        Some(game) => format!("{} {}", with_prefix(constants::ANY_GAMERS_CMD, &prefix), game.name),
+        None => with_prefix(constants::ANY_GAMERS_CMD, &prefix)
+    };
+    // the author is part of the button so nobody else can send the notifications in their name, the message is fetched
+    // again for the description of the stack
+    let game_id = game.as_ref().map(|game| game.id.to_string()).unwrap_or_default();
+    let button = CreateButton::new(format!("{BUTTON_PREFIX}{user_discord_id}:{game_id}:{}", msg.id.get())).label(messages.notify_subscribers_button(subscribers)).style(ButtonStyle::Primary);
+    let offer = CreateMessage::new()
+        .content(format!("@{} {}", msg.author.name, messages.game_call_offer(game.as_ref().map(|game| game.name.as_str()), subscribers, &any_gamers_command)))
+        .reference_message(msg)
+        .allowed_mentions(CreateAllowedMentions::new())
+        .components(vec![CreateActionRow::Buttons(vec![button])]);
+    if let Err(e) = chat.send_message(msg.channel_id, offer).await {
+        error!("Error sending message: {:?}", e);
+    }
+}
+
+/// Handles the button of the offer, which runs the `!any-gamers` command for the author of the message with the message as
+/// the description. The summary replaces the offer
+pub(crate) async fn handle_component(chat: &dyn ChatBackend, interaction: &ComponentInteraction, database: &dyn Store) {
+    let Some(ids) = interaction.data.custom_id.strip_prefix(BUTTON_PREFIX) else {
+        return;
+    };
+    // offers made before the message was part of the button only have the author and the game
+    let (owner_discord_id, game_id, message_id) = match ids.split(':').collect::<Vec<_>>()[..] {
+        [owner_discord_id] => (owner_discord_id, "", None),
+        [owner_discord_id, game_id] => (owner_discord_id, game_id, None),
+        [owner_discord_id, game_id, message_id] => (owner_discord_id, game_id, Some(message_id)),
+        _ => return
+    };
+    let Ok(owner_discord_id) = owner_discord_id.parse::<u64>() else {
+        return;
+    };
+    let game_id = match game_id {
+        "" => None,
+        game_id => match game_id.parse::<i32>() {
+            Ok(game_id) => Some(game_id),
+            Err(_) => return
+        }
+    };
+    let message_id = match message_id.map(str::parse::<u64>) {
+        Some(Ok(message_id)) if message_id != 0 => Some(MessageId::new(message_id)),
+        Some(_) => return,
+        None => None
+    };
+    let user_discord_id = interaction.user.id.get();
+    let locale = get_locale(database, interaction.guild_id.map(GuildId::get), user_discord_id).await;
+    if owner_discord_id != user_discord_id {
+        let response = CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(locale.messages().not_your_game_call()).ephemeral(true));
+        if let Err(e) = chat.respond_to_interaction(interaction.id, &interaction.token, response).await {
+            error!("Error sending interaction response: {:?}", e);
+        }
+        return;
+    }
+    if let Err(e) = chat.respond_to_interaction(interaction.id, &interaction.token, CreateInteractionResponse::Acknowledge).await {
+        error!("Error acknowledging interaction: {:?}", e);
+        return;
+    }
+    // a game that was removed since the offer was made has no subscribers left, everyone in the channel is notified
+    let game_name = match (game_id, interaction.guild_id) {
+        (Some(game_id), Some(guild_id)) => match database.get_games(guild_id.get()).await {
+            Ok(games) => games.into_iter().find(|game| game.id == game_id).map(|game| game.name).unwrap_or_default(),
+            Err(error) => {
+                error!("Unable to get the games of guild {}: {:?}", guild_id.get(), error);
+                String::new()
+            }
+        },
+        _ => String::new()
+    };
+    // the stack still goes out when the message was deleted in the meantime, only without a description
+    let description = match message_id {
+        Some(message_id) => match chat.fetch_message(interaction.channel_id, message_id).await {
+            Ok(message) => message.content.trim().to_string(),
+            Err(e) => {
+                error!("Unable to fetch message {} for the game call offer: {:?}", message_id.get(), e);
+                String::new()
+            }
+        },
+        None => String::new()
+    };
+    info!("Discord user {} accepted the game call offer in channel {}", user_discord_id, interaction.channel_id.get());
+    let prefix = get_command_prefix(chat, database, interaction.guild_id).await;
+    let invocation = Invocation::from_component(interaction, format!("{game_name} {description}").trim(), prefix, locale);
+    handle_command(constants::ANY_GAMERS_CMD, &invocation, chat, database).await;
+    invocation.finish(chat).await;
+}
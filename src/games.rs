@@ -0,0 +1,85 @@
+use crate::constants;
+use crate::errors::WololoError;
+use crate::messages::Messages;
+use crate::parsing::split_first_word;
+use crate::store::Store;
+use crate::structs::Game;
+
+/// What `!games add` was given, e.g. "aoe2 size=8 🏰 aoe age"
+pub(crate) struct GameOptions {
+    pub(crate) name: String,
+    pub(crate) aliases: Vec<String>,
+    pub(crate) default_party_size: i32,
+    pub(crate) emoji: Option<String>,
+}
+
+// unicode emojis have no letters in them, custom server emojis look like <:name:id>
+fn is_emoji(word: &str) -> bool {
+    !word.chars().any(char::is_alphanumeric) || (word.starts_with('<') && word.ends_with('>'))
+}
+
+/// The first word is the name, an emoji is the emoji and every other word is an alias. Aliases can also be comma
+/// separated since that's how the slash command sends them
+pub(crate) fn parse_game_options(text: &str) -> Option<GameOptions> {
+    let (name, rest) = split_first_word(text);
+    if name.is_empty() || is_emoji(name) {
+        return None;
+    }
+    let mut options = GameOptions {
+        name: name.to_lowercase(),
+        aliases: Vec::new(),
+        default_party_size: constants::DEFAULT_LOBBY_SIZE,
+        emoji: None
+    };
+    for word in rest.split(|c: char| c.is_whitespace() || c == ',').filter(|word| !word.is_empty()) {
+        if let Some(size) = word.strip_prefix("size=") {
+            match size.parse::<i32>() {
+                Ok(size) if (2..=constants::MAX_LOBBY_SIZE).contains(&size) => options.default_party_size = size,
+                _ => return None
+            }
+        }
+        else if is_emoji(word) {
+            options.emoji = Some(word.to_string());
+        }
+        else {
+            options.aliases.push(word.to_lowercase());
+        }
+    }
+    Some(options)
+}
+
+/// e.g. "🏰 aoe2", or just the name when the game has no emoji
+pub(crate) fn game_label(game: &Game) -> String {
+    match &game.emoji {
+        Some(emoji) => format!("{} {}", emoji, game.name),
+        None => game.name.clone()
+    }
+}
+
+/// e.g. "aoe2 (aoe, age): stacks of 8"
+pub(crate) fn describe_game(messages: &dyn Messages, game: &Game) -> String {
+    messages.game_description(&game_label(game), &game.aliases, game.default_party_size)
+}
+
+/// "Known games: dota, aoe2", for replies to people who named a game that doesn't exist
+pub(crate) async fn known_games(messages: &dyn Messages, database: &dyn Store, guild_id: u64) -> Result<String, WololoError> {
+    let games = database.get_games(guild_id).await?;
+    if games.is_empty() {
+        return Ok(messages.no_games());
+    }
+    Ok(messages.known_games(&games.iter().map(|game| game.name.as_str()).collect::<Vec<_>>()))
+}
+
+/// Takes the game off the front of a command when the first word names one, e.g. "aoe2 ranked tonight" gives aoe2
+/// and "ranked tonight"
+pub(crate) async fn split_game(database: &dyn Store, guild_id: u64, text: &str) -> Result<(Option<Game>, String), WololoError> {
+    let (first_word, rest) = split_first_word(text);
+    if first_word.is_empty() {
+        return Ok((None, text.to_string()));
+    }
+    match database.get_game_by_name(guild_id, first_word).await {
+        Ok(game) => Ok((Some(game), rest.to_string())),
+        Err(WololoError::NotFound) => Ok((None, text.to_string())),
+        Err(error) => Err(error)
+    }
+}
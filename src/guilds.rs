@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use serenity::all::{ChannelId, GuildId};
+use serenity::prelude::*;
+use tracing::{error, info, warn};
+use crate::chat::ChatBackend;
+use crate::constants;
+use crate::store::Store;
+
+/// Every message the bot sees needs its guild's command prefix, so they are only read from the database once
+pub(crate) struct CommandPrefixes;
+
+impl TypeMapKey for CommandPrefixes {
+    type Value = Arc<RwLock<HashMap<u64, String>>>;
+}
+
+/// What text commands start with in the guild, dms always use the default
+pub(crate) async fn get_command_prefix(chat: &dyn ChatBackend, database: &dyn Store, guild_id: Option<GuildId>) -> String {
+    let Some(guild_id) = guild_id else {
+        return constants::DEFAULT_COMMAND_PREFIX.to_string();
+    };
+    let prefixes = chat.data().read().await.get::<CommandPrefixes>().cloned().unwrap_or_default();
+    if let Some(prefix) = prefixes.read().await.get(&guild_id.get()) {
+        return prefix.clone();
+    }
+    match database.get_guild_settings(guild_id.get()).await {
+        Ok(settings) => {
+            let prefix = settings.and_then(|settings| settings.command_prefix).unwrap_or_else(|| constants::DEFAULT_COMMAND_PREFIX.to_string());
+            prefixes.write().await.insert(guild_id.get(), prefix.clone());
+            prefix
+        }
+        // not cached so the next message tries again
+        Err(error) => {
+            error!("Unable to get settings for guild {}: {:?}", guild_id.get(), error);
+            constants::DEFAULT_COMMAND_PREFIX.to_string()
+        }
+    }
+}
+
+/// Called after the prefix was saved, so the next message in the guild already uses it
+pub(crate) async fn cache_command_prefix(chat: &dyn ChatBackend, guild_id: GuildId, prefix: &str) {
+    if let Some(prefixes) = chat.data().read().await.get::<CommandPrefixes>() {
+        prefixes.write().await.insert(guild_id.get(), prefix.to_string());
+    }
+}
+
+/// Fills in the guild for rows created before everything was scoped by guild. Channels are looked up on discord since
+/// that's the only place that knows their guild. Admins, blacklisted users and games can only be given a guild when
+/// every channel turned out to be in the same one, otherwise they stay unscoped and the owner has to add them again
+pub(crate) async fn backfill_guild_ids(chat: &dyn ChatBackend, database: &dyn Store) {
+    let channel_ids = match database.get_channels_without_guild().await {
+        Ok(channel_ids) => channel_ids,
+        Err(error) => {
+            error!("Unable to get channels without a guild: {}", error);
+            return;
+        }
+    };
+    for discord_channel_id in channel_ids {
+        let guild_id = match chat.channel_guild(ChannelId::new(discord_channel_id as u64)).await {
+            Ok(Some(guild_id)) => guild_id,
+            Ok(None) => continue,
+            // the channel was deleted or the bot was removed from the guild, nothing can use these rows anymore
+            Err(e) => {
+                warn!("Unable to get the guild of channel {}: {:?}", discord_channel_id, e);
+                continue;
+            }
+        };
+        match database.backfill_guild_for_channel(discord_channel_id as u64, guild_id.get()).await {
+            Ok(updated) => info!("Backfilled guild {} for {} rows in channel {}", guild_id.get(), updated, discord_channel_id),
+            Err(error) => error!("Unable to backfill guild {} for channel {}: {}", guild_id.get(), discord_channel_id, error)
+        }
+    }
+    match database.get_known_guild_ids().await.as_deref() {
+        Ok([guild_id]) => match database.backfill_guild_for_unscoped_rows(*guild_id as u64).await {
+            Ok(0) => {}
+            Ok(updated) => info!("Backfilled guild {} for {} admins, blacklisted users and games", guild_id, updated),
+            Err(error) => error!("Unable to backfill guild {} for admins, blacklisted users and games: {}", guild_id, error)
+        },
+        Ok(_) => {}
+        Err(error) => error!("Unable to get the known guilds: {}", error)
+    }
+}
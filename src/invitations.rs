@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use std::time::Duration;
+use serenity::async_trait;
+use serenity::prelude::*;
+use tracing::{error, info};
+use crate::chat::ChatBackend;
+use crate::messages::Locale;
+use crate::store::Store;
+
+// the invitation is only worth it if it doesn't hold up the notifications
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(4);
+pub(crate) const DEFAULT_DAILY_TOKEN_BUDGET: i64 = 20_000;
+// discord messages can be 2000 characters long, the reply hint goes after the invitation
+const MAX_INVITATION_LENGTH: usize = 600;
+// the longest answer the model can give, a request that didn't come back is charged as much
+pub(crate) const MAX_INVITATION_TOKENS: u32 = 150;
+
+/// What the invitation is about
+pub(crate) struct InvitationRequest<'a> {
+    pub(crate) organizer: &'a str,
+    pub(crate) game: Option<&'a str>,
+    // what the organizer wrote after the command, it can mention a time like "in 20"
+    pub(crate) description: &'a str,
+    // when a scheduled game starts, as a discord timestamp that has to be kept as is
+    pub(crate) when: Option<&'a str>,
+    // everyone who is in so far, the organizer first
+    pub(crate) roster: &'a [&'a str],
+    pub(crate) target_size: Option<i32>,
+    pub(crate) locale: Locale,
+    // e.g. "an age of empires monk"
+    pub(crate) theme: Option<&'a str>,
+}
+
+pub(crate) struct Invitation {
+    pub(crate) text: String,
+    // what writing it cost, counted against the guild's daily budget
+    pub(crate) tokens: i64,
+}
+
+/// Writes the text of notification dms
+#[async_trait]
+pub(crate) trait InvitationWriter: Send + Sync {
+    async fn write(&self, request: &InvitationRequest<'_>) -> anyhow::Result<Invitation>;
+}
+
+/// The writer in the client's data, there is none when no language model is configured
+pub(crate) struct Invitations {
+    pub(crate) writer: Arc<dyn InvitationWriter>,
+    pub(crate) timeout: Duration,
+    // per guild and per utc day
+    pub(crate) daily_token_budget: i64,
+}
+
+impl TypeMapKey for Invitations {
+    type Value = Arc<Invitations>;
+}
+
+/// The invitation for a notification, or None to use the usual template. That's the case unless a language model is
+/// configured and the guild turned personalized invitations on, and also when the guild used up its tokens for the day,
+/// the model takes too long or anything goes wrong
+pub(crate) async fn write_invitation(chat: &dyn ChatBackend, database: &dyn Store, guild_id: u64, request: InvitationRequest<'_>) -> Option<String> {
+    let invitations = chat.data().read().await.get::<Invitations>().cloned()?;
+    let settings = match database.get_guild_settings(guild_id).await {
+        Ok(settings) => settings.unwrap_or_default(),
+        Err(error) => {
+            error!("Unable to get settings for guild {}: {:?}", guild_id, error);
+            return None;
+        }
+    };
+    if !settings.personalized_invitations {
+        return None;
+    }
+    let today = chrono::offset::Utc::now().date_naive();
+    match database.get_language_model_tokens(guild_id, today).await {
+        Ok(used) if used >= invitations.daily_token_budget => {
+            info!("Guild {} used its {} tokens for today, the invitation uses the template", guild_id, invitations.daily_token_budget);
+            return None;
+        }
+        Ok(_) => {}
+        Err(error) => {
+            error!("Unable to get the tokens guild {} used today: {:?}", guild_id, error);
+            return None;
+        }
+    }
+    let request = InvitationRequest { theme: settings.invitation_theme.as_deref(), ..request };
+    // the model may still have answered a request that failed or timed out, and that gets billed all the same
+    let (text, tokens) = match tokio::time::timeout(invitations.timeout, invitations.writer.write(&request)).await {
+        Ok(Ok(invitation)) => (Some(invitation.text), invitation.tokens),
+        Ok(Err(error)) => {
+            error!("Unable to write an invitation for guild {}: {:?}", guild_id, error);
+            (None, MAX_INVITATION_TOKENS as i64)
+        }
+        Err(_) => {
+            error!("Writing an invitation for guild {} took longer than {:?}", guild_id, invitations.timeout);
+            (None, MAX_INVITATION_TOKENS as i64)
+        }
+    };
+    if let Err(error) = database.add_language_model_tokens(guild_id, today, tokens).await {
+        error!("Unable to count the {} tokens guild {} used: {:?}", tokens, guild_id, error);
+    }
+    let text = text?;
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(text.chars().take(MAX_INVITATION_LENGTH).collect())
+}
@@ -0,0 +1,205 @@
+use serenity::all::{ButtonStyle, ChannelId, ComponentInteraction, CreateActionRow, CreateAllowedMentions, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage, GuildId, Message, MessageId, ReactionType};
+use tracing::{error, info};
+use chrono::{DateTime, Utc};
+use crate::chat::ChatBackend;
+use crate::commands::get_or_create_user;
+use crate::constants;
+use crate::errors::WololoError;
+use crate::messages::{get_guild_locale, get_locale, Messages, Roster};
+use crate::notifications;
+use crate::store::Store;
+use crate::structs::{Lobby, LobbyMember};
+
+const JOIN_BUTTON_PREFIX: &str = "lobby-join:";
+const DECLINE_BUTTON_PREFIX: &str = "lobby-decline:";
+
+// reacting to a notification dm with any other emoji counts as joining
+pub(crate) const DECLINE_EMOJIS: [&str; 3] = ["👎", "❌", "🚫"];
+
+pub(crate) fn lobby_buttons(messages: &dyn Messages, lobby_id: i32) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{JOIN_BUTTON_PREFIX}{lobby_id}")).label(messages.join_button()).style(ButtonStyle::Success),
+        CreateButton::new(format!("{DECLINE_BUTTON_PREFIX}{lobby_id}")).label(messages.not_this_time_button()).style(ButtonStyle::Secondary),
+    ])]
+}
+
+pub(crate) fn is_decline_emoji(emoji: &ReactionType) -> bool {
+    match emoji {
+        ReactionType::Unicode(emoji) => DECLINE_EMOJIS.contains(&emoji.as_str()),
+        _ => false
+    }
+}
+
+/// How many stacks someone can start and games they can schedule in a guild per window
+pub(crate) struct CallLimit {
+    pub(crate) limit: i32,
+    pub(crate) window_seconds: i64,
+}
+
+impl CallLimit {
+    /// The guild's, or `DEFAULT_CALL_LIMIT` stacks per `DEFAULT_CALL_WINDOW_SECONDS` when an admin didn't set one
+    pub(crate) async fn for_guild(database: &dyn Store, guild_id: u64) -> Result<Self, WololoError> {
+        let settings = database.get_guild_settings(guild_id).await?.unwrap_or_default();
+        Ok(CallLimit {
+            limit: settings.call_limit.unwrap_or(constants::DEFAULT_CALL_LIMIT),
+            window_seconds: settings.call_window_seconds.unwrap_or(constants::DEFAULT_CALL_WINDOW_SECONDS)
+        })
+    }
+
+    /// How many seconds the organizer has to wait before starting another stack or scheduling another game, None when
+    /// they can start one now
+    pub(crate) async fn wait_seconds(&self, database: &dyn Store, guild_id: u64, organizer_discord_id: u64, now: DateTime<Utc>) -> Result<Option<i64>, WololoError> {
+        // windows saved before they were capped can reach back further than dates go, nothing is older than the epoch
+        let since = chrono::Duration::try_seconds(self.window_seconds).and_then(|window| now.checked_sub_signed(window)).unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+        let started = database.get_call_times_for_organizer(guild_id, organizer_discord_id, since).await?;
+        let limit = self.limit.max(1) as usize;
+        if started.len() < limit {
+            return Ok(None);
+        }
+        // another one can be started once enough of them are older than the window
+        let oldest_that_counts = started[started.len() - limit];
+        Ok(Some(oldest_that_counts.timestamp().saturating_add(self.window_seconds).saturating_sub(now.timestamp()).max(1)))
+    }
+}
+
+/// The organizer counts towards the target size, so a lobby of 5 is full after 4 people join
+fn joined_count(members: &[LobbyMember]) -> usize {
+    members.iter().filter(|member| member.joined).count() + 1
+}
+
+fn summary_text(messages: &dyn Messages, lobby: &Lobby, members: &[LobbyMember]) -> String {
+    let mentions = |joined: bool| members.iter()
+        .filter(|member| member.joined == joined)
+        .map(|member| format!("<@{}>", member.discord_user_id))
+        .collect::<Vec<_>>();
+    let roster = Roster {
+        organizer: &format!("<@{}>", lobby.organizer_discord_id),
+        game: lobby.game_name.as_deref(),
+        description: &lobby.description,
+        accepted: &mentions(true),
+        declined: &mentions(false)
+    };
+    messages.stack_summary(&roster, joined_count(members), lobby.target_size, lobby.filled_at.is_some())
+}
+
+/// Posts the roster for a new lobby in the guild's language, the mentions in it are only for display so nobody gets
+/// pinged
+pub(crate) async fn post_summary(chat: &dyn ChatBackend, messages: &dyn Messages, lobby: &Lobby) -> serenity::Result<Message> {
+    let builder = CreateMessage::new()
+        .content(summary_text(messages, lobby, &[]))
+        .allowed_mentions(CreateAllowedMentions::new())
+        .components(lobby_buttons(messages, lobby.id));
+    chat.send_message(ChannelId::new(lobby.discord_channel_id as u64), builder).await
+}
+
+async fn refresh_summary(chat: &dyn ChatBackend, messages: &dyn Messages, lobby: &Lobby, members: &[LobbyMember]) {
+    let Some(summary_message_id) = lobby.summary_message_id else {
+        return;
+    };
+    let builder = EditMessage::new()
+        .content(summary_text(messages, lobby, members))
+        .allowed_mentions(CreateAllowedMentions::new());
+    if let Err(e) = chat.edit_message(ChannelId::new(lobby.discord_channel_id as u64), MessageId::new(summary_message_id as u64), builder).await {
+        error!("Unable to edit summary message for lobby {}: {:?}", lobby.id, e);
+    }
+}
+
+/// Records that a user joined (`Some(true)`), declined (`Some(false)`) or took back their answer (`None`), then updates
+/// the summary message and pings everyone if that filled the lobby
+pub(crate) async fn update_roster(chat: &dyn ChatBackend, database: &dyn Store, lobby_id: i32, discord_user_id: u64, joined: Option<bool>) -> Result<Lobby, WololoError> {
+    match joined {
+        Some(joined) => {
+            database.upsert_lobby_member(lobby_id, discord_user_id, joined).await?;
+            notifications::record_response(database, Some(lobby_id), None, discord_user_id, joined).await;
+        }
+        None => {
+            database.delete_lobby_member(lobby_id, discord_user_id).await?;
+        }
+    }
+    let mut lobby = database.get_lobby(lobby_id).await?;
+    let members = database.get_lobby_members(lobby_id).await?;
+    let messages = get_guild_locale(database, lobby.guild_id.map(|guild_id| guild_id as u64)).await.messages();
+    if lobby.filled_at.is_none() && joined_count(&members) >= lobby.target_size as usize && database.update_filled_at_for_lobby(lobby_id).await? {
+        lobby = database.get_lobby(lobby_id).await?;
+        announce_full_lobby(chat, messages, &lobby, &members).await;
+    }
+    refresh_summary(chat, messages, &lobby, &members).await;
+    Ok(lobby)
+}
+
+async fn announce_full_lobby(chat: &dyn ChatBackend, messages: &dyn Messages, lobby: &Lobby, members: &[LobbyMember]) {
+    if let Some(filled_at) = lobby.filled_at {
+        info!("Lobby {} filled up {} seconds after it was created", lobby.id, filled_at.timestamp() - lobby.created_at.timestamp());
+    }
+    let mut mentions = vec![format!("<@{}>", lobby.organizer_discord_id)];
+    mentions.extend(members.iter().filter(|member| member.joined).map(|member| format!("<@{}>", member.discord_user_id)));
+    let mut builder = CreateMessage::new().content(messages.stack_full(&mentions.join(" ")));
+    if let Some(summary_message_id) = lobby.summary_message_id {
+        builder = builder.reference_message((ChannelId::new(lobby.discord_channel_id as u64), MessageId::new(summary_message_id as u64)));
+    }
+    if let Err(e) = chat.send_message(ChannelId::new(lobby.discord_channel_id as u64), builder).await {
+        error!("Error sending message: {:?}", e);
+    }
+}
+
+/// Handles the join/decline buttons on both the summary message and the notification dms
+pub(crate) async fn handle_component(chat: &dyn ChatBackend, interaction: &ComponentInteraction, database: &dyn Store) {
+    let custom_id = interaction.data.custom_id.as_str();
+    let (lobby_id, joined) = if let Some(lobby_id) = custom_id.strip_prefix(JOIN_BUTTON_PREFIX) {
+        (lobby_id, true)
+    }
+    else if let Some(lobby_id) = custom_id.strip_prefix(DECLINE_BUTTON_PREFIX) {
+        (lobby_id, false)
+    }
+    else {
+        return;
+    };
+    let Ok(lobby_id) = lobby_id.parse::<i32>() else {
+        return;
+    };
+    let user_discord_id = interaction.user.id.get();
+    let messages = get_locale(database, interaction.guild_id.map(GuildId::get), user_discord_id).await.messages();
+    let content = match database.get_lobby(lobby_id).await {
+        // the button can be on a dm, so the guild it counts for is the lobby's
+        Ok(lobby) => match database.is_user_blacklisted(lobby.guild_id.map(|guild_id| guild_id as u64), user_discord_id).await {
+            Ok(true) => {
+                info!("Ignoring lobby button from blacklisted user {}", user_discord_id);
+                None
+            }
+            Err(error) => {
+                error!("Unable to check whether user {} is blacklisted: {}", user_discord_id, error);
+                Some(messages.database_trouble())
+            }
+            Ok(false) if lobby.organizer_discord_id == user_discord_id as i64 => Some(messages.organizing_stack()),
+            Ok(false) => {
+                // lobby_members references users, so people who click the button in the channel get registered
+                if let Err(error) = get_or_create_user(database, user_discord_id).await {
+                    error!("Unable to register user {}: {}", user_discord_id, error);
+                    Some(messages.registration_failed())
+                }
+                else if let Err(error) = update_roster(chat, database, lobby_id, user_discord_id, Some(joined)).await {
+                    error!("Unable to update roster for lobby {}: {:?}", lobby_id, error);
+                    Some(messages.stack_update_failed())
+                }
+                else if joined {
+                    Some(messages.joined_stack(&format!("<@{}>", lobby.organizer_discord_id)))
+                }
+                else {
+                    Some(messages.told_organizer(&format!("<@{}>", lobby.organizer_discord_id)))
+                }
+            }
+        },
+        Err(WololoError::NotFound) => Some(messages.stack_not_found()),
+        Err(error) => {
+            error!("Unable to get lobby {}: {}", lobby_id, error);
+            Some(messages.database_trouble())
+        }
+    };
+    let response = match content {
+        Some(content) => CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(content).ephemeral(true)),
+        None => CreateInteractionResponse::Acknowledge
+    };
+    if let Err(e) = chat.respond_to_interaction(interaction.id, &interaction.token, response).await {
+        error!("Error sending interaction response: {:?}", e);
+    }
+}
@@ -2,273 +2,201 @@ mod queries;
 mod structs;
 mod writes;
 mod constants;
-
+mod commands;
+mod slash_commands;
+mod lobbies;
+mod parsing;
+mod notifications;
+mod scheduled_games;
+mod permissions;
+mod errors;
+mod subscriptions;
+mod direct_messages;
+mod games;
+mod guilds;
+mod messages;
+mod chat;
+mod config;
+mod reactions;
+mod store;
+mod game_calls;
+mod openai;
+mod audit;
+mod invitations;
+mod stats;
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(feature = "shuttle"))]
 use anyhow::Context as _;
 use serenity::async_trait;
 use serenity::model::channel::Message;
 use serenity::model::gateway::Ready;
 use serenity::prelude::*;
-use shuttle_openai::async_openai;
-use shuttle_openai::async_openai::config::OpenAIConfig;
-use shuttle_runtime::SecretStore;
 use tracing::{error, info};
-use crate::queries::{get_all_pings_except_for_user, get_parent_message_id_for_child_message_id, get_ping, get_user, is_user_admin};
-use crate::structs::{ParentMessageChildMessage, WololoUser};
-use crate::writes::{create_admin_user, create_child_for_message, create_ping, create_user, delete_child_for_message, delete_ping, update_notified_at_for_ping};
-use regex::Regex;
-use serenity::all::{ChannelId, MessageId, Reaction};
+#[cfg(not(feature = "shuttle"))]
+use tracing_subscriber::EnvFilter;
+use crate::chat::{ChatBackend, SerenityBackend};
+use crate::commands::{handle_command, handle_message, route_component, Invocation};
+use crate::config::Config;
+use crate::game_calls::GameCalls;
+use crate::invitations::Invitations;
+use crate::guilds::CommandPrefixes;
+use crate::messages::get_locale;
+use crate::openai::OpenAi;
+use crate::permissions::Permissions;
+use crate::store::{InMemoryStore, PgStore, Store};
+use serenity::all::{Command, CreateInteractionResponse, CreateInteractionResponseMessage, Interaction, Reaction};
 
 struct Bot {
-    database: sqlx::PgPool
+    store: Arc<dyn Store>,
+    // ready fires again after reconnects, the scheduler and backfill should only run once
+    background_tasks_started: AtomicBool
 }
 
-const NOTIFICATION_TIMEOUT_SECONDS: u64 = 60 * 2;  // 2 MIN
+#[async_trait]
+impl EventHandler for Bot {
+    async fn message(&self, ctx: Context, msg: Message) {
+        handle_message(&SerenityBackend::new(&ctx), self.store.as_ref(), &msg).await;
+    }
 
-async fn handle_command(command: &str, rest_of_command: Option<&str>, ctx: Context, msg: &Message, database: &sqlx::PgPool) {
-    let user_discord_id = msg.author.id.get();
-    let discord_channel_id = msg.channel_id.get();
-    let discord_channel_name = msg.channel_id.name(&ctx.http).await.unwrap();
-    match command {
-        constants::HELP_CMD => if let Err(e) = msg.channel_id.say(&ctx.http, constants::help_text()).await {
-            error!("Error sending message: {:?}", e);
-        }
-        constants::REGISTER_CMD =>   {
-            let user = get_or_create_user(database, user_discord_id).await;
-            match user {
-                Some(_) => {
-                    if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} I have successfully registered you, or you are already registered!", msg.author.name)).await {
-                        error!("Error sending message: {:?}", e);
-                    }
-                }
-                None => {
-                    if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} I was unable to register you, try again later.", msg.author.name)).await {
-                        error!("Error sending message: {:?}", e);
-                    }
-                }
-            }
-        }
-        constants::GAME_NOTIFICATION_ON_CMD => {
-            let ping = get_ping(database, user_discord_id, discord_channel_id).await;
-            match ping {
-                Some(_) => {
-                    if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} You are already signed up for game search notifications in #{}", msg.author.name, discord_channel_name)).await {
-                        error!("Error sending message: {:?}", e);
-                    }
-                }
-                None => {
-                    let new_ping = create_ping(database, user_discord_id, discord_channel_id).await;
-                    match new_ping {
-                        Some(_) => {
-                            if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} You are now signed up for game search notifications in #{}", msg.author.name, discord_channel_name)).await {
-                                error!("Error sending message: {:?}", e);
-                            }
-                        }
-                        None => {
-                            if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} I was unable to sign you up for game search notifications in #{}", msg.author.name, discord_channel_name)).await {
-                                error!("Error sending message: {:?}", e);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        constants::GAME_NOTIFICATION_OFF_CMD => {
-            let ping = get_ping(database, user_discord_id, discord_channel_id).await;
-            if ping.is_some() {
-                if delete_ping(database, ping.unwrap()).await {
-                    if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} You have been removed from game search notifications in #{}", msg.author.name, discord_channel_name)).await {
-                        error!("Error sending message: {:?}", e);
-                    }
-                }
-                else {
-                    if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} I was unable to remove you from game search notifications in #{}", msg.author.name, discord_channel_name)).await {
-                        error!("Error sending message: {:?}", e);
-                    }
-                }
-            }
-            else {
-                if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} You aren't signed up for game search notifications in #{}", msg.author.name, discord_channel_name)).await {
-                    error!("Error sending message: {:?}", e);
-                }
-            }
-        }
-        constants::ANY_GAMERS_CMD => {
-            let user = get_user(database, user_discord_id).await;
-            if user.is_none() {
-                if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} You aren't registered in #{}, you can register using {}", msg.author.name, discord_channel_name, constants::REGISTER_CMD)).await {
-                    error!("Error sending message: {:?}", e);
-                }
-            }
-            else {
-                let now = chrono::offset::Utc::now();
-                let pings = get_all_pings_except_for_user(database, user_discord_id, discord_channel_id).await;
-                for ping in pings {
-                    let should_notify;
-                    if ping.last_notified.is_some() {
-                        should_notify = (now.timestamp() - ping.last_notified.unwrap().timestamp()) >= NOTIFICATION_TIMEOUT_SECONDS as i64;
-                    }
-                    else {
-                        // last_notified is None
-                        should_notify = true
-                    }
-                    if should_notify {
-                        let user = serenity::all::UserId::new(ping.user_discord_id as u64);
-                        let mut additional_context = "".to_string();
-                        if rest_of_command.is_some() {
-                            let rest_of_command_str = rest_of_command.unwrap();
-                            if rest_of_command_str != "" {
-                                additional_context = format!("They also said this: {rest_of_command_str}")
-                            }
-                        }
+    async fn reaction_add(&self, ctx: Context, add_reaction: Reaction) {
+        reactions::handle_reaction_add(&SerenityBackend::new(&ctx), self.store.as_ref(), add_reaction.message_id, add_reaction.user_id, &add_reaction.emoji).await;
+    }
 
-                        let builder = serenity::builder::CreateMessage::new().content(format!("@{} is trying to get a stack for dota in #{}. {}\n\n(You can unsubscribe from notifications in #{} by going there and typing {}. You can also let them know you are joining by reacting to this message.)", msg.author.name, discord_channel_name, additional_context, discord_channel_name, constants::GAME_NOTIFICATION_OFF_CMD));
-                        let result = user.direct_message(&ctx.http, builder).await;
-                        if result.is_err() {
-                            error!("Error sending dm to {} (id {}): {:?}", msg.author.name, user_discord_id, result.unwrap_err());
-                        }
-                        else {
-                            let child_message = result.unwrap();
-                            let parent_msg_child_msg = ParentMessageChildMessage {
-                                parent: msg.id.get() as i64,
-                                child: child_message.id.get() as i64,
-                                parent_channel_id: discord_channel_id as i64,
-                                child_channel_id: child_message.channel_id.get() as i64
-                            };
-                            if let Err(error) = create_child_for_message(database, parent_msg_child_msg).await {
-                                error!("Unable to save message {} as child for parent message {}: {}", child_message.id.get(), msg.id.get(), error);
-                            }
-                            if update_notified_at_for_ping(database, ping).await.is_err() {
-                                error!("Unable to update notified_at for user {}", user.get())
-                            }
-                        }
-                    }
+    async fn reaction_remove(&self, ctx: Context, removed_reaction: Reaction) {
+        reactions::handle_reaction_remove(&SerenityBackend::new(&ctx), self.store.as_ref(), removed_reaction.message_id, removed_reaction.user_id).await;
+    }
 
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let chat = SerenityBackend::new(&ctx);
+        match interaction {
+            Interaction::Command(command_interaction) => {
+                let Some(command) = slash_commands::text_command(&command_interaction.data.name) else {
+                    return;
+                };
+                info!("Received slash command {} from discord user {} in channel {}", command, command_interaction.user.id.get(), command_interaction.channel_id.get());
+                // confirmations are only shown to the user who invoked the command
+                let defer = CreateInteractionResponse::Defer(CreateInteractionResponseMessage::new().ephemeral(true));
+                if let Err(e) = chat.respond_to_interaction(command_interaction.id, &command_interaction.token, defer).await {
+                    error!("Error deferring interaction response: {:?}", e);
+                    return;
                 }
+                let prefix = guilds::get_command_prefix(&chat, self.store.as_ref(), command_interaction.guild_id).await;
+                let locale = get_locale(self.store.as_ref(), command_interaction.guild_id.map(|guild_id| guild_id.get()), command_interaction.user.id.get()).await;
+                let invocation = Invocation::from_interaction(&command_interaction, prefix, locale);
+                handle_command(command, &invocation, &chat, self.store.as_ref()).await;
+                invocation.finish(&chat).await;
             }
-        }
-        "!admin" => {
-            if let Some(_) = get_user(database, user_discord_id).await {
-                if is_user_admin(database, user_discord_id).await.is_ok() {
-                    // caller is an admin, lets add the
-                    for mentioned_user in &msg.mentions {
-                        if is_user_admin(database, mentioned_user.id.get()).await.is_ok() {
-                            if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} {} is already an admin.", msg.author.name, mentioned_user.name)).await {
-                                error!("Error sending message: {:?}", e);
-                            }
-                        }
-                        else if create_admin_user(database, mentioned_user.id.get()).await.is_ok() {
-                            if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} {} has been added as an admin.", msg.author.name, mentioned_user.name)).await {
-                                error!("Error sending message: {:?}", e);
-                            }
-                        }
-                        else {
-                            if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} I was unable to add {} as an admin.", msg.author.name, mentioned_user.name)).await {
-                                error!("Error sending message: {:?}", e);
-                            }
-                        }
-                    }
-
-                }
-                else {
-                    if let Err(e) = msg.channel_id.say(&ctx.http, format!("@{} You are not an admin.", msg.author.name)).await {
-                        error!("Error sending message: {:?}", e);
-                    }
-                }
+            Interaction::Component(component_interaction) => {
+                route_component(&chat, &component_interaction, self.store.as_ref()).await;
             }
-        }
-        _ => {}
-    }
-}
-
-async fn get_or_create_user(pool: &sqlx::PgPool, discord_id: u64) -> Option<WololoUser> {
-    let mut user = get_user(pool, discord_id).await;
-    if user.is_none() {
-        user = create_user(pool, discord_id).await;
-        // we could run into an error if we for some reason have already created this user
-        // pretty overkill for this but eh
-        if user.is_none() {
-            user = get_user(pool, discord_id).await;
+            Interaction::Autocomplete(autocomplete_interaction) => {
+                slash_commands::handle_autocomplete(&chat, &autocomplete_interaction, self.store.as_ref()).await;
+            }
+            _ => {}
         }
     }
-    user
-
-}
 
-#[async_trait]
-impl EventHandler for Bot {
-    async fn message(&self, ctx: Context, msg: Message) {
-        let stripped_content = msg.content.trim();
-
-        let user_discord_id = msg.author.id.get();
-        let discord_channel_id = msg.channel_id.get();
-        let discord_channel_name = msg.channel_id.name(&ctx.http).await.unwrap();
-        // don't respond to bots
-        if msg.author.bot {
-            return;
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        info!("{} is connected!", ready.user.name);
+        if let Err(e) = Command::set_global_commands(&ctx.http, slash_commands::create_commands()).await {
+            error!("Unable to register slash commands: {:?}", e);
         }
-        info!("Received message from discord user {} in channel {} ({})", user_discord_id, discord_channel_id, discord_channel_name);
-        // check commands first
-        let command_regex = Regex::new(r"^(!\S*)(.*)").unwrap();
-        if let Some(captures) = command_regex.captures(stripped_content) {
-            if let Some(command) = captures.get(1) {
-                let mut rest_of_command = None;
-                // if they have other text after the command we may want it, so get it
-                if let Some(rest_of_command_match) = captures.get(2) {
-                    rest_of_command = Some(rest_of_command_match.as_str().trim())
-                }
-
-                handle_command(command.as_str(), rest_of_command, ctx, &msg, &self.database).await;
-            }
+        if !self.background_tasks_started.swap(true, Ordering::SeqCst) {
+            let chat = SerenityBackend::new(&ctx);
+            let (backfill_chat, backfill_store) = (chat.clone(), self.store.clone());
+            tokio::spawn(async move { guilds::backfill_guild_ids(&backfill_chat, backfill_store.as_ref()).await });
+            tokio::spawn(scheduled_games::run_scheduler(Arc::new(chat), self.store.clone()));
         }
-
     }
-    async fn reaction_add(&self, ctx: Context, add_reaction: Reaction) {
-        let message_with_reaction_id = add_reaction.message_id.get();
-        if let Ok(parent_msg_child_msg) = get_parent_message_id_for_child_message_id(&self.database, message_with_reaction_id).await {
-            info!("Received a reaction for a game notification dm: {} in channel: {}", parent_msg_child_msg.child, parent_msg_child_msg.child_channel_id);
-            if let Ok(parent_msg) = &ctx.http.get_message(ChannelId::from(parent_msg_child_msg.parent_channel_id as u64), MessageId::from(parent_msg_child_msg.parent as u64)).await {
-                if let Ok(reactor_user) = add_reaction.user(&ctx.http).await {
-                    if let Err(error) = parent_msg.reply(&ctx.http, format!("@{}: {}", reactor_user.name, add_reaction.emoji)).await {
-                        error!("Error sending reply message: {:?}", error);
-                    }
-                    else {
-                        // don't allow further reactions to cause replies
-                        if let Err(error) = delete_child_for_message(&self.database, parent_msg_child_msg).await {
-                            error!("Unable to delete message_children_row: {:?}", error);
-                        }
-                    }
+}
 
-                }
-            }
+/// Builds the same bot for both entry points
+async fn build_client(config: Config, store: Arc<dyn Store>, openai: Option<OpenAi>) -> serenity::Result<Client> {
+    // Set gateway intents, which decides what events the bot will be notified about
+    let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT | GatewayIntents::DIRECT_MESSAGES | GatewayIntents::DIRECT_MESSAGE_REACTIONS;
+    let bot = Bot {
+        store,
+        background_tasks_started: AtomicBool::new(false)
+    };
+    let mut builder = Client::builder(&config.discord_token, intents)
+        .event_handler(bot)
+        .type_map_insert::<Permissions>(Arc::new(config.permissions))
+        .type_map_insert::<CommandPrefixes>(Arc::default());
+    match openai {
+        Some(openai) => {
+            let openai = Arc::new(openai);
+            let game_calls = GameCalls {
+                classifier: openai.clone(),
+                daily_token_budget: config.invitation_token_budget
+            };
+            let invitations = Invitations {
+                writer: openai.clone(),
+                timeout: invitations::DEFAULT_TIMEOUT,
+                daily_token_budget: config.invitation_token_budget
+            };
+            builder = builder
+                .type_map_insert::<GameCalls>(Arc::new(game_calls))
+                .type_map_insert::<Invitations>(Arc::new(invitations));
         }
+        None => info!("OPENAI_API_KEY isn't set, game calls in plain words won't be detected and notifications use the usual text")
     }
+    builder.await
+}
 
-
-    async fn ready(&self, _: Context, ready: Ready) {
-        info!("{} is connected!", ready.user.name);
-    }
+fn in_memory_store() -> Arc<dyn Store> {
+    info!("Running with an in-memory store, nothing will be saved");
+    Arc::new(InMemoryStore::new())
 }
 
+#[cfg(feature = "shuttle")]
 #[shuttle_runtime::main]
 async fn serenity(
-    #[shuttle_runtime::Secrets] secrets: SecretStore,
+    #[shuttle_runtime::Secrets] secrets: shuttle_runtime::SecretStore,
     #[shuttle_shared_db::Postgres] pool: sqlx::PgPool,
     #[shuttle_openai::OpenAI(api_key = "{secrets.OPENAI_API_KEY}")]
-    _openai: async_openai::Client<OpenAIConfig>,
+    openai: shuttle_openai::async_openai::Client<shuttle_openai::async_openai::config::OpenAIConfig>,
 ) -> shuttle_serenity::ShuttleSerenity {
-    // Get the discord token set in `Secrets.toml`
-    let token = secrets
-        .get("DISCORD_TOKEN")
-        .context("'DISCORD_TOKEN' was not found")?;
-
-    // Set gateway intents, which decides what events the bot will be notified about
-    let intents = GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT | GatewayIntents::DIRECT_MESSAGE_REACTIONS;
-    let bot = Bot {
-        database: pool
+    // Get the discord token and the rest of the settings from `Secrets.toml`
+    let config = Config::from_settings(|key| secrets.get(key))?;
+    let store = if config.ephemeral {
+        in_memory_store()
+    }
+    else {
+        Arc::new(PgStore::prepare(pool, config.run_migrations).await?)
     };
-    let client = Client::builder(&token, intents)
-        .event_handler(bot)
-        .await
-        .expect("Err creating client");
+    let openai = OpenAi::new(openai, config.openai_model.clone());
+    let client = build_client(config, store, Some(openai)).await.expect("Err creating client");
     Ok(client.into())
 }
+
+/// Self-hosted, without shuttle
+#[cfg(not(feature = "shuttle"))]
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+    let settings = config::Settings::load()?;
+    let mut config = Config::from_settings(|key| settings.get(key))?;
+    // the standalone binary also takes `--ephemeral`, shuttle doesn't pass it arguments
+    config.ephemeral |= std::env::args().any(|arg| arg == "--ephemeral");
+    let store = if config.ephemeral {
+        in_memory_store()
+    }
+    else {
+        let database_url = settings.get("DATABASE_URL").context("'DATABASE_URL' was not found")?;
+        let pool = sqlx::postgres::PgPoolOptions::new().connect(&database_url).await.context("Unable to connect to the database")?;
+        Arc::new(PgStore::prepare(pool, config.run_migrations).await?)
+    };
+    let openai = settings.get("OPENAI_API_KEY").filter(|api_key| !api_key.trim().is_empty()).map(|api_key| {
+        let client = async_openai::Client::with_config(async_openai::config::OpenAIConfig::new().with_api_key(api_key));
+        OpenAi::new(client, config.openai_model.clone())
+    });
+    let mut client = build_client(config, store, openai).await.context("Err creating client")?;
+    client.start().await.context("Client stopped")?;
+    Ok(())
+}
@@ -0,0 +1,796 @@
+use crate::constants::{DEFAULT_LOBBY_SIZE, STOP_ALL_REPLY, STOP_REPLY};
+use crate::audit::AuditAction;
+use super::{AuditLine, Commands, Messages, Notification, Roster, ServerSettings, Stats};
+
+pub(super) struct English;
+
+impl Messages for English {
+    fn help_text(&self, c: &Commands) -> String {
+        format!("Here are my commands:
+{}: show this message
+{}: add yourself to the list of users I interact with
+{}: delete everything I know about you, including your notification settings and any stacks or games you organized
+{}: enable notifications in the current channel when another registered user invokes the {} or {} command. Add a game to only hear about that game, e.g. '{} aoe2'
+{}: disable game search notifications in the current channel, or only for one game, e.g. '{} aoe2'
+{}: list every channel you get game search notifications for, with buttons to unsubscribe. The slash command also works in dms
+{}: send a dm to all registered users who have enabled game notifications in the current channel and start a stack they can join. Start with a game to only notify its subscribers, e.g. '{} aoe2 ranked tonight'. Add size=<n> to change the size of the stack (default is the game's party size, or {})
+{}: schedule a game for later and send a dm to everyone who enabled game notifications in the current channel, e.g. '{} tonight 9pm ranked' or '{} aoe2 in 2h'. Everyone who is coming gets a reminder and a ping when it starts
+{}: list the games you can subscribe to and start stacks for
+{}: list the games scheduled in the current channel
+{}: cancel a game you scheduled, e.g. '{} 3'. Admins can cancel any game
+{}: set the minimum time between game notifications I send you in any channel, e.g. '{} 1h'. Use '{} off' to get every notification
+{}: show or set your timezone, e.g. '{} America/Toronto'
+{}: set hours when I shouldn't send you game notifications, e.g. '{} 23:00-08:00 America/Toronto'. Use '{} off' to turn them off
+{}: show or set the language I talk to you in, e.g. '{} fr'. Use '{} off' to go back to the server's language
+{}: show how often game notifications can be sent in the current channel
+{}: show this server's settings
+{}: show who started the most stacks and games and who joined the most in the last 30 days, the busiest hour of each channel and how often you join
+You can also reply to my game notification dms: '{STOP_REPLY}' unsubscribes you from that channel, '{STOP_ALL_REPLY}' from every channel, and anything else (e.g. 'omw 10m') is passed along to the organizer in a thread
+-----------ADMIN ONLY------------
+{}: adds all mentioned users as admins. For example, '{} @<some guy> would add <some guy> as an admin. Use '{} remove @<some guy>' to remove an admin (only the owner can) and '{} list' to see who the admins are
+{}: stops all mentioned users from using {}, {}, {} and {}, and ignores their reactions
+{}: removes all mentioned users from the blacklist
+{} add <name> [size=<n>] [emoji] [aliases]: add a game people can subscribe to, e.g. '{} add aoe2 size=8 🏰 aoe age'. Use '{} remove <name>' to remove a game along with its subscriptions
+{} <duration>: set how often game notifications can be sent to the same user in the current channel, e.g. '{} 10m'
+{} help-message <text>: show some text above my commands in {}, or 'off' to remove it
+{} cooldown <duration>: set the notification cooldown for channels that don't have their own, e.g. '{} cooldown 5m', or 'off' to go back to the default
+{} prefix <prefix>: change what my commands start with in this server, e.g. '{} prefix ?', or 'off' to go back to !
+{} language <language>: change the language I use in this server, e.g. '{} language fr'. Everyone can still pick their own with {}
+{} invitations on|off: have game notifications written as personalized invitations by the language model
+{} theme <text>: write the invitations in someone's voice, e.g. '{} theme an age of empires monk', or 'off' to remove it
+{} game-calls on|off: when the language model thinks a message is someone looking for players, offer them to notify the channel's subscribers
+{} mod-log <#channel>: also post every admin action in a channel, or 'off' to stop
+{} call-limit <n> <duration>: how many stacks everyone but admins can start, e.g. '{} call-limit 3 30m', or 'off' to go back to the default
+{} [n]: show the last n admin actions in this server, 10 by default",
+            c.help,
+            c.register,
+            c.unregister,
+            c.game_notification_on, c.any_gamers, c.schedule, c.game_notification_on,
+            c.game_notification_off, c.game_notification_off,
+            c.my_subscriptions,
+            c.any_gamers, c.any_gamers, DEFAULT_LOBBY_SIZE,
+            c.schedule, c.schedule, c.schedule,
+            c.games,
+            c.scheduled,
+            c.cancel_game, c.cancel_game,
+            c.notification_gap, c.notification_gap, c.notification_gap,
+            c.timezone, c.timezone,
+            c.quiet_hours, c.quiet_hours, c.quiet_hours,
+            c.language, c.language, c.language,
+            c.channel_cooldown,
+            c.server_settings,
+            c.stats,
+            c.add_admins, c.add_admins, c.add_admins, c.add_admins,
+            c.blacklist, c.any_gamers, c.schedule, c.register, c.game_notification_on,
+            c.unblacklist,
+            c.games, c.games, c.games,
+            c.channel_cooldown, c.channel_cooldown,
+            c.server_settings, c.help,
+            c.server_settings, c.server_settings,
+            c.server_settings, c.server_settings,
+            c.server_settings, c.server_settings, c.language,
+            c.server_settings,
+            c.server_settings, c.server_settings,
+            c.server_settings,
+            c.server_settings,
+            c.server_settings, c.server_settings,
+            c.audit)
+    }
+
+    fn database_trouble(&self) -> String {
+        "I'm having database trouble right now, try again later.".to_string()
+    }
+
+    fn not_registered(&self, register_command: &str) -> String {
+        format!("You aren't registered, you can register using {register_command}")
+    }
+
+    fn not_an_admin(&self) -> String {
+        "You are not an admin.".to_string()
+    }
+
+    fn only_in_a_server(&self, command: &str) -> String {
+        format!("{command} only works in a server")
+    }
+
+    fn unknown_duration(&self, text: &str) -> String {
+        format!("I don't understand \"{text}\", try something like 30m or 1h")
+    }
+
+    fn unknown_duration_or_off(&self, text: &str) -> String {
+        format!("I don't understand \"{text}\", try something like 30m, 1h or off")
+    }
+
+    fn unknown_timezone(&self, text: &str) -> String {
+        format!("I don't know the timezone \"{text}\", try something like America/Toronto or Europe/Paris")
+    }
+
+    fn registration_failed(&self) -> String {
+        "I was unable to register you, try again later.".to_string()
+    }
+
+    fn also_said(&self, description: &str) -> String {
+        format!("They also said this: {description}")
+    }
+
+    fn registered(&self) -> String {
+        "I have successfully registered you, or you are already registered!".to_string()
+    }
+
+    fn unregistered(&self) -> String {
+        "I deleted all of your data.".to_string()
+    }
+
+    fn unregistered_check_dms(&self) -> String {
+        "I deleted all of your data, check your dms for the details.".to_string()
+    }
+
+    fn unregistered_dm(&self, register_command: &str) -> String {
+        format!("I deleted everything I had stored about you: your registration, your game notification subscriptions and settings, and any stacks or games you organized or answered. You can register again at any time with {register_command}.")
+    }
+
+    fn notifications_for(&self, game: Option<&str>, channel_name: &str) -> String {
+        match game {
+            Some(game) => format!("game search notifications for {game} in #{channel_name}"),
+            None => format!("game search notifications in #{channel_name}")
+        }
+    }
+
+    fn already_signed_up(&self, notifications: &str) -> String {
+        format!("You are already signed up for {notifications}")
+    }
+
+    fn signed_up(&self, notifications: &str) -> String {
+        format!("You are now signed up for {notifications}")
+    }
+
+    fn removed_from(&self, notifications: &str) -> String {
+        format!("You have been removed from {notifications}")
+    }
+
+    fn signed_up_for_every_game(&self, channel_name: &str, off_command: &str) -> String {
+        format!("You are signed up for every game in #{channel_name}, use {off_command} to stop those notifications")
+    }
+
+    fn not_signed_up(&self, notifications: &str) -> String {
+        format!("You aren't signed up for {notifications}")
+    }
+
+    fn unknown_game(&self, name: &str) -> String {
+        format!("I don't know the game \"{name}\"")
+    }
+
+    fn known_games(&self, names: &[&str]) -> String {
+        format!("Known games: {}", names.join(", "))
+    }
+
+    fn no_games(&self) -> String {
+        "No games have been added yet.".to_string()
+    }
+
+    fn game_description(&self, label: &str, aliases: &[String], default_party_size: i32) -> String {
+        let aliases = if aliases.is_empty() {
+            "".to_string()
+        }
+        else {
+            format!(" ({})", aliases.join(", "))
+        };
+        format!("{label}{aliases}: stacks of {default_party_size}")
+    }
+
+    fn games_list(&self, descriptions: &[String]) -> String {
+        format!("Games you can subscribe to and start stacks for:\n{}", descriptions.join("\n"))
+    }
+
+    fn game_options_help(&self, games_command: &str) -> String {
+        format!("Tell me the game's name, and optionally its stack size, emoji and aliases, e.g. {games_command} add aoe2 size=8 🏰 aoe age")
+    }
+
+    fn game_name_taken(&self, name: &str, game: &str) -> String {
+        format!("\"{name}\" is already used by {game}")
+    }
+
+    fn game_added(&self, description: &str, subscribe_command: &str) -> String {
+        format!("I added {description}. People can subscribe to it with {subscribe_command}")
+    }
+
+    fn game_exists(&self, name: &str) -> String {
+        format!("{name} already exists")
+    }
+
+    fn shared_game(&self, name: &str) -> String {
+        format!("{name} is shared by every server, only the owner can remove it")
+    }
+
+    fn game_removed(&self, name: &str) -> String {
+        format!("I removed {name} along with everyone's subscriptions to it")
+    }
+
+    fn join_button(&self) -> String {
+        "I'm in".to_string()
+    }
+
+    fn not_this_time_button(&self) -> String {
+        "Not this time".to_string()
+    }
+
+    fn stack_failed(&self, channel_name: &str) -> String {
+        format!("I was unable to start a stack in #{channel_name}, try again later.")
+    }
+
+    fn stack_notification(&self, notification: &Notification, target_size: i32) -> String {
+        let Notification { organizer, game, channel_name, description, invitation, reply_hint, decline_emoji } = *notification;
+        let for_game = game.map(|game| format!(" for {game}")).unwrap_or_default();
+        let also_said = if description.is_empty() { "".to_string() } else { self.also_said(description) };
+        let text = invitation.map(str::to_string).unwrap_or_else(|| format!("@{organizer} is trying to get a stack of {target_size}{for_game} in #{channel_name}. {also_said}"));
+        format!("{text}\n\n({reply_hint} You can also let them know if you are joining with the buttons below or by reacting to this message, {decline_emoji} means you can't make it.)")
+    }
+
+    fn stack_summary(&self, roster: &Roster, joined_count: usize, target_size: i32, full: bool) -> String {
+        let Roster { organizer, game, description, accepted: joined, declined } = *roster;
+        let for_game = game.map(|game| format!(" for {game}")).unwrap_or_default();
+        let mut summary = if full {
+            format!("{organizer}'s stack{for_game} is full ({joined_count}/{target_size})!")
+        }
+        else {
+            format!("{organizer} is looking for a stack{for_game} ({joined_count}/{target_size}).")
+        };
+        if !description.is_empty() {
+            summary.push_str(&format!(" {}", self.also_said(description)));
+        }
+        if !joined.is_empty() {
+            summary.push_str(&format!("\nJoined: {}", joined.join(", ")));
+        }
+        if !declined.is_empty() {
+            summary.push_str(&format!("\nDeclined: {}", declined.join(", ")));
+        }
+        summary
+    }
+
+    fn stack_full(&self, mentions: &str) -> String {
+        format!("{mentions} your stack is full, time to play!")
+    }
+
+    fn organizing_stack(&self) -> String {
+        "You are organizing this stack!".to_string()
+    }
+
+    fn stack_update_failed(&self) -> String {
+        "I was unable to update the stack, try again later.".to_string()
+    }
+
+    fn joined_stack(&self, organizer: &str) -> String {
+        format!("You joined {organizer}'s stack!")
+    }
+
+    fn told_organizer(&self, organizer: &str) -> String {
+        format!("I let {organizer} know you can't make it.")
+    }
+
+    fn stack_not_found(&self) -> String {
+        "I couldn't find that stack anymore.".to_string()
+    }
+
+    fn notification_summary(&self, notified: usize, skipped_cooldown: usize, skipped_quiet_hours: usize, skipped_hourly_cap: usize, failed: usize) -> String {
+        let mut description = format!("{notified} notified");
+        for (count, reason) in [(skipped_cooldown, "skipped (cooldown)"), (skipped_quiet_hours, "skipped (quiet hours)"), (skipped_hourly_cap, "skipped (hourly limit)"), (failed, "failed (dms closed)")] {
+            if count > 0 {
+                description.push_str(&format!(", {count} {reason}"));
+            }
+        }
+        description
+    }
+
+    fn call_limit_reached(&self, limit: i32, window: &str, wait: &str) -> String {
+        format!("You already started {limit} stack(s) in the last {window}, you can start another one in {wait}. This keeps everyone's dms from filling up.")
+    }
+
+    fn game_call_offer(&self, game: Option<&str>, subscribers: usize, any_gamers_command: &str) -> String {
+        let game = game.map(|game| format!(" of {game}")).unwrap_or_default();
+        let people = if subscribers == 1 { "person" } else { "people" };
+        format!("Looking for a game{game}? {subscribers} {people} signed up for notifications here, I can let them know like {any_gamers_command} would.")
+    }
+
+    fn notify_subscribers_button(&self, subscribers: usize) -> String {
+        format!("Notify {subscribers}")
+    }
+
+    fn not_your_game_call(&self) -> String {
+        "Only the person who was looking for a game can send the notifications.".to_string()
+    }
+
+    fn coming_button(&self) -> String {
+        "I'll be there".to_string()
+    }
+
+    fn not_coming_button(&self) -> String {
+        "Can't make it".to_string()
+    }
+
+    fn in_the_past(&self, when: &str) -> String {
+        format!("{when} is in the past")
+    }
+
+    fn unknown_when(&self, text: &str, schedule_command: &str) -> String {
+        format!("I don't understand when \"{text}\" is, try something like {schedule_command} tonight 9pm ranked")
+    }
+
+    fn schedule_failed(&self, channel_name: &str) -> String {
+        format!("I was unable to schedule a game in #{channel_name}, try again later.")
+    }
+
+    fn schedule_notification(&self, notification: &Notification, when: &str) -> String {
+        let Notification { organizer, game, channel_name, description, invitation, reply_hint, decline_emoji } = *notification;
+        let of_game = game.map(|game| format!(" of {game}")).unwrap_or_default();
+        let also_said = if description.is_empty() { "".to_string() } else { self.also_said(description) };
+        let text = invitation.map(str::to_string).unwrap_or_else(|| format!("@{organizer} scheduled a game{of_game} in #{channel_name} for {when}. {also_said}"));
+        format!("{text}\n\n({reply_hint} You can also let them know if you are coming with the buttons below or by reacting to this message, {decline_emoji} means you can't make it.)")
+    }
+
+    fn scheduled(&self, scheduled_game_id: i32, when: &str, summary: &str) -> String {
+        format!("I scheduled game #{scheduled_game_id} for {when}. {summary}")
+    }
+
+    fn assumed_utc(&self, timezone_command: &str) -> String {
+        format!("(I assumed your times are in UTC, you can set your timezone with {timezone_command})")
+    }
+
+    fn announcement(&self, roster: &Roster, scheduled_game_id: i32, when: &str, cancelled: bool) -> String {
+        let Roster { organizer, game, description, accepted: coming, declined: not_coming } = *roster;
+        let of_game = game.map(|game| format!(" of {game}")).unwrap_or_default();
+        let mut announcement = if cancelled {
+            format!("~~{organizer} scheduled a game{of_game} (#{scheduled_game_id}) for {when}~~ This game was cancelled.")
+        }
+        else {
+            format!("{organizer} scheduled a game{of_game} (#{scheduled_game_id}) for {when}.")
+        };
+        if !description.is_empty() {
+            announcement.push_str(&format!(" {}", self.also_said(description)));
+        }
+        if !coming.is_empty() {
+            announcement.push_str(&format!("\nComing: {}", coming.join(", ")));
+        }
+        if !not_coming.is_empty() {
+            announcement.push_str(&format!("\nNot coming: {}", not_coming.join(", ")));
+        }
+        announcement
+    }
+
+    fn no_scheduled_games(&self, channel_name: &str, schedule_command: &str) -> String {
+        format!("There are no games scheduled in #{channel_name}, you can schedule one with {schedule_command}")
+    }
+
+    fn scheduled_game_line(&self, scheduled_game_id: i32, game: Option<&str>, when: &str, organizer: &str, description: &str) -> String {
+        let game = game.map(|game| format!("{game} ")).unwrap_or_default();
+        format!("#{scheduled_game_id}: {game}{when} by {organizer} {description}")
+    }
+
+    fn scheduled_games(&self, channel_name: &str, lines: &[String]) -> String {
+        format!("Games scheduled in #{channel_name}:\n{}", lines.join("\n"))
+    }
+
+    fn which_game(&self, scheduled_command: &str) -> String {
+        format!("Which game? You can see the game numbers with {scheduled_command}")
+    }
+
+    fn game_number_not_found(&self, scheduled_game_id: i32) -> String {
+        format!("I couldn't find game #{scheduled_game_id}.")
+    }
+
+    fn cancel_not_allowed(&self, scheduled_game_id: i32) -> String {
+        format!("Only the organizer or an admin can cancel game #{scheduled_game_id}.")
+    }
+
+    fn cancelled(&self, scheduled_game_id: i32) -> String {
+        format!("Game #{scheduled_game_id} has been cancelled.")
+    }
+
+    fn already_over(&self, scheduled_game_id: i32) -> String {
+        format!("Game #{scheduled_game_id} already started or was already cancelled.")
+    }
+
+    fn cancelled_ping(&self, scheduled_game_id: i32) -> String {
+        format!("game #{scheduled_game_id} has been cancelled.")
+    }
+
+    fn game_time(&self, scheduled_game_id: i32) -> String {
+        format!("it's time for game #{scheduled_game_id}!")
+    }
+
+    fn started_late(&self, when: &str) -> String {
+        format!("(Sorry I'm late, this was scheduled for {when})")
+    }
+
+    fn reminder(&self, organizer: &str, channel_name: &str, when: &str) -> String {
+        format!("Reminder: {organizer}'s game in #{channel_name} starts {when}")
+    }
+
+    fn organizing_game(&self) -> String {
+        "You are organizing this game!".to_string()
+    }
+
+    fn game_over(&self) -> String {
+        "This game already started or was cancelled.".to_string()
+    }
+
+    fn rsvp_failed(&self) -> String {
+        "I was unable to save your answer, try again later.".to_string()
+    }
+
+    fn see_you(&self, when: &str, reminder_minutes: i64) -> String {
+        format!("See you {when}! I'll remind you {reminder_minutes} minutes before it starts.")
+    }
+
+    fn game_not_found(&self) -> String {
+        "I couldn't find that game anymore.".to_string()
+    }
+
+    fn admin_list(&self, owner: Option<&str>, admins: &[String], admin_roles: &[String]) -> String {
+        let mut lines = Vec::new();
+        if let Some(owner) = owner {
+            lines.push(format!("Owner: {owner}"));
+        }
+        lines.push(format!("Admins: {}", if admins.is_empty() { "none".to_string() } else { admins.join(", ") }));
+        if !admin_roles.is_empty() {
+            lines.push(format!("Everyone with one of these roles: {}", admin_roles.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    fn isnt_an_admin(&self, name: &str) -> String {
+        format!("{name} isn't an admin.")
+    }
+
+    fn role_admin(&self, name: &str) -> String {
+        format!("{name} is an admin because of their roles in this server, only a server moderator can take those away.")
+    }
+
+    fn cant_remove_admin(&self, name: &str) -> String {
+        format!("You can't remove {name}, only someone above them can.")
+    }
+
+    fn admin_removed(&self, name: &str) -> String {
+        format!("{name} is no longer an admin.")
+    }
+
+    fn cant_add_admin(&self) -> String {
+        "Admins from a server role can't add admins, ask an admin or the owner.".to_string()
+    }
+
+    fn admin_added(&self, name: &str) -> String {
+        format!("{name} has been added as an admin.")
+    }
+
+    fn already_an_admin(&self, name: &str) -> String {
+        format!("{name} is already an admin.")
+    }
+
+    fn admin_cant_be_blacklisted(&self, name: &str) -> String {
+        format!("{name} is an admin and can't be blacklisted.")
+    }
+
+    fn blacklisted(&self, name: &str) -> String {
+        format!("{name} has been blacklisted.")
+    }
+
+    fn already_blacklisted(&self, name: &str) -> String {
+        format!("{name} is already blacklisted.")
+    }
+
+    fn unblacklisted(&self, name: &str) -> String {
+        format!("{name} has been removed from the blacklist.")
+    }
+
+    fn not_blacklisted(&self, name: &str) -> String {
+        format!("{name} isn't blacklisted.")
+    }
+
+    fn channel_cooldown(&self, channel_name: &str, cooldown: &str) -> String {
+        format!("Game notifications in #{channel_name} are sent at most once every {cooldown}")
+    }
+
+    fn channel_cooldown_changed(&self, channel_name: &str, cooldown: &str) -> String {
+        format!("Game notifications in #{channel_name} will now be sent at most once every {cooldown}")
+    }
+
+    fn server_settings(&self, settings: &ServerSettings) -> String {
+        let ServerSettings { help_text, cooldown, prefix, language, invitations, invitation_theme, game_calls, mod_log, call_limit, call_window } = *settings;
+        let help_text = help_text.map(|help_text| format!("\"{help_text}\"")).unwrap_or_else(|| "none".to_string());
+        let invitations = match (invitations, invitation_theme) {
+            (false, _) => "off".to_string(),
+            (true, None) => "on".to_string(),
+            (true, Some(theme)) => format!("on, in the voice of {theme}")
+        };
+        format!("Help message: {help_text}\nNotification cooldown for channels without their own: {cooldown}\nCommand prefix: {prefix}\nLanguage: {language}\nPersonalized invitations: {invitations}\nGame call offers: {}\nMod log: {}\nStacks per person: {call_limit} every {call_window} (admins have no limit)", if game_calls { "on" } else { "off" }, mod_log.unwrap_or("none"))
+    }
+
+    fn help_message_missing(&self) -> String {
+        "Tell me the text to show above my commands, or off to remove it".to_string()
+    }
+
+    fn help_message_changed(&self, removed: bool) -> String {
+        format!("The help message has been {}", if removed { "removed" } else { "changed" })
+    }
+
+    fn server_cooldown_changed(&self, cooldown: &str) -> String {
+        format!("Game notifications in channels without their own cooldown will now be sent at most once every {cooldown}")
+    }
+
+    fn invalid_prefix(&self, text: &str) -> String {
+        format!("\"{text}\" can't be a prefix, it has to be 1 to 5 characters without spaces and can't start with /")
+    }
+
+    fn prefix_changed(&self, help_command: &str) -> String {
+        format!("My commands now look like {help_command}")
+    }
+
+    fn server_language_changed(&self, language: &str) -> String {
+        format!("I will now talk in {language} in this server")
+    }
+
+    fn on_or_off(&self, setting: &str) -> String {
+        format!("Tell me on or off for {setting}")
+    }
+
+    fn invitations_changed(&self, on: bool) -> String {
+        if on {
+            "Game notifications will now be written as personalized invitations, when the language model is available".to_string()
+        }
+        else {
+            "Game notifications will now use the usual text".to_string()
+        }
+    }
+
+    fn invitations_unavailable(&self) -> String {
+        "Personalized invitations need a language model, the bot's owner has to set OPENAI_API_KEY".to_string()
+    }
+
+    fn game_calls_changed(&self, on: bool) -> String {
+        if on {
+            "Messages that look like someone looking for players will now get an offer to notify the channel's subscribers".to_string()
+        }
+        else {
+            "I won't look for game calls in messages anymore".to_string()
+        }
+    }
+
+    fn game_calls_unavailable(&self) -> String {
+        "Game call offers need a language model, the bot's owner has to set OPENAI_API_KEY".to_string()
+    }
+
+    fn invitation_theme_missing(&self) -> String {
+        "Tell me whose voice invitations should be written in, e.g. an age of empires monk, or off to remove it".to_string()
+    }
+
+    fn invitation_theme_changed(&self, theme: Option<&str>) -> String {
+        match theme {
+            Some(theme) => format!("Invitations will now be written in the voice of {theme}"),
+            None => "Invitations will now be written without a theme".to_string()
+        }
+    }
+
+    fn unknown_channel(&self, text: &str) -> String {
+        format!("\"{text}\" isn't a channel, mention it like #mod-log")
+    }
+
+    fn mod_log_changed(&self, channel: Option<&str>) -> String {
+        match channel {
+            Some(channel) => format!("Admin actions will now also be posted in {channel}"),
+            None => "Admin actions won't be posted in a channel anymore".to_string()
+        }
+    }
+
+    fn invalid_call_limit(&self, text: &str, max_window: &str) -> String {
+        format!("\"{text}\" isn't a limit, tell me how many stacks per how long (up to {max_window}), e.g. 3 30m, or off to go back to the default")
+    }
+
+    fn call_limit_changed(&self, limit: i32, window: &str) -> String {
+        format!("Everyone but admins can now start {limit} stack(s) every {window}")
+    }
+
+    fn unknown_setting(&self, setting: &str) -> String {
+        format!("I don't know the setting \"{setting}\", try help-message, cooldown, prefix, language, invitations, theme, game-calls, mod-log or call-limit")
+    }
+
+    fn audit_entry(&self, line: &AuditLine) -> String {
+        let AuditLine { when, actor, action, target, details, channel } = *line;
+        let target = target.unwrap_or("someone");
+        let did = match action {
+            AuditAction::AdminAdded => format!("made {target} an admin"),
+            AuditAction::AdminRemoved => format!("removed {target} as an admin"),
+            AuditAction::Blacklisted => format!("blacklisted {target}"),
+            AuditAction::Unblacklisted => format!("removed {target} from the blacklist"),
+            AuditAction::SettingChanged => format!("set {}", details.unwrap_or_default()),
+            AuditAction::GameAdded => format!("added the game {}", details.unwrap_or_default()),
+            AuditAction::GameRemoved => format!("removed the game {}", details.unwrap_or_default())
+        };
+        let in_channel = channel.map(|channel| format!(" in {channel}")).unwrap_or_default();
+        format!("{when} {actor} {did}{in_channel}")
+    }
+
+    fn audit_log(&self, lines: &[String]) -> String {
+        format!("The last {} admin actions, newest first:\n{}", lines.len(), lines.join("\n"))
+    }
+
+    fn no_audit_entries(&self) -> String {
+        "No admin actions have been recorded in this server yet.".to_string()
+    }
+
+    fn stats(&self, stats: &Stats) -> String {
+        let mut lines = vec![format!("Over the last {} days:", stats.days), "Most active organizers:".to_string()];
+        lines.extend(stats.organizers.iter().map(|(organizer, calls)| format!("{organizer}: {calls} {}", if *calls == 1 { "call" } else { "calls" })));
+        lines.push("Most responsive players:".to_string());
+        if stats.players.is_empty() {
+            lines.push("Nobody joined anything yet".to_string());
+        }
+        lines.extend(stats.players.iter().map(|(player, joined, received)| format!("{player}: joined {joined} of {received}")));
+        lines.push(format!("Busiest hours ({}):", stats.timezone));
+        lines.extend(stats.busiest_hours.iter().map(|(channel, hour, calls)| format!("{channel}: {hour} ({calls} {})", if *calls == 1 { "call" } else { "calls" })));
+        lines.push(match stats.own_response {
+            (_, 0) => "You weren't notified about anything".to_string(),
+            (joined, received) => format!("You joined {joined} of the {received} calls you were notified about ({}%)", joined * 100 / received)
+        });
+        lines.join("\n")
+    }
+
+    fn no_stats(&self, days: i64) -> String {
+        format!("Nobody started a stack or scheduled a game in this server in the last {days} days.")
+    }
+
+    fn notification_gap(&self, gap: &str) -> String {
+        format!("I send you game notifications at most once every {gap}")
+    }
+
+    fn no_notification_gap(&self) -> String {
+        "You get every game notification that the channel cooldowns allow".to_string()
+    }
+
+    fn notification_gap_changed(&self, gap: &str) -> String {
+        format!("I will send you game notifications at most once every {gap}")
+    }
+
+    fn notification_gap_off(&self) -> String {
+        "You will get every game notification that the channel cooldowns allow".to_string()
+    }
+
+    fn timezone(&self, timezone: &str) -> String {
+        format!("Your timezone is {timezone}")
+    }
+
+    fn no_timezone(&self, timezone_command: &str) -> String {
+        format!("You haven't set a timezone, you can set one like this: {timezone_command} America/Toronto")
+    }
+
+    fn timezone_changed(&self, timezone: &str) -> String {
+        format!("Your timezone is now {timezone}")
+    }
+
+    fn quiet_hours(&self, start: &str, end: &str, timezone: &str) -> String {
+        format!("Your quiet hours are {start}-{end} ({timezone})")
+    }
+
+    fn no_quiet_hours(&self, quiet_hours_command: &str) -> String {
+        format!("You don't have quiet hours, you can set them like this: {quiet_hours_command} 23:00-08:00 America/Toronto")
+    }
+
+    fn quiet_hours_off(&self) -> String {
+        "Your quiet hours are off".to_string()
+    }
+
+    fn unknown_time_window(&self, text: &str) -> String {
+        format!("I don't understand \"{text}\", try something like 23:00-08:00")
+    }
+
+    fn quiet_hours_need_timezone(&self, quiet_hours_command: &str, window: &str) -> String {
+        format!("I need to know your timezone too, for example: {quiet_hours_command} {window} America/Toronto")
+    }
+
+    fn quiet_hours_changed(&self, start: &str, end: &str, timezone: &str) -> String {
+        format!("I won't send you game notifications between {start} and {end} ({timezone})")
+    }
+
+    fn language(&self, language: &str) -> String {
+        format!("I talk to you in {language}")
+    }
+
+    fn no_language(&self, language: &str) -> String {
+        format!("You haven't picked a language, so I use the server's: {language}")
+    }
+
+    fn language_changed(&self, language: &str) -> String {
+        format!("I will now talk to you in {language}")
+    }
+
+    fn language_off(&self, language: &str) -> String {
+        format!("I will use the server's language with you: {language}")
+    }
+
+    fn unknown_language(&self, text: &str, languages: &str) -> String {
+        format!("I don't speak \"{text}\", try one of: {languages}")
+    }
+
+    fn no_subscriptions(&self, on_command: &str) -> String {
+        format!("You aren't subscribed to game notifications anywhere. Use {on_command} in a channel to subscribe to it.")
+    }
+
+    fn subscriptions(&self, lines: &[String]) -> String {
+        format!("You are subscribed to game notifications in:\n{}", lines.join("\n"))
+    }
+
+    fn subscription_line(&self, channel_name: &str, guild_name: Option<&str>, game: Option<&str>, since: &str, last_notified: Option<&str>) -> String {
+        let guild_name = guild_name.map(|guild_name| format!(" ({guild_name})")).unwrap_or_default();
+        let game = game.unwrap_or("every game");
+        let last_notified = match last_notified {
+            Some(last_notified) => format!("last notified {last_notified}"),
+            None => "never notified".to_string()
+        };
+        format!("#{channel_name}{guild_name}, {game}: since {since}, {last_notified}")
+    }
+
+    fn too_many_subscriptions(&self, max_buttons: usize, off_command: &str) -> String {
+        format!("(I can only show {max_buttons} buttons, use {off_command} in the other channels to unsubscribe)")
+    }
+
+    fn unsubscribe_button(&self, game: Option<&str>, channel_name: &str) -> String {
+        match game {
+            Some(game) => format!("Unsubscribe from {game} in #{channel_name}"),
+            None => format!("Unsubscribe from #{channel_name}")
+        }
+    }
+
+    fn not_your_subscriptions(&self, my_subscriptions_command: &str) -> String {
+        format!("These aren't your subscriptions, you can see yours with {my_subscriptions_command}")
+    }
+
+    fn unknown_server(&self) -> String {
+        "an unknown server".to_string()
+    }
+
+    fn deleted_channel(&self) -> String {
+        "deleted-channel".to_string()
+    }
+
+    fn reply_hint(&self, channel_name: &str, stop_reply: &str, stop_all_reply: &str) -> String {
+        format!("Reply '{stop_reply}' to stop getting notifications from #{channel_name}, or '{stop_all_reply}' to stop getting them from everywhere. Anything else you reply, like 'omw 10m', is passed along to them.")
+    }
+
+    fn not_subscribed_anywhere(&self) -> String {
+        "You weren't subscribed to game notifications anywhere.".to_string()
+    }
+
+    fn unsubscribed_everywhere(&self, channel_count: u64) -> String {
+        format!("I won't send you game notifications anymore, you were unsubscribed from {channel_count} channel(s).")
+    }
+
+    fn unknown_notification(&self, stop_all_reply: &str) -> String {
+        format!("I don't know which game that is about. Reply to one of my game notifications, or say '{stop_all_reply}' to stop getting them.")
+    }
+
+    fn not_allowed(&self) -> String {
+        "You are not allowed to do that.".to_string()
+    }
+
+    fn unsubscribed_from_channel(&self, channel: &str) -> String {
+        format!("I won't send you game notifications from {channel} anymore.")
+    }
+
+    fn already_unsubscribed_from_channel(&self, channel: &str) -> String {
+        format!("You already aren't subscribed to game notifications in {channel}.")
+    }
+
+    fn relay_thread_name(&self) -> String {
+        "Replies from dms".to_string()
+    }
+
+    fn relayed_reply(&self, organizer: &str, user: &str, text: &str) -> String {
+        format!("{organizer}{user} replied to the notification: {text}")
+    }
+
+    fn relayed(&self, thread: &str) -> String {
+        format!("I passed that along in {thread}.")
+    }
+
+    fn relay_failed(&self, channel: &str) -> String {
+        format!("I couldn't pass that along, you can answer them in {channel} instead.")
+    }
+}
@@ -0,0 +1,795 @@
+use crate::constants::{DEFAULT_LOBBY_SIZE, STOP_ALL_REPLY, STOP_REPLY};
+use crate::audit::AuditAction;
+use super::{AuditLine, Commands, Messages, Notification, Roster, ServerSettings, Stats};
+
+pub(super) struct French;
+
+impl Messages for French {
+    fn help_text(&self, c: &Commands) -> String {
+        format!("Voici mes commandes :
+{}: affiche ce message
+{}: t'ajoute à la liste des utilisateurs avec qui j'interagis
+{}: supprime tout ce que je sais de toi, y compris tes réglages de notification et les groupes ou parties que tu as organisés
+{}: active les notifications dans ce salon quand un autre utilisateur inscrit utilise {} ou {}. Ajoute un jeu pour n'être prévenu que pour ce jeu, par ex. '{} aoe2'
+{}: désactive les notifications de recherche de partie dans ce salon, ou seulement pour un jeu, par ex. '{} aoe2'
+{}: liste tous les salons où tu reçois des notifications de recherche de partie, avec des boutons pour te désabonner. La commande slash marche aussi en mp
+{}: envoie un mp à tous les utilisateurs inscrits qui ont activé les notifications dans ce salon et lance un groupe qu'ils peuvent rejoindre. Commence par un jeu pour ne prévenir que ses abonnés, par ex. '{} aoe2 classé ce soir'. Ajoute size=<n> pour changer la taille du groupe (par défaut celle du jeu, ou {})
+{}: prévoit une partie pour plus tard et envoie un mp à tous ceux qui ont activé les notifications dans ce salon, par ex. '{} tonight 9pm classé' ou '{} aoe2 in 2h'. Tous ceux qui viennent reçoivent un rappel et une mention quand elle commence
+{}: liste les jeux auxquels tu peux t'abonner et pour lesquels tu peux lancer un groupe
+{}: liste les parties prévues dans ce salon
+{}: annule une partie que tu as prévue, par ex. '{} 3'. Les admins peuvent annuler n'importe quelle partie
+{}: règle le temps minimum entre deux notifications que je t'envoie, tous salons confondus, par ex. '{} 1h'. Utilise '{} off' pour recevoir toutes les notifications
+{}: affiche ou règle ton fuseau horaire, par ex. '{} Europe/Paris'
+{}: règle des heures pendant lesquelles je ne t'envoie pas de notifications, par ex. '{} 23:00-08:00 Europe/Paris'. Utilise '{} off' pour les désactiver
+{}: affiche ou règle la langue dans laquelle je te parle, par ex. '{} en'. Utilise '{} off' pour revenir à la langue du serveur
+{}: affiche à quelle fréquence les notifications peuvent être envoyées dans ce salon
+{}: affiche les réglages de ce serveur
+{}: affiche qui a lancé le plus de groupes et de parties et qui en a rejoint le plus ces 30 derniers jours, l'heure la plus active de chaque salon et à quelle fréquence tu rejoins
+Tu peux aussi répondre à mes mp de notification : '{STOP_REPLY}' te désabonne de ce salon, '{STOP_ALL_REPLY}' de tous les salons, et tout le reste (par ex. 'j'arrive dans 10m') est transmis à l'organisateur dans un fil
+-----------ADMINS SEULEMENT------------
+{}: ajoute tous les utilisateurs mentionnés comme admins. Par exemple, '{} @<quelqu'un>' ajoute <quelqu'un> comme admin. Utilise '{} remove @<quelqu'un>' pour retirer un admin (seul le propriétaire peut) et '{} list' pour voir qui sont les admins
+{}: empêche tous les utilisateurs mentionnés d'utiliser {}, {}, {} et {}, et ignore leurs réactions
+{}: retire tous les utilisateurs mentionnés de la liste noire
+{} add <nom> [size=<n>] [emoji] [alias]: ajoute un jeu auquel on peut s'abonner, par ex. '{} add aoe2 size=8 🏰 aoe age'. Utilise '{} remove <nom>' pour retirer un jeu et tous les abonnements à ce jeu
+{} <durée>: règle à quelle fréquence les notifications peuvent être envoyées au même utilisateur dans ce salon, par ex. '{} 10m'
+{} help-message <texte>: affiche un texte au-dessus de mes commandes dans {}, ou 'off' pour le retirer
+{} cooldown <durée>: règle le délai entre notifications pour les salons qui n'ont pas le leur, par ex. '{} cooldown 5m', ou 'off' pour revenir à la valeur par défaut
+{} prefix <préfixe>: change le début de mes commandes dans ce serveur, par ex. '{} prefix ?', ou 'off' pour revenir à !
+{} language <langue>: change la langue que j'utilise dans ce serveur, par ex. '{} language en'. Chacun peut quand même choisir la sienne avec {}
+{} invitations on|off: fait écrire les notifications de partie comme des invitations personnalisées par le modèle de langage
+{} theme <texte>: écrit les invitations avec la voix de quelqu'un, par ex. '{} theme un moine d'age of empires', ou 'off' pour l'enlever
+{} game-calls on|off: quand le modèle de langage pense qu'un message est quelqu'un qui cherche des joueurs, lui propose de prévenir les abonnés du salon
+{} mod-log <#salon>: publie aussi chaque action d'admin dans un salon, ou 'off' pour arrêter
+{} call-limit <n> <durée>: combien de groupes tout le monde sauf les admins peut lancer, par ex. '{} call-limit 3 30m', ou 'off' pour revenir à la valeur par défaut
+{} [n]: affiche les n dernières actions d'admin dans ce serveur, 10 par défaut",
+            c.help,
+            c.register,
+            c.unregister,
+            c.game_notification_on, c.any_gamers, c.schedule, c.game_notification_on,
+            c.game_notification_off, c.game_notification_off,
+            c.my_subscriptions,
+            c.any_gamers, c.any_gamers, DEFAULT_LOBBY_SIZE,
+            c.schedule, c.schedule, c.schedule,
+            c.games,
+            c.scheduled,
+            c.cancel_game, c.cancel_game,
+            c.notification_gap, c.notification_gap, c.notification_gap,
+            c.timezone, c.timezone,
+            c.quiet_hours, c.quiet_hours, c.quiet_hours,
+            c.language, c.language, c.language,
+            c.channel_cooldown,
+            c.server_settings,
+            c.stats,
+            c.add_admins, c.add_admins, c.add_admins, c.add_admins,
+            c.blacklist, c.any_gamers, c.schedule, c.register, c.game_notification_on,
+            c.unblacklist,
+            c.games, c.games, c.games,
+            c.channel_cooldown, c.channel_cooldown,
+            c.server_settings, c.help,
+            c.server_settings, c.server_settings,
+            c.server_settings, c.server_settings,
+            c.server_settings, c.server_settings, c.language,
+            c.server_settings,
+            c.server_settings, c.server_settings,
+            c.server_settings,
+            c.server_settings,
+            c.server_settings, c.server_settings,
+            c.audit)
+    }
+
+    fn database_trouble(&self) -> String {
+        "J'ai des problèmes avec ma base de données, réessaie plus tard.".to_string()
+    }
+
+    fn not_registered(&self, register_command: &str) -> String {
+        format!("Tu n'es pas inscrit, tu peux t'inscrire avec {register_command}")
+    }
+
+    fn not_an_admin(&self) -> String {
+        "Tu n'es pas admin.".to_string()
+    }
+
+    fn only_in_a_server(&self, command: &str) -> String {
+        format!("{command} ne marche que dans un serveur")
+    }
+
+    fn unknown_duration(&self, text: &str) -> String {
+        format!("Je ne comprends pas \"{text}\", essaie quelque chose comme 30m ou 1h")
+    }
+
+    fn unknown_duration_or_off(&self, text: &str) -> String {
+        format!("Je ne comprends pas \"{text}\", essaie quelque chose comme 30m, 1h ou off")
+    }
+
+    fn unknown_timezone(&self, text: &str) -> String {
+        format!("Je ne connais pas le fuseau horaire \"{text}\", essaie quelque chose comme Europe/Paris ou America/Montreal")
+    }
+
+    fn registration_failed(&self) -> String {
+        "Je n'ai pas pu t'inscrire, réessaie plus tard.".to_string()
+    }
+
+    fn also_said(&self, description: &str) -> String {
+        format!("Ils ont aussi dit : {description}")
+    }
+
+    fn registered(&self) -> String {
+        "Je t'ai inscrit, ou tu étais déjà inscrit !".to_string()
+    }
+
+    fn unregistered(&self) -> String {
+        "J'ai supprimé toutes tes données.".to_string()
+    }
+
+    fn unregistered_check_dms(&self) -> String {
+        "J'ai supprimé toutes tes données, regarde tes mp pour les détails.".to_string()
+    }
+
+    fn unregistered_dm(&self, register_command: &str) -> String {
+        format!("J'ai supprimé tout ce que j'avais sur toi : ton inscription, tes abonnements et réglages de notification, et les groupes ou parties que tu as organisés ou auxquels tu as répondu. Tu peux te réinscrire à tout moment avec {register_command}.")
+    }
+
+    fn notifications_for(&self, game: Option<&str>, channel_name: &str) -> String {
+        match game {
+            Some(game) => format!("les notifications de recherche de partie de {game} dans #{channel_name}"),
+            None => format!("les notifications de recherche de partie dans #{channel_name}")
+        }
+    }
+
+    fn already_signed_up(&self, notifications: &str) -> String {
+        format!("Tu es déjà abonné à {notifications}")
+    }
+
+    fn signed_up(&self, notifications: &str) -> String {
+        format!("Tu es maintenant abonné à {notifications}")
+    }
+
+    fn removed_from(&self, notifications: &str) -> String {
+        format!("Tu as été désabonné de {notifications}")
+    }
+
+    fn signed_up_for_every_game(&self, channel_name: &str, off_command: &str) -> String {
+        format!("Tu es abonné à tous les jeux dans #{channel_name}, utilise {off_command} pour arrêter ces notifications")
+    }
+
+    fn not_signed_up(&self, notifications: &str) -> String {
+        format!("Tu n'es pas abonné à {notifications}")
+    }
+
+    fn unknown_game(&self, name: &str) -> String {
+        format!("Je ne connais pas le jeu \"{name}\"")
+    }
+
+    fn known_games(&self, names: &[&str]) -> String {
+        format!("Jeux connus : {}", names.join(", "))
+    }
+
+    fn no_games(&self) -> String {
+        "Aucun jeu n'a encore été ajouté.".to_string()
+    }
+
+    fn game_description(&self, label: &str, aliases: &[String], default_party_size: i32) -> String {
+        let aliases = if aliases.is_empty() {
+            "".to_string()
+        }
+        else {
+            format!(" ({})", aliases.join(", "))
+        };
+        format!("{label}{aliases} : groupes de {default_party_size}")
+    }
+
+    fn games_list(&self, descriptions: &[String]) -> String {
+        format!("Les jeux auxquels tu peux t'abonner et pour lesquels tu peux lancer un groupe :\n{}", descriptions.join("\n"))
+    }
+
+    fn game_options_help(&self, games_command: &str) -> String {
+        format!("Donne-moi le nom du jeu, et si tu veux la taille des groupes, un emoji et des alias, par ex. {games_command} add aoe2 size=8 🏰 aoe age")
+    }
+
+    fn game_name_taken(&self, name: &str, game: &str) -> String {
+        format!("\"{name}\" est déjà utilisé par {game}")
+    }
+
+    fn game_added(&self, description: &str, subscribe_command: &str) -> String {
+        format!("J'ai ajouté {description}. On peut s'y abonner avec {subscribe_command}")
+    }
+
+    fn game_exists(&self, name: &str) -> String {
+        format!("{name} existe déjà")
+    }
+
+    fn shared_game(&self, name: &str) -> String {
+        format!("{name} est partagé par tous les serveurs, seul le propriétaire peut le retirer")
+    }
+
+    fn game_removed(&self, name: &str) -> String {
+        format!("J'ai retiré {name} ainsi que tous les abonnements à ce jeu")
+    }
+
+    fn join_button(&self) -> String {
+        "J'en suis".to_string()
+    }
+
+    fn not_this_time_button(&self) -> String {
+        "Pas cette fois".to_string()
+    }
+
+    fn stack_failed(&self, channel_name: &str) -> String {
+        format!("Je n'ai pas pu lancer de groupe dans #{channel_name}, réessaie plus tard.")
+    }
+
+    fn stack_notification(&self, notification: &Notification, target_size: i32) -> String {
+        let Notification { organizer, game, channel_name, description, invitation, reply_hint, decline_emoji } = *notification;
+        let for_game = game.map(|game| format!(" pour {game}")).unwrap_or_default();
+        let also_said = if description.is_empty() { "".to_string() } else { self.also_said(description) };
+        let text = invitation.map(str::to_string).unwrap_or_else(|| format!("@{organizer} cherche un groupe de {target_size}{for_game} dans #{channel_name}. {also_said}"));
+        format!("{text}\n\n({reply_hint} Tu peux aussi lui dire si tu viens avec les boutons ci-dessous ou en réagissant à ce message, {decline_emoji} veut dire que tu ne peux pas.)")
+    }
+
+    fn stack_summary(&self, roster: &Roster, joined_count: usize, target_size: i32, full: bool) -> String {
+        let Roster { organizer, game, description, accepted: joined, declined } = *roster;
+        let for_game = game.map(|game| format!(" pour {game}")).unwrap_or_default();
+        let mut summary = if full {
+            format!("Le groupe{for_game} de {organizer} est complet ({joined_count}/{target_size}) !")
+        }
+        else {
+            format!("{organizer} cherche un groupe{for_game} ({joined_count}/{target_size}).")
+        };
+        if !description.is_empty() {
+            summary.push_str(&format!(" {}", self.also_said(description)));
+        }
+        if !joined.is_empty() {
+            summary.push_str(&format!("\nPartants : {}", joined.join(", ")));
+        }
+        if !declined.is_empty() {
+            summary.push_str(&format!("\nPas dispo : {}", declined.join(", ")));
+        }
+        summary
+    }
+
+    fn stack_full(&self, mentions: &str) -> String {
+        format!("{mentions} votre groupe est complet, à vous de jouer !")
+    }
+
+    fn organizing_stack(&self) -> String {
+        "C'est toi qui organises ce groupe !".to_string()
+    }
+
+    fn stack_update_failed(&self) -> String {
+        "Je n'ai pas pu mettre le groupe à jour, réessaie plus tard.".to_string()
+    }
+
+    fn joined_stack(&self, organizer: &str) -> String {
+        format!("Tu as rejoint le groupe de {organizer} !")
+    }
+
+    fn told_organizer(&self, organizer: &str) -> String {
+        format!("J'ai prévenu {organizer} que tu ne peux pas venir.")
+    }
+
+    fn stack_not_found(&self) -> String {
+        "Je ne trouve plus ce groupe.".to_string()
+    }
+
+    fn notification_summary(&self, notified: usize, skipped_cooldown: usize, skipped_quiet_hours: usize, skipped_hourly_cap: usize, failed: usize) -> String {
+        let mut description = format!("{notified} prévenu(s)");
+        for (count, reason) in [(skipped_cooldown, "ignoré(s) (délai)"), (skipped_quiet_hours, "ignoré(s) (heures calmes)"), (skipped_hourly_cap, "ignoré(s) (limite horaire)"), (failed, "échec(s) (mp fermés)")] {
+            if count > 0 {
+                description.push_str(&format!(", {count} {reason}"));
+            }
+        }
+        description
+    }
+
+    fn call_limit_reached(&self, limit: i32, window: &str, wait: &str) -> String {
+        format!("Tu as déjà lancé {limit} groupe(s) dans les dernières {window}, tu pourras en lancer un autre dans {wait}. Ça évite de remplir les mp de tout le monde.")
+    }
+
+    fn game_call_offer(&self, game: Option<&str>, subscribers: usize, any_gamers_command: &str) -> String {
+        let game = game.map(|game| format!(" de {game}")).unwrap_or_default();
+        format!("Tu cherches une partie{game} ? {subscribers} personne(s) sont inscrites aux notifications ici, je peux les prévenir comme le ferait {any_gamers_command}.")
+    }
+
+    fn notify_subscribers_button(&self, subscribers: usize) -> String {
+        format!("Prévenir {subscribers} personne(s)")
+    }
+
+    fn not_your_game_call(&self) -> String {
+        "Seule la personne qui cherchait une partie peut envoyer les notifications.".to_string()
+    }
+
+    fn coming_button(&self) -> String {
+        "J'y serai".to_string()
+    }
+
+    fn not_coming_button(&self) -> String {
+        "Je ne peux pas".to_string()
+    }
+
+    fn in_the_past(&self, when: &str) -> String {
+        format!("{when} est déjà passé")
+    }
+
+    fn unknown_when(&self, text: &str, schedule_command: &str) -> String {
+        format!("Je ne comprends pas quand est \"{text}\", essaie quelque chose comme {schedule_command} tonight 21:00 classé")
+    }
+
+    fn schedule_failed(&self, channel_name: &str) -> String {
+        format!("Je n'ai pas pu prévoir de partie dans #{channel_name}, réessaie plus tard.")
+    }
+
+    fn schedule_notification(&self, notification: &Notification, when: &str) -> String {
+        let Notification { organizer, game, channel_name, description, invitation, reply_hint, decline_emoji } = *notification;
+        let of_game = game.map(|game| format!(" de {game}")).unwrap_or_default();
+        let also_said = if description.is_empty() { "".to_string() } else { self.also_said(description) };
+        let text = invitation.map(str::to_string).unwrap_or_else(|| format!("@{organizer} a prévu une partie{of_game} dans #{channel_name} pour {when}. {also_said}"));
+        format!("{text}\n\n({reply_hint} Tu peux aussi lui dire si tu viens avec les boutons ci-dessous ou en réagissant à ce message, {decline_emoji} veut dire que tu ne peux pas.)")
+    }
+
+    fn scheduled(&self, scheduled_game_id: i32, when: &str, summary: &str) -> String {
+        format!("J'ai prévu la partie #{scheduled_game_id} pour {when}. {summary}")
+    }
+
+    fn assumed_utc(&self, timezone_command: &str) -> String {
+        format!("(J'ai supposé que tes heures sont en UTC, tu peux régler ton fuseau horaire avec {timezone_command})")
+    }
+
+    fn announcement(&self, roster: &Roster, scheduled_game_id: i32, when: &str, cancelled: bool) -> String {
+        let Roster { organizer, game, description, accepted: coming, declined: not_coming } = *roster;
+        let of_game = game.map(|game| format!(" de {game}")).unwrap_or_default();
+        let mut announcement = if cancelled {
+            format!("~~{organizer} a prévu une partie{of_game} (#{scheduled_game_id}) pour {when}~~ Cette partie a été annulée.")
+        }
+        else {
+            format!("{organizer} a prévu une partie{of_game} (#{scheduled_game_id}) pour {when}.")
+        };
+        if !description.is_empty() {
+            announcement.push_str(&format!(" {}", self.also_said(description)));
+        }
+        if !coming.is_empty() {
+            announcement.push_str(&format!("\nPrésents : {}", coming.join(", ")));
+        }
+        if !not_coming.is_empty() {
+            announcement.push_str(&format!("\nAbsents : {}", not_coming.join(", ")));
+        }
+        announcement
+    }
+
+    fn no_scheduled_games(&self, channel_name: &str, schedule_command: &str) -> String {
+        format!("Aucune partie n'est prévue dans #{channel_name}, tu peux en prévoir une avec {schedule_command}")
+    }
+
+    fn scheduled_game_line(&self, scheduled_game_id: i32, game: Option<&str>, when: &str, organizer: &str, description: &str) -> String {
+        let game = game.map(|game| format!("{game} ")).unwrap_or_default();
+        format!("#{scheduled_game_id} : {game}{when} par {organizer} {description}")
+    }
+
+    fn scheduled_games(&self, channel_name: &str, lines: &[String]) -> String {
+        format!("Parties prévues dans #{channel_name} :\n{}", lines.join("\n"))
+    }
+
+    fn which_game(&self, scheduled_command: &str) -> String {
+        format!("Quelle partie ? Tu peux voir les numéros des parties avec {scheduled_command}")
+    }
+
+    fn game_number_not_found(&self, scheduled_game_id: i32) -> String {
+        format!("Je ne trouve pas la partie #{scheduled_game_id}.")
+    }
+
+    fn cancel_not_allowed(&self, scheduled_game_id: i32) -> String {
+        format!("Seuls l'organisateur ou un admin peuvent annuler la partie #{scheduled_game_id}.")
+    }
+
+    fn cancelled(&self, scheduled_game_id: i32) -> String {
+        format!("La partie #{scheduled_game_id} a été annulée.")
+    }
+
+    fn already_over(&self, scheduled_game_id: i32) -> String {
+        format!("La partie #{scheduled_game_id} a déjà commencé ou a déjà été annulée.")
+    }
+
+    fn cancelled_ping(&self, scheduled_game_id: i32) -> String {
+        format!("la partie #{scheduled_game_id} a été annulée.")
+    }
+
+    fn game_time(&self, scheduled_game_id: i32) -> String {
+        format!("c'est l'heure de la partie #{scheduled_game_id} !")
+    }
+
+    fn started_late(&self, when: &str) -> String {
+        format!("(Désolé du retard, elle était prévue pour {when})")
+    }
+
+    fn reminder(&self, organizer: &str, channel_name: &str, when: &str) -> String {
+        format!("Rappel : la partie de {organizer} dans #{channel_name} commence {when}")
+    }
+
+    fn organizing_game(&self) -> String {
+        "C'est toi qui organises cette partie !".to_string()
+    }
+
+    fn game_over(&self) -> String {
+        "Cette partie a déjà commencé ou a été annulée.".to_string()
+    }
+
+    fn rsvp_failed(&self) -> String {
+        "Je n'ai pas pu enregistrer ta réponse, réessaie plus tard.".to_string()
+    }
+
+    fn see_you(&self, when: &str, reminder_minutes: i64) -> String {
+        format!("À {when} ! Je te le rappellerai {reminder_minutes} minutes avant le début.")
+    }
+
+    fn game_not_found(&self) -> String {
+        "Je ne trouve plus cette partie.".to_string()
+    }
+
+    fn admin_list(&self, owner: Option<&str>, admins: &[String], admin_roles: &[String]) -> String {
+        let mut lines = Vec::new();
+        if let Some(owner) = owner {
+            lines.push(format!("Propriétaire : {owner}"));
+        }
+        lines.push(format!("Admins : {}", if admins.is_empty() { "aucun".to_string() } else { admins.join(", ") }));
+        if !admin_roles.is_empty() {
+            lines.push(format!("Tous ceux qui ont un de ces rôles : {}", admin_roles.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    fn isnt_an_admin(&self, name: &str) -> String {
+        format!("{name} n'est pas admin.")
+    }
+
+    fn role_admin(&self, name: &str) -> String {
+        format!("{name} est admin grâce à ses rôles dans ce serveur, seul un modérateur du serveur peut les retirer.")
+    }
+
+    fn cant_remove_admin(&self, name: &str) -> String {
+        format!("Tu ne peux pas retirer {name}, seul quelqu'un au-dessus peut le faire.")
+    }
+
+    fn admin_removed(&self, name: &str) -> String {
+        format!("{name} n'est plus admin.")
+    }
+
+    fn cant_add_admin(&self) -> String {
+        "Les admins par rôle du serveur ne peuvent pas ajouter d'admins, demande à un admin ou au propriétaire.".to_string()
+    }
+
+    fn admin_added(&self, name: &str) -> String {
+        format!("{name} a été ajouté comme admin.")
+    }
+
+    fn already_an_admin(&self, name: &str) -> String {
+        format!("{name} est déjà admin.")
+    }
+
+    fn admin_cant_be_blacklisted(&self, name: &str) -> String {
+        format!("{name} est admin et ne peut pas être mis sur la liste noire.")
+    }
+
+    fn blacklisted(&self, name: &str) -> String {
+        format!("{name} a été mis sur la liste noire.")
+    }
+
+    fn already_blacklisted(&self, name: &str) -> String {
+        format!("{name} est déjà sur la liste noire.")
+    }
+
+    fn unblacklisted(&self, name: &str) -> String {
+        format!("{name} a été retiré de la liste noire.")
+    }
+
+    fn not_blacklisted(&self, name: &str) -> String {
+        format!("{name} n'est pas sur la liste noire.")
+    }
+
+    fn channel_cooldown(&self, channel_name: &str, cooldown: &str) -> String {
+        format!("Les notifications dans #{channel_name} sont envoyées au plus une fois toutes les {cooldown}")
+    }
+
+    fn channel_cooldown_changed(&self, channel_name: &str, cooldown: &str) -> String {
+        format!("Les notifications dans #{channel_name} seront maintenant envoyées au plus une fois toutes les {cooldown}")
+    }
+
+    fn server_settings(&self, settings: &ServerSettings) -> String {
+        let ServerSettings { help_text, cooldown, prefix, language, invitations, invitation_theme, game_calls, mod_log, call_limit, call_window } = *settings;
+        let help_text = help_text.map(|help_text| format!("\"{help_text}\"")).unwrap_or_else(|| "aucun".to_string());
+        let invitations = match (invitations, invitation_theme) {
+            (false, _) => "non".to_string(),
+            (true, None) => "oui".to_string(),
+            (true, Some(theme)) => format!("oui, avec la voix de {theme}")
+        };
+        format!("Message d'aide : {help_text}\nDélai entre notifications pour les salons qui n'ont pas le leur : {cooldown}\nPréfixe des commandes : {prefix}\nLangue : {language}\nInvitations personnalisées : {invitations}\nPropositions pour les appels à jouer : {}\nJournal de modération : {}\nGroupes par personne : {call_limit} toutes les {call_window} (les admins n'ont pas de limite)", if game_calls { "oui" } else { "non" }, mod_log.unwrap_or("aucun"))
+    }
+
+    fn help_message_missing(&self) -> String {
+        "Donne-moi le texte à afficher au-dessus de mes commandes, ou off pour le retirer".to_string()
+    }
+
+    fn help_message_changed(&self, removed: bool) -> String {
+        format!("Le message d'aide a été {}", if removed { "retiré" } else { "changé" })
+    }
+
+    fn server_cooldown_changed(&self, cooldown: &str) -> String {
+        format!("Les notifications dans les salons qui n'ont pas leur propre délai seront maintenant envoyées au plus une fois toutes les {cooldown}")
+    }
+
+    fn invalid_prefix(&self, text: &str) -> String {
+        format!("\"{text}\" ne peut pas être un préfixe, il doit faire de 1 à 5 caractères sans espace et ne pas commencer par /")
+    }
+
+    fn prefix_changed(&self, help_command: &str) -> String {
+        format!("Mes commandes ressemblent maintenant à {help_command}")
+    }
+
+    fn server_language_changed(&self, language: &str) -> String {
+        format!("Je parlerai maintenant en {language} dans ce serveur")
+    }
+
+    fn on_or_off(&self, setting: &str) -> String {
+        format!("Dis-moi on ou off pour {setting}")
+    }
+
+    fn invitations_changed(&self, on: bool) -> String {
+        if on {
+            "Les notifications de partie seront maintenant écrites comme des invitations personnalisées, quand le modèle de langage est disponible".to_string()
+        }
+        else {
+            "Les notifications de partie utiliseront maintenant le texte habituel".to_string()
+        }
+    }
+
+    fn invitations_unavailable(&self) -> String {
+        "Les invitations personnalisées ont besoin d'un modèle de langage, le propriétaire du bot doit définir OPENAI_API_KEY".to_string()
+    }
+
+    fn game_calls_changed(&self, on: bool) -> String {
+        if on {
+            "Les messages qui ressemblent à quelqu'un qui cherche des joueurs recevront maintenant une proposition de prévenir les abonnés du salon".to_string()
+        }
+        else {
+            "Je ne chercherai plus d'appels à jouer dans les messages".to_string()
+        }
+    }
+
+    fn game_calls_unavailable(&self) -> String {
+        "Les propositions pour les appels à jouer ont besoin d'un modèle de langage, le propriétaire du bot doit définir OPENAI_API_KEY".to_string()
+    }
+
+    fn invitation_theme_missing(&self) -> String {
+        "Dis-moi avec quelle voix écrire les invitations, par exemple un moine d'age of empires, ou off pour l'enlever".to_string()
+    }
+
+    fn invitation_theme_changed(&self, theme: Option<&str>) -> String {
+        match theme {
+            Some(theme) => format!("Les invitations seront maintenant écrites avec la voix de {theme}"),
+            None => "Les invitations seront maintenant écrites sans thème".to_string()
+        }
+    }
+
+    fn unknown_channel(&self, text: &str) -> String {
+        format!("\"{text}\" n'est pas un salon, mentionne-le comme #mod-log")
+    }
+
+    fn mod_log_changed(&self, channel: Option<&str>) -> String {
+        match channel {
+            Some(channel) => format!("Les actions d'admin seront maintenant aussi publiées dans {channel}"),
+            None => "Les actions d'admin ne seront plus publiées dans un salon".to_string()
+        }
+    }
+
+    fn invalid_call_limit(&self, text: &str, max_window: &str) -> String {
+        format!("\"{text}\" n'est pas une limite, dis-moi combien de groupes pour quelle durée (jusqu'à {max_window}), par ex. 3 30m, ou off pour revenir à la valeur par défaut")
+    }
+
+    fn call_limit_changed(&self, limit: i32, window: &str) -> String {
+        format!("Tout le monde sauf les admins peut maintenant lancer {limit} groupe(s) toutes les {window}")
+    }
+
+    fn unknown_setting(&self, setting: &str) -> String {
+        format!("Je ne connais pas le réglage \"{setting}\", essaie help-message, cooldown, prefix, language, invitations, theme, game-calls, mod-log ou call-limit")
+    }
+
+    fn audit_entry(&self, line: &AuditLine) -> String {
+        let AuditLine { when, actor, action, target, details, channel } = *line;
+        let target = target.unwrap_or("quelqu'un");
+        let did = match action {
+            AuditAction::AdminAdded => format!("a fait de {target} un admin"),
+            AuditAction::AdminRemoved => format!("a retiré {target} des admins"),
+            AuditAction::Blacklisted => format!("a mis {target} sur la liste noire"),
+            AuditAction::Unblacklisted => format!("a retiré {target} de la liste noire"),
+            AuditAction::SettingChanged => format!("a réglé {}", details.unwrap_or_default()),
+            AuditAction::GameAdded => format!("a ajouté le jeu {}", details.unwrap_or_default()),
+            AuditAction::GameRemoved => format!("a retiré le jeu {}", details.unwrap_or_default())
+        };
+        let in_channel = channel.map(|channel| format!(" dans {channel}")).unwrap_or_default();
+        format!("{when} {actor} {did}{in_channel}")
+    }
+
+    fn audit_log(&self, lines: &[String]) -> String {
+        format!("Les {} dernières actions d'admin, les plus récentes d'abord :\n{}", lines.len(), lines.join("\n"))
+    }
+
+    fn no_audit_entries(&self) -> String {
+        "Aucune action d'admin n'a encore été enregistrée dans ce serveur.".to_string()
+    }
+
+    fn stats(&self, stats: &Stats) -> String {
+        let mut lines = vec![format!("Ces {} derniers jours :", stats.days), "Organisateurs les plus actifs :".to_string()];
+        lines.extend(stats.organizers.iter().map(|(organizer, calls)| format!("{organizer} : {calls} {}", if *calls == 1 { "appel" } else { "appels" })));
+        lines.push("Joueurs qui répondent le plus :".to_string());
+        if stats.players.is_empty() {
+            lines.push("Personne n'a encore rien rejoint".to_string());
+        }
+        lines.extend(stats.players.iter().map(|(player, joined, received)| format!("{player} : {joined} rejoints sur {received}")));
+        lines.push(format!("Heures les plus actives ({}) :", stats.timezone));
+        lines.extend(stats.busiest_hours.iter().map(|(channel, hour, calls)| format!("{channel} : {hour} ({calls} {})", if *calls == 1 { "appel" } else { "appels" })));
+        lines.push(match stats.own_response {
+            (_, 0) => "Tu n'as été prévenu de rien".to_string(),
+            (joined, received) => format!("Tu as rejoint {joined} des {received} appels dont tu as été prévenu ({} %)", joined * 100 / received)
+        });
+        lines.join("\n")
+    }
+
+    fn no_stats(&self, days: i64) -> String {
+        format!("Personne n'a lancé de groupe ni prévu de partie dans ce serveur ces {days} derniers jours.")
+    }
+
+    fn notification_gap(&self, gap: &str) -> String {
+        format!("Je t'envoie des notifications au plus une fois toutes les {gap}")
+    }
+
+    fn no_notification_gap(&self) -> String {
+        "Tu reçois toutes les notifications que les délais des salons permettent".to_string()
+    }
+
+    fn notification_gap_changed(&self, gap: &str) -> String {
+        format!("Je t'enverrai des notifications au plus une fois toutes les {gap}")
+    }
+
+    fn notification_gap_off(&self) -> String {
+        "Tu recevras toutes les notifications que les délais des salons permettent".to_string()
+    }
+
+    fn timezone(&self, timezone: &str) -> String {
+        format!("Ton fuseau horaire est {timezone}")
+    }
+
+    fn no_timezone(&self, timezone_command: &str) -> String {
+        format!("Tu n'as pas de fuseau horaire, tu peux en régler un comme ceci : {timezone_command} Europe/Paris")
+    }
+
+    fn timezone_changed(&self, timezone: &str) -> String {
+        format!("Ton fuseau horaire est maintenant {timezone}")
+    }
+
+    fn quiet_hours(&self, start: &str, end: &str, timezone: &str) -> String {
+        format!("Tes heures calmes sont {start}-{end} ({timezone})")
+    }
+
+    fn no_quiet_hours(&self, quiet_hours_command: &str) -> String {
+        format!("Tu n'as pas d'heures calmes, tu peux les régler comme ceci : {quiet_hours_command} 23:00-08:00 Europe/Paris")
+    }
+
+    fn quiet_hours_off(&self) -> String {
+        "Tes heures calmes sont désactivées".to_string()
+    }
+
+    fn unknown_time_window(&self, text: &str) -> String {
+        format!("Je ne comprends pas \"{text}\", essaie quelque chose comme 23:00-08:00")
+    }
+
+    fn quiet_hours_need_timezone(&self, quiet_hours_command: &str, window: &str) -> String {
+        format!("J'ai aussi besoin de ton fuseau horaire, par exemple : {quiet_hours_command} {window} Europe/Paris")
+    }
+
+    fn quiet_hours_changed(&self, start: &str, end: &str, timezone: &str) -> String {
+        format!("Je ne t'enverrai pas de notifications entre {start} et {end} ({timezone})")
+    }
+
+    fn language(&self, language: &str) -> String {
+        format!("Je te parle en {language}")
+    }
+
+    fn no_language(&self, language: &str) -> String {
+        format!("Tu n'as pas choisi de langue, j'utilise donc celle du serveur : {language}")
+    }
+
+    fn language_changed(&self, language: &str) -> String {
+        format!("Je te parlerai maintenant en {language}")
+    }
+
+    fn language_off(&self, language: &str) -> String {
+        format!("J'utiliserai la langue du serveur avec toi : {language}")
+    }
+
+    fn unknown_language(&self, text: &str, languages: &str) -> String {
+        format!("Je ne parle pas \"{text}\", essaie une de ces langues : {languages}")
+    }
+
+    fn no_subscriptions(&self, on_command: &str) -> String {
+        format!("Tu n'es abonné aux notifications nulle part. Utilise {on_command} dans un salon pour t'y abonner.")
+    }
+
+    fn subscriptions(&self, lines: &[String]) -> String {
+        format!("Tu es abonné aux notifications de recherche de partie dans :\n{}", lines.join("\n"))
+    }
+
+    fn subscription_line(&self, channel_name: &str, guild_name: Option<&str>, game: Option<&str>, since: &str, last_notified: Option<&str>) -> String {
+        let guild_name = guild_name.map(|guild_name| format!(" ({guild_name})")).unwrap_or_default();
+        let game = game.unwrap_or("tous les jeux");
+        let last_notified = match last_notified {
+            Some(last_notified) => format!("dernière notification {last_notified}"),
+            None => "jamais notifié".to_string()
+        };
+        format!("#{channel_name}{guild_name}, {game} : depuis {since}, {last_notified}")
+    }
+
+    fn too_many_subscriptions(&self, max_buttons: usize, off_command: &str) -> String {
+        format!("(Je ne peux afficher que {max_buttons} boutons, utilise {off_command} dans les autres salons pour te désabonner)")
+    }
+
+    fn unsubscribe_button(&self, game: Option<&str>, channel_name: &str) -> String {
+        match game {
+            Some(game) => format!("Se désabonner de {game} dans #{channel_name}"),
+            None => format!("Se désabonner de #{channel_name}")
+        }
+    }
+
+    fn not_your_subscriptions(&self, my_subscriptions_command: &str) -> String {
+        format!("Ce ne sont pas tes abonnements, tu peux voir les tiens avec {my_subscriptions_command}")
+    }
+
+    fn unknown_server(&self) -> String {
+        "un serveur inconnu".to_string()
+    }
+
+    fn deleted_channel(&self) -> String {
+        "salon-supprimé".to_string()
+    }
+
+    fn reply_hint(&self, channel_name: &str, stop_reply: &str, stop_all_reply: &str) -> String {
+        format!("Réponds '{stop_reply}' pour ne plus recevoir de notifications de #{channel_name}, ou '{stop_all_reply}' pour ne plus en recevoir de nulle part. Tout le reste, comme 'j'arrive dans 10m', lui est transmis.")
+    }
+
+    fn not_subscribed_anywhere(&self) -> String {
+        "Tu n'étais abonné aux notifications nulle part.".to_string()
+    }
+
+    fn unsubscribed_everywhere(&self, channel_count: u64) -> String {
+        format!("Je ne t'enverrai plus de notifications, tu as été désabonné de {channel_count} salon(s).")
+    }
+
+    fn unknown_notification(&self, stop_all_reply: &str) -> String {
+        format!("Je ne sais pas de quelle partie tu parles. Réponds à une de mes notifications, ou dis '{stop_all_reply}' pour ne plus en recevoir.")
+    }
+
+    fn not_allowed(&self) -> String {
+        "Tu n'as pas le droit de faire ça.".to_string()
+    }
+
+    fn unsubscribed_from_channel(&self, channel: &str) -> String {
+        format!("Je ne t'enverrai plus de notifications de {channel}.")
+    }
+
+    fn already_unsubscribed_from_channel(&self, channel: &str) -> String {
+        format!("Tu n'es déjà plus abonné aux notifications de {channel}.")
+    }
+
+    fn relay_thread_name(&self) -> String {
+        "Réponses par mp".to_string()
+    }
+
+    fn relayed_reply(&self, organizer: &str, user: &str, text: &str) -> String {
+        format!("{organizer}{user} a répondu à la notification : {text}")
+    }
+
+    fn relayed(&self, thread: &str) -> String {
+        format!("Je l'ai transmis dans {thread}.")
+    }
+
+    fn relay_failed(&self, channel: &str) -> String {
+        format!("Je n'ai pas pu le transmettre, tu peux leur répondre dans {channel} à la place.")
+    }
+}
@@ -0,0 +1,392 @@
+mod en;
+mod fr;
+
+use tracing::error;
+use crate::audit::AuditAction;
+use crate::constants;
+use crate::store::Store;
+use crate::slash_commands::slash_name;
+
+/// The languages the bot can reply in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Locale {
+    #[default]
+    En,
+    Fr,
+}
+
+impl Locale {
+    pub(crate) const ALL: [Locale; 2] = [Locale::En, Locale::Fr];
+
+    /// What is stored in the database
+    pub(crate) fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr"
+        }
+    }
+
+    /// The language's name in itself
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::Fr => "Français"
+        }
+    }
+
+    /// Takes the code or the language's name in english or in itself, e.g. "fr", "french" or "français"
+    pub(crate) fn parse(text: &str) -> Option<Locale> {
+        match text.trim().to_lowercase().as_str() {
+            "en" | "english" | "anglais" => Some(Locale::En),
+            "fr" | "french" | "français" | "francais" => Some(Locale::Fr),
+            _ => None
+        }
+    }
+
+    pub(crate) fn messages(self) -> &'static dyn Messages {
+        match self {
+            Locale::En => &en::English,
+            Locale::Fr => &fr::French
+        }
+    }
+}
+
+/// The language of messages posted in a guild's channels, english unless an admin picked another one
+pub(crate) async fn get_guild_locale(database: &dyn Store, guild_id: Option<u64>) -> Locale {
+    let Some(guild_id) = guild_id else {
+        return Locale::default();
+    };
+    match database.get_guild_settings(guild_id).await {
+        Ok(settings) => settings.and_then(|settings| settings.locale).as_deref().and_then(Locale::parse).unwrap_or_default(),
+        Err(error) => {
+            error!("Unable to get settings for guild {}: {:?}", guild_id, error);
+            Locale::default()
+        }
+    }
+}
+
+/// The language to reply to a user in, their own choice wins over their guild's
+pub(crate) async fn get_locale(database: &dyn Store, guild_id: Option<u64>, user_discord_id: u64) -> Locale {
+    match database.get_user_settings(user_discord_id).await {
+        Ok(settings) => match settings.and_then(|settings| settings.locale).as_deref().and_then(Locale::parse) {
+            Some(locale) => locale,
+            None => get_guild_locale(database, guild_id).await
+        },
+        Err(error) => {
+            error!("Unable to get settings for user {}: {:?}", user_discord_id, error);
+            get_guild_locale(database, guild_id).await
+        }
+    }
+}
+
+/// A text command the way people have to type it in a guild, e.g. "?help" when the guild's prefix is "?"
+pub(crate) fn with_prefix(command: &str, prefix: &str) -> String {
+    format!("{}{}", prefix, slash_name(command))
+}
+
+/// Every text command with the guild's prefix, for the help text
+pub(crate) struct Commands {
+    pub(crate) help: String,
+    pub(crate) register: String,
+    pub(crate) unregister: String,
+    pub(crate) game_notification_on: String,
+    pub(crate) game_notification_off: String,
+    pub(crate) my_subscriptions: String,
+    pub(crate) any_gamers: String,
+    pub(crate) add_admins: String,
+    pub(crate) blacklist: String,
+    pub(crate) unblacklist: String,
+    pub(crate) channel_cooldown: String,
+    pub(crate) notification_gap: String,
+    pub(crate) timezone: String,
+    pub(crate) quiet_hours: String,
+    pub(crate) schedule: String,
+    pub(crate) scheduled: String,
+    pub(crate) cancel_game: String,
+    pub(crate) games: String,
+    pub(crate) server_settings: String,
+    pub(crate) language: String,
+    pub(crate) audit: String,
+    pub(crate) stats: String,
+}
+
+impl Commands {
+    pub(crate) fn new(prefix: &str) -> Self {
+        Commands {
+            help: with_prefix(constants::HELP_CMD, prefix),
+            register: with_prefix(constants::REGISTER_CMD, prefix),
+            unregister: with_prefix(constants::UNREGISTER_CMD, prefix),
+            game_notification_on: with_prefix(constants::GAME_NOTIFICATION_ON_CMD, prefix),
+            game_notification_off: with_prefix(constants::GAME_NOTIFICATION_OFF_CMD, prefix),
+            my_subscriptions: with_prefix(constants::MY_SUBSCRIPTIONS_CMD, prefix),
+            any_gamers: with_prefix(constants::ANY_GAMERS_CMD, prefix),
+            add_admins: with_prefix(constants::ADD_ADMINS_CMD, prefix),
+            blacklist: with_prefix(constants::BLACKLIST_CMD, prefix),
+            unblacklist: with_prefix(constants::UNBLACKLIST_CMD, prefix),
+            channel_cooldown: with_prefix(constants::CHANNEL_COOLDOWN_CMD, prefix),
+            notification_gap: with_prefix(constants::NOTIFICATION_GAP_CMD, prefix),
+            timezone: with_prefix(constants::TIMEZONE_CMD, prefix),
+            quiet_hours: with_prefix(constants::QUIET_HOURS_CMD, prefix),
+            schedule: with_prefix(constants::SCHEDULE_CMD, prefix),
+            scheduled: with_prefix(constants::SCHEDULED_CMD, prefix),
+            cancel_game: with_prefix(constants::CANCEL_GAME_CMD, prefix),
+            games: with_prefix(constants::GAMES_CMD, prefix),
+            server_settings: with_prefix(constants::SERVER_SETTINGS_CMD, prefix),
+            language: with_prefix(constants::LANGUAGE_CMD, prefix),
+            audit: with_prefix(constants::AUDIT_CMD, prefix),
+            stats: with_prefix(constants::STATS_CMD, prefix),
+        }
+    }
+}
+
+/// What the dms about a new stack or scheduled game have in common
+pub(crate) struct Notification<'a> {
+    // the organizer's name, the dm shouldn't ping them
+    pub(crate) organizer: &'a str,
+    pub(crate) game: Option<&'a str>,
+    pub(crate) channel_name: &'a str,
+    pub(crate) description: &'a str,
+    // written by the language model, replaces the usual text up to the reply hint
+    pub(crate) invitation: Option<&'a str>,
+    pub(crate) reply_hint: &'a str,
+    pub(crate) decline_emoji: &'a str,
+}
+
+/// The stack summary or game announcement posted in the channel, the organizer and the answers are mentions
+pub(crate) struct Roster<'a> {
+    pub(crate) organizer: &'a str,
+    pub(crate) game: Option<&'a str>,
+    pub(crate) description: &'a str,
+    // who joined or is coming
+    pub(crate) accepted: &'a [String],
+    pub(crate) declined: &'a [String],
+}
+
+/// What `!server-settings` shows, the values are already formatted
+pub(crate) struct ServerSettings<'a> {
+    pub(crate) help_text: Option<&'a str>,
+    pub(crate) cooldown: &'a str,
+    pub(crate) prefix: &'a str,
+    pub(crate) language: &'a str,
+    pub(crate) invitations: bool,
+    pub(crate) invitation_theme: Option<&'a str>,
+    pub(crate) game_calls: bool,
+    // a channel mention
+    pub(crate) mod_log: Option<&'a str>,
+    // stacks per window for everyone but admins
+    pub(crate) call_limit: i32,
+    pub(crate) call_window: &'a str,
+}
+
+/// An entry of the audit log, the users and the channel are mentions
+pub(crate) struct AuditLine<'a> {
+    pub(crate) when: &'a str,
+    pub(crate) actor: &'a str,
+    pub(crate) action: AuditAction,
+    pub(crate) target: Option<&'a str>,
+    pub(crate) details: Option<&'a str>,
+    pub(crate) channel: Option<&'a str>,
+}
+
+/// What `!stats` shows, the users and channels are mentions and the hours are already formatted
+pub(crate) struct Stats<'a> {
+    pub(crate) days: i64,
+    // mention and how many stacks or games they called
+    pub(crate) organizers: &'a [(String, usize)],
+    // mention, how many dms they joined and how many they got
+    pub(crate) players: &'a [(String, usize, usize)],
+    // channel mention, the busiest hour and how many calls started in it
+    pub(crate) busiest_hours: &'a [(String, String, usize)],
+    pub(crate) timezone: &'a str,
+    // how many of their dms the caller joined and how many they got
+    pub(crate) own_response: (usize, usize),
+}
+
+/// Everything the bot says, one implementation per language. Commands mentioned in a message are passed in with the
+/// guild's prefix already applied, mentions (`<@id>`, `<#id>`) and timestamps are passed in already formatted
+pub(crate) trait Messages: Sync {
+    // help
+    /// Every command, one per line starting with the command followed by a colon so `!help <command>` can find it
+    fn help_text(&self, commands: &Commands) -> String;
+
+    // shared by many commands
+    fn database_trouble(&self) -> String;
+    fn not_registered(&self, register_command: &str) -> String;
+    fn not_an_admin(&self) -> String;
+    fn only_in_a_server(&self, command: &str) -> String;
+    fn unknown_duration(&self, text: &str) -> String;
+    fn unknown_duration_or_off(&self, text: &str) -> String;
+    fn unknown_timezone(&self, text: &str) -> String;
+    fn registration_failed(&self) -> String;
+    /// What the organizer wrote after the command, e.g. "They also said this: ranked"
+    fn also_said(&self, description: &str) -> String;
+
+    // registration
+    fn registered(&self) -> String;
+    fn unregistered(&self) -> String;
+    fn unregistered_check_dms(&self) -> String;
+    fn unregistered_dm(&self, register_command: &str) -> String;
+
+    // subscriptions in a channel
+    /// e.g. "game search notifications for aoe2 in #general"
+    fn notifications_for(&self, game: Option<&str>, channel_name: &str) -> String;
+    fn already_signed_up(&self, notifications: &str) -> String;
+    fn signed_up(&self, notifications: &str) -> String;
+    fn removed_from(&self, notifications: &str) -> String;
+    fn signed_up_for_every_game(&self, channel_name: &str, off_command: &str) -> String;
+    fn not_signed_up(&self, notifications: &str) -> String;
+
+    // games
+    fn unknown_game(&self, name: &str) -> String;
+    fn known_games(&self, names: &[&str]) -> String;
+    fn no_games(&self) -> String;
+    /// e.g. "🏰 aoe2 (aoe, age): stacks of 8"
+    fn game_description(&self, label: &str, aliases: &[String], default_party_size: i32) -> String;
+    fn games_list(&self, descriptions: &[String]) -> String;
+    fn game_options_help(&self, games_command: &str) -> String;
+    fn game_name_taken(&self, name: &str, game: &str) -> String;
+    fn game_added(&self, description: &str, subscribe_command: &str) -> String;
+    fn game_exists(&self, name: &str) -> String;
+    fn shared_game(&self, name: &str) -> String;
+    fn game_removed(&self, name: &str) -> String;
+
+    // stacks
+    fn join_button(&self) -> String;
+    fn not_this_time_button(&self) -> String;
+    fn stack_failed(&self, channel_name: &str) -> String;
+    fn stack_notification(&self, notification: &Notification, target_size: i32) -> String;
+    fn stack_summary(&self, roster: &Roster, joined_count: usize, target_size: i32, full: bool) -> String;
+    fn stack_full(&self, mentions: &str) -> String;
+    fn organizing_stack(&self) -> String;
+    fn stack_update_failed(&self) -> String;
+    fn joined_stack(&self, organizer: &str) -> String;
+    fn told_organizer(&self, organizer: &str) -> String;
+    fn stack_not_found(&self) -> String;
+    /// e.g. "3 notified, 2 skipped (quiet hours)"
+    fn notification_summary(&self, notified: usize, skipped_cooldown: usize, skipped_quiet_hours: usize, skipped_hourly_cap: usize, failed: usize) -> String;
+    fn call_limit_reached(&self, limit: i32, window: &str, wait: &str) -> String;
+    /// Offered to someone who asked for players in plain words instead of with the command
+    fn game_call_offer(&self, game: Option<&str>, subscribers: usize, any_gamers_command: &str) -> String;
+    fn notify_subscribers_button(&self, subscribers: usize) -> String;
+    fn not_your_game_call(&self) -> String;
+
+    // scheduled games
+    fn coming_button(&self) -> String;
+    fn not_coming_button(&self) -> String;
+    fn in_the_past(&self, when: &str) -> String;
+    fn unknown_when(&self, text: &str, schedule_command: &str) -> String;
+    fn schedule_failed(&self, channel_name: &str) -> String;
+    fn schedule_notification(&self, notification: &Notification, when: &str) -> String;
+    fn scheduled(&self, scheduled_game_id: i32, when: &str, summary: &str) -> String;
+    fn assumed_utc(&self, timezone_command: &str) -> String;
+    fn announcement(&self, roster: &Roster, scheduled_game_id: i32, when: &str, cancelled: bool) -> String;
+    fn no_scheduled_games(&self, channel_name: &str, schedule_command: &str) -> String;
+    fn scheduled_game_line(&self, scheduled_game_id: i32, game: Option<&str>, when: &str, organizer: &str, description: &str) -> String;
+    fn scheduled_games(&self, channel_name: &str, lines: &[String]) -> String;
+    fn which_game(&self, scheduled_command: &str) -> String;
+    fn game_number_not_found(&self, scheduled_game_id: i32) -> String;
+    fn cancel_not_allowed(&self, scheduled_game_id: i32) -> String;
+    fn cancelled(&self, scheduled_game_id: i32) -> String;
+    fn already_over(&self, scheduled_game_id: i32) -> String;
+    /// Pinged at everyone who is coming
+    fn cancelled_ping(&self, scheduled_game_id: i32) -> String;
+    fn game_time(&self, scheduled_game_id: i32) -> String;
+    fn started_late(&self, when: &str) -> String;
+    fn reminder(&self, organizer: &str, channel_name: &str, when: &str) -> String;
+    fn organizing_game(&self) -> String;
+    fn game_over(&self) -> String;
+    fn rsvp_failed(&self) -> String;
+    fn see_you(&self, when: &str, reminder_minutes: i64) -> String;
+    fn game_not_found(&self) -> String;
+
+    // admins and the blacklist
+    fn admin_list(&self, owner: Option<&str>, admins: &[String], admin_roles: &[String]) -> String;
+    fn isnt_an_admin(&self, name: &str) -> String;
+    fn role_admin(&self, name: &str) -> String;
+    fn cant_remove_admin(&self, name: &str) -> String;
+    fn admin_removed(&self, name: &str) -> String;
+    fn cant_add_admin(&self) -> String;
+    fn admin_added(&self, name: &str) -> String;
+    fn already_an_admin(&self, name: &str) -> String;
+    fn admin_cant_be_blacklisted(&self, name: &str) -> String;
+    fn blacklisted(&self, name: &str) -> String;
+    fn already_blacklisted(&self, name: &str) -> String;
+    fn unblacklisted(&self, name: &str) -> String;
+    fn not_blacklisted(&self, name: &str) -> String;
+
+    // channel and server settings
+    fn channel_cooldown(&self, channel_name: &str, cooldown: &str) -> String;
+    fn channel_cooldown_changed(&self, channel_name: &str, cooldown: &str) -> String;
+    fn server_settings(&self, settings: &ServerSettings) -> String;
+    fn help_message_missing(&self) -> String;
+    fn help_message_changed(&self, removed: bool) -> String;
+    fn server_cooldown_changed(&self, cooldown: &str) -> String;
+    fn invalid_prefix(&self, text: &str) -> String;
+    fn prefix_changed(&self, help_command: &str) -> String;
+    fn server_language_changed(&self, language: &str) -> String;
+    fn on_or_off(&self, setting: &str) -> String;
+    fn invitations_changed(&self, on: bool) -> String;
+    fn invitations_unavailable(&self) -> String;
+    fn game_calls_changed(&self, on: bool) -> String;
+    fn game_calls_unavailable(&self) -> String;
+    fn invitation_theme_missing(&self) -> String;
+    fn invitation_theme_changed(&self, theme: Option<&str>) -> String;
+    fn unknown_channel(&self, text: &str) -> String;
+    fn mod_log_changed(&self, channel: Option<&str>) -> String;
+    fn invalid_call_limit(&self, text: &str, max_window: &str) -> String;
+    fn call_limit_changed(&self, limit: i32, window: &str) -> String;
+    fn unknown_setting(&self, setting: &str) -> String;
+
+    // audit log
+    fn audit_entry(&self, line: &AuditLine) -> String;
+    fn audit_log(&self, lines: &[String]) -> String;
+    fn no_audit_entries(&self) -> String;
+
+    // notification history
+    fn stats(&self, stats: &Stats) -> String;
+    fn no_stats(&self, days: i64) -> String;
+
+    // personal settings
+    fn notification_gap(&self, gap: &str) -> String;
+    fn no_notification_gap(&self) -> String;
+    fn notification_gap_changed(&self, gap: &str) -> String;
+    fn notification_gap_off(&self) -> String;
+    fn timezone(&self, timezone: &str) -> String;
+    fn no_timezone(&self, timezone_command: &str) -> String;
+    fn timezone_changed(&self, timezone: &str) -> String;
+    fn quiet_hours(&self, start: &str, end: &str, timezone: &str) -> String;
+    fn no_quiet_hours(&self, quiet_hours_command: &str) -> String;
+    fn quiet_hours_off(&self) -> String;
+    fn unknown_time_window(&self, text: &str) -> String;
+    fn quiet_hours_need_timezone(&self, quiet_hours_command: &str, window: &str) -> String;
+    fn quiet_hours_changed(&self, start: &str, end: &str, timezone: &str) -> String;
+    fn language(&self, language: &str) -> String;
+    fn no_language(&self, language: &str) -> String;
+    fn language_changed(&self, language: &str) -> String;
+    fn language_off(&self, language: &str) -> String;
+    fn unknown_language(&self, text: &str, languages: &str) -> String;
+
+    // subscription lists
+    fn no_subscriptions(&self, on_command: &str) -> String;
+    fn subscriptions(&self, lines: &[String]) -> String;
+    fn subscription_line(&self, channel_name: &str, guild_name: Option<&str>, game: Option<&str>, since: &str, last_notified: Option<&str>) -> String;
+    fn too_many_subscriptions(&self, max_buttons: usize, off_command: &str) -> String;
+    fn unsubscribe_button(&self, game: Option<&str>, channel_name: &str) -> String;
+    fn not_your_subscriptions(&self, my_subscriptions_command: &str) -> String;
+    fn unknown_server(&self) -> String;
+    fn deleted_channel(&self) -> String;
+
+    // replies to notification dms
+    fn reply_hint(&self, channel_name: &str, stop_reply: &str, stop_all_reply: &str) -> String;
+    fn not_subscribed_anywhere(&self) -> String;
+    fn unsubscribed_everywhere(&self, channel_count: u64) -> String;
+    fn unknown_notification(&self, stop_all_reply: &str) -> String;
+    fn not_allowed(&self) -> String;
+    fn unsubscribed_from_channel(&self, channel: &str) -> String;
+    fn already_unsubscribed_from_channel(&self, channel: &str) -> String;
+    fn relay_thread_name(&self) -> String;
+    /// Posted in the thread, `organizer` is a mention or empty and `user` is a mention
+    fn relayed_reply(&self, organizer: &str, user: &str, text: &str) -> String;
+    fn relayed(&self, thread: &str) -> String;
+    fn relay_failed(&self, channel: &str) -> String;
+}
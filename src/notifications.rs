@@ -0,0 +1,260 @@
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use serenity::all::{ChannelId, CreateMessage, MessageId, UserId};
+use tracing::error;
+use crate::chat::ChatBackend;
+use crate::constants;
+use crate::errors::WololoError;
+use crate::messages::{get_guild_locale, Locale, Messages};
+use crate::parsing::{minutes_after_midnight, parse_timezone};
+use crate::store::Store;
+use crate::structs::{ParentMessageChildMessage, Ping, UserSettings};
+
+/// The channel message that the notification dms belong to, reactions to the dms are tracked against it
+pub(crate) struct NotificationParent {
+    pub(crate) guild_id: u64,
+    pub(crate) message_id: MessageId,
+    pub(crate) lobby_id: Option<i32>,
+    pub(crate) scheduled_game_id: Option<i32>,
+    // what the organizer wrote, kept in the notification history
+    pub(crate) description: String,
+}
+
+#[derive(Default)]
+pub(crate) struct NotificationSummary {
+    pub(crate) notified: usize,
+    pub(crate) skipped_cooldown: usize,
+    pub(crate) skipped_quiet_hours: usize,
+    pub(crate) skipped_hourly_cap: usize,
+    pub(crate) failed: usize,
+}
+
+impl NotificationSummary {
+    fn count(&mut self, delivery: Delivery) {
+        match delivery {
+            Delivery::Sent => self.notified += 1,
+            Delivery::Cooldown => self.skipped_cooldown += 1,
+            Delivery::QuietHours => self.skipped_quiet_hours += 1,
+            Delivery::HourlyCap => self.skipped_hourly_cap += 1,
+            Delivery::Failed => self.failed += 1,
+        }
+    }
+
+    /// e.g. "3 notified, 2 skipped (quiet hours)"
+    pub(crate) fn describe(&self, messages: &dyn Messages) -> String {
+        messages.notification_summary(self.notified, self.skipped_cooldown, self.skipped_quiet_hours, self.skipped_hourly_cap, self.failed)
+    }
+}
+
+/// Sends the message to everyone subscribed to the game (or to every game when `game_id` is None) in the channel except
+/// the organizer, skipping anyone who is in their cooldown or quiet hours or already got their hourly share of dms. The
+/// message is built in each subscriber's own locale, or the guild's when they haven't picked one
+///
+/// Up to `NOTIFICATION_CONCURRENCY` subscribers are handled at once so the last one doesn't wait on everyone before
+/// them, serenity holds back requests that would go over discord's rate limits. The subscribers who pass the checks are
+/// claimed in a single write before any dm goes out, so a notification that overlaps this one can't dm them too. The
+/// claim is given back for anyone whose dm couldn't be sent
+pub(crate) async fn notify_subscribers(chat: &dyn ChatBackend, database: &dyn Store, organizer_discord_id: u64, channel_id: ChannelId, game_id: Option<i32>, parent: &NotificationParent, builder: impl Fn(Locale) -> CreateMessage) -> Result<NotificationSummary, WololoError> {
+    let discord_channel_id = channel_id.get();
+    let channel_cooldown_seconds = get_notification_cooldown_for_channel(database, parent.guild_id, discord_channel_id).await;
+    let guild_locale = get_guild_locale(database, Some(parent.guild_id)).await;
+    let now = chrono::offset::Utc::now();
+    let pings = database.get_all_pings_except_for_user(organizer_discord_id, discord_channel_id, game_id).await?;
+    // a call nobody could be notified about still counts for the organizer
+    let notification_id = match database.create_notification(parent.guild_id, discord_channel_id, organizer_discord_id, parent.lobby_id, parent.scheduled_game_id, &parent.description).await {
+        Ok(notification) => Some(notification.id),
+        Err(error) => {
+            error!("Unable to save the notification by {} in channel {}: {:?}", organizer_discord_id, discord_channel_id, error);
+            None
+        }
+    };
+    let fan_out = FanOut { chat, database, parent, channel_cooldown_seconds, guild_locale, now, builder };
+    let checks = stream::iter(pings)
+        .map(|ping| fan_out.check(ping))
+        .buffer_unordered(constants::NOTIFICATION_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    let mut summary = NotificationSummary::default();
+    let mut recipients = vec![];
+    for check in checks {
+        match check {
+            Ok(recipient) => recipients.push(recipient),
+            Err(delivery) => summary.count(delivery)
+        }
+    }
+    let recipient_discord_ids = recipients.iter().map(|recipient| recipient.ping.user_discord_id as u64).collect::<Vec<_>>();
+    let claimed = match recipient_discord_ids.is_empty() {
+        true => vec![],
+        false => database.reserve_notification_dms(parent.guild_id, discord_channel_id, notification_id, &recipient_discord_ids, channel_cooldown_seconds, now).await?
+    };
+    // whoever an overlapping notification claimed first was just notified
+    let (recipients, taken) = recipients.into_iter().partition::<Vec<_>, _>(|recipient| claimed.contains(&(recipient.ping.user_discord_id as u64)));
+    summary.skipped_cooldown += taken.len();
+    let deliveries = stream::iter(recipients)
+        .map(|recipient| fan_out.send(recipient))
+        .buffer_unordered(constants::NOTIFICATION_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    for delivery in deliveries {
+        summary.count(delivery);
+    }
+    Ok(summary)
+}
+
+/// Saves how the recipient answered the dms they got about the lobby or scheduled game for the stats. Their answer was
+/// already saved, so failing to do so is only logged
+pub(crate) async fn record_response(database: &dyn Store, lobby_id: Option<i32>, scheduled_game_id: Option<i32>, recipient_discord_id: u64, accepted: bool) {
+    if let Err(error) = database.update_response_for_notification_dms(lobby_id, scheduled_game_id, recipient_discord_id, accepted).await {
+        error!("Unable to save the response of {} to the notifications about lobby {:?} scheduled game {:?}: {:?}", recipient_discord_id, lobby_id, scheduled_game_id, error);
+    }
+}
+
+enum Delivery {
+    Sent,
+    Cooldown,
+    QuietHours,
+    HourlyCap,
+    Failed,
+}
+
+/// What every subscriber's notification shares
+struct FanOut<'a, F> {
+    chat: &'a dyn ChatBackend,
+    database: &'a dyn Store,
+    parent: &'a NotificationParent,
+    channel_cooldown_seconds: i64,
+    guild_locale: Locale,
+    now: DateTime<Utc>,
+    builder: F,
+}
+
+/// A subscriber who passed the checks, in the language their dm is written in
+struct Recipient {
+    ping: Ping,
+    locale: Locale,
+}
+
+impl<F: Fn(Locale) -> CreateMessage> FanOut<'_, F> {
+    async fn check(&self, ping: Ping) -> Result<Recipient, Delivery> {
+        let settings = match self.database.get_user_settings(ping.user_discord_id as u64).await {
+            Ok(settings) => settings,
+            Err(error) => {
+                error!("Unable to get settings for user {}: {:?}", ping.user_discord_id, error);
+                None
+            }
+        };
+        match check_notification(self.database, &ping, settings.as_ref(), self.channel_cooldown_seconds, self.now).await {
+            NotificationCheck::Notify => {}
+            NotificationCheck::Cooldown => return Err(Delivery::Cooldown),
+            NotificationCheck::QuietHours => return Err(Delivery::QuietHours),
+            NotificationCheck::HourlyCap => return Err(Delivery::HourlyCap)
+        }
+        let locale = settings.and_then(|settings| settings.locale).as_deref().and_then(Locale::parse).unwrap_or(self.guild_locale);
+        Ok(Recipient { ping, locale })
+    }
+
+    async fn send(&self, recipient: Recipient) -> Delivery {
+        let (chat, database, parent) = (self.chat, self.database, self.parent);
+        let Recipient { ping, locale } = recipient;
+        let user = UserId::new(ping.user_discord_id as u64);
+        let child_message = match chat.send_dm(user, (self.builder)(locale)).await {
+            Ok(child_message) => child_message,
+            Err(e) => {
+                error!("Error sending dm to user {}: {:?}", user.get(), e);
+                // they didn't get anything, so they aren't in their cooldown and it doesn't count towards their cap
+                if let Err(error) = database.release_notification_dm(ping.discord_channel_id as u64, user.get(), self.now, ping.last_notified).await {
+                    error!("Unable to give back the notification dm of user {} in channel {}: {:?}", user.get(), ping.discord_channel_id, error);
+                }
+                return Delivery::Failed;
+            }
+        };
+        let parent_msg_child_msg = ParentMessageChildMessage {
+            parent: parent.message_id.get() as i64,
+            child: child_message.id.get() as i64,
+            parent_channel_id: ping.discord_channel_id,
+            child_channel_id: child_message.channel_id.get() as i64,
+            guild_id: Some(parent.guild_id as i64),
+            lobby_id: parent.lobby_id,
+            scheduled_game_id: parent.scheduled_game_id
+        };
+        if let Err(error) = database.create_child_for_message(parent_msg_child_msg).await {
+            error!("Unable to save message {} as child for parent message {}: {}", child_message.id.get(), parent.message_id.get(), error);
+        }
+        Delivery::Sent
+    }
+}
+
+/// The channel's own cooldown, or the guild's when the channel doesn't have one
+pub(crate) async fn get_notification_cooldown_for_channel(database: &dyn Store, guild_id: u64, discord_channel_id: u64) -> i64 {
+    match database.get_channel_settings(discord_channel_id).await {
+        Ok(Some(settings)) => return settings.notification_cooldown_seconds,
+        Ok(None) => {}
+        Err(error) => {
+            error!("Unable to get settings for channel {}: {:?}", discord_channel_id, error);
+            return constants::DEFAULT_NOTIFICATION_COOLDOWN_SECONDS;
+        }
+    }
+    get_notification_cooldown_for_guild(database, guild_id).await
+}
+
+pub(crate) async fn get_notification_cooldown_for_guild(database: &dyn Store, guild_id: u64) -> i64 {
+    match database.get_guild_settings(guild_id).await {
+        Ok(settings) => settings.and_then(|settings| settings.notification_cooldown_seconds).unwrap_or(constants::DEFAULT_NOTIFICATION_COOLDOWN_SECONDS),
+        Err(error) => {
+            error!("Unable to get settings for guild {}: {:?}", guild_id, error);
+            constants::DEFAULT_NOTIFICATION_COOLDOWN_SECONDS
+        }
+    }
+}
+
+enum NotificationCheck {
+    Notify,
+    Cooldown,
+    QuietHours,
+    HourlyCap,
+}
+
+/// A subscriber is only notified once the channel's cooldown and their own minimum gap between dms (across all
+/// channels) have both passed, so whichever of the two is larger wins. They are never notified during their quiet hours,
+/// nor once they got `MAX_DMS_PER_RECIPIENT_PER_HOUR` dms in the last hour from any channel.
+async fn check_notification(database: &dyn Store, ping: &Ping, settings: Option<&UserSettings>, channel_cooldown_seconds: i64, now: DateTime<Utc>) -> NotificationCheck {
+    let seconds_since = |last_notified: Option<DateTime<Utc>>| last_notified.map(|last_notified| now.timestamp() - last_notified.timestamp());
+    if seconds_since(ping.last_notified).is_some_and(|seconds| seconds < channel_cooldown_seconds) {
+        return NotificationCheck::Cooldown;
+    }
+    if let Some(settings) = settings {
+        if is_quiet_hours(settings, now) {
+            return NotificationCheck::QuietHours;
+        }
+        if let Some(gap) = settings.min_notification_gap_seconds.filter(|gap| *gap > 0) {
+            match database.get_last_notified_for_user(ping.user_discord_id as u64).await {
+                Ok(last_notified) if seconds_since(last_notified).is_some_and(|seconds| seconds < gap) => return NotificationCheck::Cooldown,
+                Ok(_) => {}
+                Err(error) => error!("Unable to get last notification for user {}: {:?}", ping.user_discord_id, error)
+            }
+        }
+    }
+    match database.count_notification_dms_for_user(ping.user_discord_id as u64, now - chrono::Duration::hours(1)).await {
+        Ok(count) if count >= constants::MAX_DMS_PER_RECIPIENT_PER_HOUR => NotificationCheck::HourlyCap,
+        Ok(_) => NotificationCheck::Notify,
+        Err(error) => {
+            error!("Unable to count the notification dms of user {}: {:?}", ping.user_discord_id, error);
+            NotificationCheck::Notify
+        }
+    }
+}
+
+fn is_quiet_hours(settings: &UserSettings, now: DateTime<Utc>) -> bool {
+    let (Some(timezone), Some(start), Some(end)) = (settings.timezone.as_deref().and_then(parse_timezone), settings.quiet_start_minutes, settings.quiet_end_minutes) else {
+        return false;
+    };
+    let local_time = now.with_timezone(&timezone).time();
+    let minutes = minutes_after_midnight(local_time);
+    if start <= end {
+        start <= minutes && minutes < end
+    }
+    else {
+        // the window wraps around midnight, e.g. 23:00-08:00
+        minutes >= start || minutes < end
+    }
+}
@@ -0,0 +1,88 @@
+use async_openai::config::OpenAIConfig;
+use async_openai::types::{ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequestArgs};
+use async_openai::Client;
+use serenity::async_trait;
+use crate::game_calls::{Classification, GameCall, GameCallClassifier, MAX_CLASSIFY_TOKENS};
+use crate::invitations::{Invitation, InvitationRequest, InvitationWriter, MAX_INVITATION_TOKENS};
+
+pub(crate) const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// The language model behind the features that need one, through OpenAI's chat completions
+pub(crate) struct OpenAi {
+    client: Client<OpenAIConfig>,
+    model: String,
+}
+
+impl OpenAi {
+    pub(crate) fn new(client: Client<OpenAIConfig>, model: String) -> Self {
+        OpenAi { client, model }
+    }
+
+    /// The model's answer to `text` following `instructions`, and the tokens it used
+    async fn complete(&self, instructions: &str, text: &str, max_tokens: u32, temperature: f32) -> anyhow::Result<(String, i64)> {
+        let request = CreateChatCompletionRequestArgs::default()
+            .model(&self.model)
+            .max_tokens(max_tokens)
+            .temperature(temperature)
+            .messages([
+                ChatCompletionRequestSystemMessageArgs::default().content(instructions).build()?.into(),
+                ChatCompletionRequestUserMessageArgs::default().content(text).build()?.into(),
+            ])
+            .build()?;
+        let response = self.client.chat().create(request).await?;
+        let tokens = response.usage.map(|usage| usage.total_tokens as i64).unwrap_or_default();
+        Ok((response.choices.into_iter().next().and_then(|choice| choice.message.content).unwrap_or_default(), tokens))
+    }
+}
+
+#[async_trait]
+impl GameCallClassifier for OpenAi {
+    async fn classify(&self, message: &str, games: &[String]) -> anyhow::Result<Classification> {
+        let instructions = format!(
+            "You read messages from a Discord server where people get together to play games. If the message is someone \
+             looking for people to play with now or soon, answer with the game they want to play from this list: {}. \
+             Answer ANY if they didn't name one of them, and NO if the message isn't someone looking for players. Answer \
+             with nothing else.",
+            games.join(", ")
+        );
+        let (answer, tokens) = self.complete(&instructions, message, MAX_CLASSIFY_TOKENS, 0.0).await?;
+        Ok(Classification { game_call: parse_game_call(&answer, games), tokens })
+    }
+}
+
+#[async_trait]
+impl InvitationWriter for OpenAi {
+    async fn write(&self, request: &InvitationRequest<'_>) -> anyhow::Result<Invitation> {
+        let theme = request.theme.map(|theme| format!(" Write it in the voice of {theme}.")).unwrap_or_default();
+        let instructions = format!(
+            "You write the Discord direct message that invites someone to join a game. Write a short, friendly \
+             invitation of one to three sentences in {}, with the game, when it starts and who is in so far. Don't make \
+             anything up, keep text between < and > exactly as it is and don't add mentions or links.{theme}",
+            request.locale.name()
+        );
+        let mut details = vec![format!("Organizer: {}", request.organizer)];
+        if let Some(game) = request.game {
+            details.push(format!("Game: {game}"));
+        }
+        if let Some(when) = request.when {
+            details.push(format!("Starts: {when}"));
+        }
+        let roster = request.target_size.map(|target_size| format!(" ({}/{target_size})", request.roster.len())).unwrap_or_default();
+        details.push(format!("In so far{roster}: {}", request.roster.join(", ")));
+        if !request.description.is_empty() {
+            details.push(format!("The organizer wrote: {}", request.description));
+        }
+        let (text, tokens) = self.complete(&instructions, &details.join("\n"), MAX_INVITATION_TOKENS, 0.7).await?;
+        Ok(Invitation { text, tokens })
+    }
+}
+
+/// Anything the model answers that isn't ANY or one of the games counts as no, an offer nobody asked for is worse
+/// than a missed one
+pub(crate) fn parse_game_call(answer: &str, games: &[String]) -> Option<GameCall> {
+    let answer = answer.trim().trim_end_matches('.').trim();
+    if answer.eq_ignore_ascii_case("any") {
+        return Some(GameCall { game: None });
+    }
+    games.iter().find(|game| game.eq_ignore_ascii_case(answer)).map(|game| GameCall { game: Some(game.clone()) })
+}
@@ -0,0 +1,183 @@
+use chrono::{DateTime, Duration, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use crate::constants;
+
+/// Parses durations like `90s`, `15m`, `2h`, `1d` or combinations like `1h30m`. A bare number is taken as minutes.
+/// Anything longer than `MAX_DURATION_SECONDS` is rejected.
+pub(crate) fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().to_lowercase();
+    let allowed = |duration: &Duration| *duration >= Duration::zero() && duration.num_seconds() <= constants::MAX_DURATION_SECONDS;
+    if let Ok(minutes) = text.parse::<i64>() {
+        return Duration::try_minutes(minutes).filter(allowed);
+    }
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let amount = number.parse::<i64>().ok()?;
+        number.clear();
+        total = total.checked_add(&match c {
+            's' => Duration::try_seconds(amount)?,
+            'm' => Duration::try_minutes(amount)?,
+            'h' => Duration::try_hours(amount)?,
+            'd' => Duration::try_days(amount)?,
+            _ => return None
+        })?;
+    }
+    // trailing digits without a unit aren't allowed once units are used, e.g. `1h30`
+    if !number.is_empty() {
+        return None;
+    }
+    Some(total).filter(allowed)
+}
+
+/// Formats a number of seconds the same way `parse_duration` reads them, e.g. `1h30m`
+pub(crate) fn format_duration(seconds: i64) -> String {
+    if seconds <= 0 {
+        return "0s".to_string();
+    }
+    let mut remaining = seconds;
+    let mut formatted = String::new();
+    for (unit, unit_seconds) in [("d", 60 * 60 * 24), ("h", 60 * 60), ("m", 60), ("s", 1)] {
+        if remaining >= unit_seconds {
+            formatted.push_str(&format!("{}{}", remaining / unit_seconds, unit));
+            remaining %= unit_seconds;
+        }
+    }
+    formatted
+}
+
+/// Parses a time of day like `23:00`, `8:30`, `9pm` or `9:30pm`
+pub(crate) fn parse_time_of_day(text: &str) -> Option<NaiveTime> {
+    let text = text.trim().to_lowercase();
+    let (text, pm) = if let Some(text) = text.strip_suffix("pm") {
+        (text.trim().to_string(), Some(true))
+    }
+    else if let Some(text) = text.strip_suffix("am") {
+        (text.trim().to_string(), Some(false))
+    }
+    else {
+        (text, None)
+    };
+    let (hours, minutes) = match text.split_once(':') {
+        Some((hours, minutes)) => (hours.parse::<u32>().ok()?, minutes.parse::<u32>().ok()?),
+        // a bare hour is only allowed with am/pm so `9` isn't mistaken for a duration or a date
+        None if pm.is_some() => (text.parse::<u32>().ok()?, 0),
+        None => return None
+    };
+    let hours = match pm {
+        Some(_) if !(1..=12).contains(&hours) => return None,
+        Some(true) => hours % 12 + 12,
+        Some(false) => hours % 12,
+        None => hours
+    };
+    NaiveTime::from_hms_opt(hours, minutes, 0)
+}
+
+pub(crate) fn minutes_after_midnight(time: NaiveTime) -> i32 {
+    (time.hour() * 60 + time.minute()) as i32
+}
+
+pub(crate) fn format_minutes_after_midnight(minutes: i32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Parses a window like `23:00-08:00`
+pub(crate) fn parse_time_window(text: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = text.split_once('-')?;
+    Some((parse_time_of_day(start)?, parse_time_of_day(end)?))
+}
+
+pub(crate) fn parse_timezone(text: &str) -> Option<Tz> {
+    text.trim().parse::<Tz>().ok()
+}
+
+/// A channel mention like `<#123>`, or the bare id
+pub(crate) fn parse_channel_mention(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let id = text.strip_prefix("<#").and_then(|text| text.strip_suffix('>')).unwrap_or(text);
+    id.parse::<u64>().ok().filter(|id| *id != 0)
+}
+
+pub(crate) fn split_first_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.split_once(char::is_whitespace) {
+        Some((first, rest)) => (first, rest.trim_start()),
+        None => (text, "")
+    }
+}
+
+/// Splits text into messages of at most `max_chars` characters, between lines when it can
+pub(crate) fn split_message(text: &str, max_chars: usize) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    for line in text.lines() {
+        let line_chars = line.chars().count();
+        if current_chars > 0 && current_chars + 1 + line_chars > max_chars {
+            messages.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        // a line that doesn't fit in a message on its own is cut wherever the limit falls
+        let mut line = line;
+        while line.chars().count() > max_chars {
+            let cut = line.char_indices().nth(max_chars).map(|(index, _)| index).unwrap_or(line.len());
+            messages.push(line[..cut].to_string());
+            line = &line[cut..];
+        }
+        if current_chars > 0 {
+            current.push('\n');
+            current_chars += 1;
+        }
+        current.push_str(line);
+        current_chars += line.chars().count();
+    }
+    if !current.trim().is_empty() {
+        messages.push(current);
+    }
+    messages
+}
+
+/// Parses when something happens from the start of `text` and returns it along with the rest of the text. Understands
+/// `in 2h`, `tonight 9pm`, `today 18:30`, `tomorrow 8pm`, a bare time like `9pm` (the next time it comes around) and
+/// ISO timestamps like `2026-10-20T21:00`. Anything without an explicit offset is in `timezone`.
+pub(crate) fn parse_when(text: &str, now: DateTime<Utc>, timezone: Tz) -> Option<(DateTime<Utc>, &str)> {
+    let (first, rest) = split_first_word(text);
+    let local_to_utc = |local: NaiveDateTime| timezone.from_local_datetime(&local).earliest().map(|local| local.with_timezone(&Utc));
+    let today = now.with_timezone(&timezone).date_naive();
+    match first.to_lowercase().as_str() {
+        "in" => {
+            let (duration_text, rest) = split_first_word(rest);
+            Some((now.checked_add_signed(parse_duration(duration_text)?)?, rest))
+        }
+        day @ ("today" | "tonight" | "tomorrow") => {
+            let (time_text, rest) = split_first_word(rest);
+            let mut time = parse_time_of_day(time_text)?;
+            // "tonight 9:00" means 21:00
+            if day == "tonight" && time.hour() < 12 {
+                time += Duration::hours(12);
+            }
+            let date = if day == "tomorrow" { today.succ_opt()? } else { today };
+            Some((local_to_utc(date.and_time(time))?, rest))
+        }
+        _ => {
+            if let Ok(timestamp) = DateTime::parse_from_rfc3339(first) {
+                return Some((timestamp.with_timezone(&Utc), rest));
+            }
+            for format in ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"] {
+                if let Ok(local) = NaiveDateTime::parse_from_str(first, format) {
+                    return Some((local_to_utc(local)?, rest));
+                }
+            }
+            let time = parse_time_of_day(first)?;
+            let mut when = local_to_utc(today.and_time(time))?;
+            if when <= now {
+                when = local_to_utc(today.succ_opt()?.and_time(time))?;
+            }
+            Some((when, rest))
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use serenity::all::{GuildId, RoleId, UserId};
+use serenity::prelude::*;
+use tracing::error;
+use crate::chat::ChatBackend;
+use crate::errors::WololoError;
+use crate::store::Store;
+
+/// Who gets admin rights without being in the admins table, read from the secrets when the bot starts
+#[derive(Default)]
+pub(crate) struct Permissions {
+    pub(crate) owner_discord_id: Option<u64>,
+    // role names (case insensitive) or role ids, everyone with one of them is an admin
+    pub(crate) admin_roles: Vec<String>,
+}
+
+impl TypeMapKey for Permissions {
+    type Value = Arc<Permissions>;
+}
+
+impl Permissions {
+    /// `admin_roles` is a comma separated list, e.g. "Moderator, 123456789012345678"
+    pub(crate) fn new(owner_discord_id: Option<u64>, admin_roles: Option<&str>) -> Self {
+        Permissions {
+            owner_discord_id,
+            admin_roles: admin_roles.unwrap_or("")
+                .split(',')
+                .map(str::trim)
+                .filter(|role| !role.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// Ordered from least to most powerful, anyone can only remove rights from people below them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum AccessLevel {
+    Member,
+    // has one of the configured admin roles in the guild, only discord can take that away
+    RoleAdmin,
+    Admin,
+    Owner,
+}
+
+impl AccessLevel {
+    pub(crate) fn is_admin(self) -> bool {
+        self != AccessLevel::Member
+    }
+}
+
+pub(crate) async fn get_permissions(chat: &dyn ChatBackend) -> Arc<Permissions> {
+    chat.data().read().await.get::<Permissions>().cloned().unwrap_or_default()
+}
+
+/// Admins are only admins in the guild they were added in, outside of a guild only the owner has any rights.
+/// `member_roles` should be passed when they are already known, otherwise they are fetched if any admin roles are
+/// configured
+pub(crate) async fn get_access_level(chat: &dyn ChatBackend, database: &dyn Store, guild_id: Option<GuildId>, user_id: UserId, member_roles: Option<&[RoleId]>) -> Result<AccessLevel, WololoError> {
+    let permissions = get_permissions(chat).await;
+    if permissions.owner_discord_id == Some(user_id.get()) {
+        return Ok(AccessLevel::Owner);
+    }
+    let Some(guild_id) = guild_id else {
+        return Ok(AccessLevel::Member);
+    };
+    if database.is_user_admin(guild_id.get(), user_id.get()).await? {
+        return Ok(AccessLevel::Admin);
+    }
+    if !permissions.admin_roles.is_empty() && has_admin_role(chat, &permissions, guild_id, user_id, member_roles).await {
+        return Ok(AccessLevel::RoleAdmin);
+    }
+    Ok(AccessLevel::Member)
+}
+
+async fn has_admin_role(chat: &dyn ChatBackend, permissions: &Permissions, guild_id: GuildId, user_id: UserId, member_roles: Option<&[RoleId]>) -> bool {
+    let member_roles = match member_roles {
+        Some(member_roles) => member_roles.to_vec(),
+        None => match chat.member_roles(guild_id, user_id).await {
+            Ok(member_roles) => member_roles,
+            Err(e) => {
+                error!("Unable to get member {} of guild {}: {:?}", user_id.get(), guild_id.get(), e);
+                return false;
+            }
+        }
+    };
+    let guild_roles = match chat.role_names(guild_id).await {
+        Ok(guild_roles) => guild_roles,
+        Err(e) => {
+            error!("Unable to get roles of guild {}: {:?}", guild_id.get(), e);
+            return false;
+        }
+    };
+    member_roles.iter().any(|role_id| permissions.admin_roles.iter().any(|admin_role| {
+        *admin_role == role_id.get().to_string() || guild_roles.get(role_id).is_some_and(|name| name.eq_ignore_ascii_case(admin_role))
+    }))
+}
@@ -1,128 +1,349 @@
-use chrono::{TimeZone, Utc};
-use sqlx::{Error, Row};
-use tracing::error;
-use crate::structs::{ParentMessageChildMessage, Ping};
+use chrono::{DateTime, Utc};
+use crate::errors::WololoError;
+use crate::structs::{AdminUser, AuditEntry, ChannelSettings, Game, GuildSettings, Lobby, LobbyMember, NotificationDm, NotificationRecord, ParentMessageChildMessage, Ping, ScheduledGame, ScheduledGameRsvp, UserSettings};
 use crate::structs::WololoUser;
 
-pub(crate) async fn get_user(pool: &sqlx::PgPool, discord_id: u64) -> Option<WololoUser> {
-    let result = sqlx::query(
+pub(crate) async fn get_user(pool: &sqlx::PgPool, discord_id: u64) -> Result<WololoUser, WololoError> {
+    Ok(sqlx::query_as!(
+        WololoUser,
         "SELECT discord_id, created_at FROM users WHERE discord_id = $1",
+        discord_id as i64,
     )
-        .bind(discord_id as i64)
         .fetch_one(pool)
-        .await;
+        .await?)
+}
 
-    match result {
-        Ok(row) => Some(WololoUser{ discord_id: row.get("discord_id"), created_at: Utc.timestamp_opt(row.get("created_at"), 0).unwrap()}),
-        Err(e) => {
-            error!("Unable to get user {}: {}", discord_id, e);
-            None
-        },
-    }
+pub(crate) async fn get_ping(pool: &sqlx::PgPool, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Ping, WololoError> {
+    Ok(sqlx::query_as!(
+        Ping,
+        "SELECT discord_user_id AS user_discord_id, guild_id, discord_channel_id, game_id, created_at, last_notified FROM ping_list WHERE discord_user_id = $1 AND discord_channel_id = $2 AND game_id IS NOT DISTINCT FROM $3",
+        user_discord_id as i64,
+        discord_channel_id as i64,
+        game_id,
+    )
+        .fetch_one(pool)
+        .await?)
+}
 
+/// Everyone who should hear about a call for `game_id` in the channel: people subscribed to that game and people
+/// subscribed to every game, or everyone in the channel when no game was given. Someone subscribed to both only shows
+/// up once, with their most recent notification
+pub(crate) async fn get_all_pings_except_for_user(pool: &sqlx::PgPool, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Vec<Ping>, WololoError> {
+    Ok(sqlx::query_as!(
+        Ping,
+        "SELECT DISTINCT ON (discord_user_id) discord_user_id AS user_discord_id, guild_id, discord_channel_id, game_id, created_at, last_notified FROM ping_list
+         WHERE discord_user_id != $1 AND discord_channel_id = $2 AND ($3::INTEGER IS NULL OR game_id IS NULL OR game_id = $3)
+         ORDER BY discord_user_id, last_notified DESC NULLS LAST",
+        user_discord_id as i64,
+        discord_channel_id as i64,
+        game_id,
+    )
+        .fetch_all(pool)
+        .await?)
 }
 
-pub(crate) async fn get_ping(pool: &sqlx::PgPool, user_discord_id: u64, discord_channel_id: u64) -> Option<Ping> {
-    let result = sqlx::query(
-        "SELECT discord_user_id, discord_channel_id, created_at, last_notified FROM ping_list WHERE discord_user_id = $1 AND discord_channel_id = $2",
+/// Every channel the user is subscribed to, oldest subscription first
+pub(crate) async fn get_pings_for_user(pool: &sqlx::PgPool, user_discord_id: u64) -> Result<Vec<Ping>, WololoError> {
+    Ok(sqlx::query_as!(
+        Ping,
+        "SELECT discord_user_id AS user_discord_id, guild_id, discord_channel_id, game_id, created_at, last_notified FROM ping_list WHERE discord_user_id = $1 ORDER BY created_at, game_id NULLS FIRST",
+        user_discord_id as i64,
     )
-        .bind(user_discord_id as i64)
-        .bind(discord_channel_id as i64)
-        .fetch_one(pool)
-        .await;
-
-    match result {
-        Ok(row) => Some(
-            Ping {
-                user_discord_id: row.get("discord_user_id"),
-                discord_channel_id: row.get("discord_channel_id"),
-                created_at: Utc.timestamp_opt(row.get("created_at"), 0).unwrap(),
-                last_notified: {
-                    if let Some(last_notified) = row.get("last_notified") {
-                        Some(Utc.timestamp_opt(last_notified, 0).unwrap())
-                    }
-                    else {
-                        None
-                    }
-                }
-            }
-        ),
-        Err(e) => {
-            error!(
-                "Unable to get ping for user {} channel {}: {}",
-                user_discord_id,
-                discord_channel_id,
-                e
-            );
-            None
-        },
-    }
-
-}
-
-pub(crate) async fn get_all_pings_except_for_user(pool: &sqlx::PgPool, user_discord_id: u64, discord_channel_id: u64) -> Vec<Ping> {
-    let result = sqlx::query(
-        "SELECT discord_user_id, discord_channel_id, created_at, last_notified FROM ping_list WHERE discord_user_id != $1 AND discord_channel_id = $2",
-    )
-        .bind(user_discord_id as i64)
-        .bind(discord_channel_id as i64)
         .fetch_all(pool)
-        .await;
-    let mut pings: Vec<Ping> = Vec::new();
-
-    match result {
-        Ok(rows) => {
-            for row in rows {
-                pings.push(
-                    Ping {
-                        user_discord_id: row.get("discord_user_id"),
-                        discord_channel_id: row.get("discord_channel_id"),
-                        created_at: Utc.timestamp_opt(row.get("created_at"), 0).unwrap(),
-                        last_notified: {
-                            if let Some(last_notified) = row.get("last_notified") {
-                                Some(Utc.timestamp_opt(last_notified, 0).unwrap())
-                            }
-                            else {
-                                None
-                            }
-                        }
-                    }
-                )
-            }
-            pings
-        }
-        Err(e) => {
-            error!(
-                "Unable to get ping for user {} channel {}: {}",
-                user_discord_id,
-                discord_channel_id,
-                e
-            );
-            pings
-        },
-    }
-}
-
-pub(crate) async fn is_user_admin(pool: &sqlx::PgPool, discord_id: u64) -> Result<bool, Error> {
-    let _ = sqlx::query(
-        "SELECT discord_user_id FROM admins WHERE discord_user_id = $1",
-    )
-        .bind(discord_id as i64)
+        .await?)
+}
+
+/// The guild's own games and the ones shared by every guild
+pub(crate) async fn get_games(pool: &sqlx::PgPool, guild_id: u64) -> Result<Vec<Game>, WololoError> {
+    Ok(sqlx::query_as!(
+        Game,
+        "SELECT id, guild_id, name, aliases, default_party_size, emoji FROM games WHERE guild_id = $1 OR guild_id IS NULL ORDER BY name",
+        guild_id as i64,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Finds a game by its name or one of its aliases, ignoring case. The guild's own games win over shared ones
+pub(crate) async fn get_game_by_name(pool: &sqlx::PgPool, guild_id: u64, name: &str) -> Result<Game, WololoError> {
+    Ok(sqlx::query_as!(
+        Game,
+        "SELECT id, guild_id, name, aliases, default_party_size, emoji FROM games
+         WHERE (guild_id = $1 OR guild_id IS NULL) AND (name = $2 OR $2 = ANY(aliases)) ORDER BY guild_id NULLS LAST LIMIT 1",
+        guild_id as i64,
+        name.to_lowercase(),
+    )
         .fetch_one(pool)
+        .await?)
+}
+
+pub(crate) async fn is_user_admin(pool: &sqlx::PgPool, guild_id: u64, discord_id: u64) -> Result<bool, WololoError> {
+    let row = sqlx::query!(
+        "SELECT discord_user_id FROM admins WHERE guild_id = $1 AND discord_user_id = $2",
+        guild_id as i64,
+        discord_id as i64,
+    )
+        .fetch_optional(pool)
         .await?;
-    Ok(true)
+    Ok(row.is_some())
 }
 
-pub(crate) async fn get_parent_message_id_for_child_message_id(pool: &sqlx::PgPool, child_id: u64) -> Result<ParentMessageChildMessage, Error> {
-    let row = sqlx::query(
-        "SELECT parent, child, parent_channel_id, child_channel_id from message_children WHERE child=$1",
-    ).bind(child_id as i64)
-        .fetch_one(pool)
+pub(crate) async fn get_admin_users(pool: &sqlx::PgPool, guild_id: u64) -> Result<Vec<AdminUser>, WololoError> {
+    Ok(sqlx::query_as!(
+        AdminUser,
+        "SELECT discord_user_id AS discord_id FROM admins WHERE guild_id = $1 ORDER BY id",
+        guild_id as i64,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+/// With no guild (e.g. for commands used in dms) being blacklisted in any guild counts
+pub(crate) async fn is_user_blacklisted(pool: &sqlx::PgPool, guild_id: Option<u64>, discord_id: u64) -> Result<bool, WololoError> {
+    let row = sqlx::query!(
+        "SELECT discord_user_id FROM blacklisted_users WHERE ($1::BIGINT IS NULL OR guild_id = $1) AND discord_user_id = $2 LIMIT 1",
+        guild_id.map(|guild_id| guild_id as i64),
+        discord_id as i64,
+    )
+        .fetch_optional(pool)
         .await?;
-    Ok( ParentMessageChildMessage {
-        parent: row.get("parent"),
-        parent_channel_id: row.get("parent_channel_id"),
-        child: row.get("child"),
-        child_channel_id: row.get("child_channel_id"),
-    })
-}
\ No newline at end of file
+    Ok(row.is_some())
+}
+
+pub(crate) async fn get_parent_message_id_for_child_message_id(pool: &sqlx::PgPool, child_id: u64) -> Result<ParentMessageChildMessage, WololoError> {
+    Ok(sqlx::query_as!(
+        ParentMessageChildMessage,
+        "SELECT parent, child, parent_channel_id, child_channel_id, guild_id, lobby_id, scheduled_game_id from message_children WHERE child=$1",
+        child_id as i64,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// The most recent notification dm sent in the dm channel, message ids grow over time so the highest child is the newest
+pub(crate) async fn get_latest_child_for_channel(pool: &sqlx::PgPool, child_channel_id: u64) -> Result<ParentMessageChildMessage, WololoError> {
+    Ok(sqlx::query_as!(
+        ParentMessageChildMessage,
+        "SELECT parent, child, parent_channel_id, child_channel_id, guild_id, lobby_id, scheduled_game_id from message_children WHERE child_channel_id=$1 ORDER BY child DESC LIMIT 1",
+        child_channel_id as i64,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+pub(crate) async fn get_lobby(pool: &sqlx::PgPool, lobby_id: i32) -> Result<Lobby, WololoError> {
+    Ok(sqlx::query_as!(
+        Lobby,
+        "SELECT lobbies.id, lobbies.guild_id, organizer_discord_id, discord_channel_id, summary_message_id, target_size, games.name AS \"game_name?\", description, lobbies.created_at, filled_at FROM lobbies
+         LEFT JOIN games ON games.id = lobbies.game_id WHERE lobbies.id = $1",
+        lobby_id,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+pub(crate) async fn get_lobby_members(pool: &sqlx::PgPool, lobby_id: i32) -> Result<Vec<LobbyMember>, WololoError> {
+    Ok(sqlx::query_as!(
+        LobbyMember,
+        "SELECT discord_user_id, joined FROM lobby_members WHERE lobby_id = $1 ORDER BY updated_at, id",
+        lobby_id,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+pub(crate) async fn get_channel_settings(pool: &sqlx::PgPool, discord_channel_id: u64) -> Result<Option<ChannelSettings>, WololoError> {
+    Ok(sqlx::query_as!(
+        ChannelSettings,
+        "SELECT notification_cooldown_seconds FROM channel_settings WHERE discord_channel_id = $1",
+        discord_channel_id as i64,
+    )
+        .fetch_optional(pool)
+        .await?)
+}
+
+pub(crate) async fn get_guild_settings(pool: &sqlx::PgPool, guild_id: u64) -> Result<Option<GuildSettings>, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "SELECT help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds FROM guild_settings WHERE guild_id = $1",
+        guild_id as i64,
+    )
+        .fetch_optional(pool)
+        .await?)
+}
+
+pub(crate) async fn get_user_settings(pool: &sqlx::PgPool, discord_user_id: u64) -> Result<Option<UserSettings>, WololoError> {
+    Ok(sqlx::query_as!(
+        UserSettings,
+        "SELECT min_notification_gap_seconds, timezone, quiet_start_minutes, quiet_end_minutes, locale FROM user_settings WHERE discord_user_id = $1",
+        discord_user_id as i64,
+    )
+        .fetch_optional(pool)
+        .await?)
+}
+
+/// The guild's `limit` most recent audit log entries, newest first
+pub(crate) async fn get_audit_entries(pool: &sqlx::PgPool, guild_id: u64, limit: i64) -> Result<Vec<AuditEntry>, WololoError> {
+    Ok(sqlx::query_as!(
+        AuditEntry,
+        "SELECT guild_id, discord_channel_id, actor_discord_id, action, target_discord_id, details, created_at FROM audit_log
+         WHERE guild_id = $1 ORDER BY created_at DESC, id DESC LIMIT $2",
+        guild_id as i64,
+        limit,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Tokens the language model used for the guild on `day`
+pub(crate) async fn get_language_model_tokens(pool: &sqlx::PgPool, guild_id: u64, day: chrono::NaiveDate) -> Result<i64, WololoError> {
+    Ok(sqlx::query_scalar!(
+        "SELECT COALESCE(SUM(tokens), 0)::BIGINT AS \"tokens!\" FROM language_model_usage WHERE guild_id = $1 AND day = $2",
+        guild_id as i64,
+        day,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// The last time the user was sent a game notification in any channel
+pub(crate) async fn get_last_notified_for_user(pool: &sqlx::PgPool, discord_user_id: u64) -> Result<Option<DateTime<Utc>>, WololoError> {
+    Ok(sqlx::query_scalar!(
+        "SELECT MAX(last_notified) AS last_notified FROM ping_list WHERE discord_user_id = $1",
+        discord_user_id as i64,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// How many notification dms the user was sent since `since`, in any channel
+pub(crate) async fn count_notification_dms_for_user(pool: &sqlx::PgPool, recipient_discord_id: u64, since: DateTime<Utc>) -> Result<i64, WololoError> {
+    Ok(sqlx::query_scalar!(
+        "SELECT COUNT(*) AS \"count!\" FROM notification_dms WHERE recipient_discord_id = $1 AND sent_at > $2",
+        recipient_discord_id as i64,
+        since,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// The guild's notifications since `since`, oldest first
+pub(crate) async fn get_notifications_for_guild(pool: &sqlx::PgPool, guild_id: u64, since: DateTime<Utc>) -> Result<Vec<NotificationRecord>, WololoError> {
+    Ok(sqlx::query_as!(
+        NotificationRecord,
+        "SELECT id, discord_channel_id, organizer_discord_id, created_at FROM notifications WHERE guild_id = $1 AND created_at > $2 ORDER BY created_at, id",
+        guild_id as i64,
+        since,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+/// The dms of the guild's notifications since `since`
+pub(crate) async fn get_notification_dms_for_guild(pool: &sqlx::PgPool, guild_id: u64, since: DateTime<Utc>) -> Result<Vec<NotificationDm>, WololoError> {
+    Ok(sqlx::query_as!(
+        NotificationDm,
+        "SELECT recipient_discord_id, accepted FROM notification_dms
+         INNER JOIN notifications ON notifications.id = notification_dms.notification_id WHERE notifications.guild_id = $1 AND notifications.created_at > $2",
+        guild_id as i64,
+        since,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+/// When the organizer started the stacks and scheduled the games they started in the guild since `since`, oldest first
+pub(crate) async fn get_call_times_for_organizer(pool: &sqlx::PgPool, guild_id: u64, organizer_discord_id: u64, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>, WololoError> {
+    Ok(sqlx::query_scalar!(
+        "SELECT created_at AS \"created_at!\" FROM lobbies WHERE guild_id = $1 AND organizer_discord_id = $2 AND created_at > $3
+         UNION ALL SELECT created_at FROM scheduled_games WHERE guild_id = $1 AND organizer_discord_id = $2 AND created_at > $3
+         ORDER BY 1",
+        guild_id as i64,
+        organizer_discord_id as i64,
+        since,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+pub(crate) async fn get_scheduled_game(pool: &sqlx::PgPool, scheduled_game_id: i32) -> Result<ScheduledGame, WololoError> {
+    Ok(sqlx::query_as!(
+        ScheduledGame,
+        "SELECT scheduled_games.id, scheduled_games.guild_id, organizer_discord_id, discord_channel_id, announcement_message_id, games.name AS \"game_name?\", description, scheduled_for, started_at, cancelled_at FROM scheduled_games
+         LEFT JOIN games ON games.id = scheduled_games.game_id WHERE scheduled_games.id = $1",
+        scheduled_game_id,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// Games in the channel that haven't started and weren't cancelled, soonest first
+pub(crate) async fn get_upcoming_scheduled_games_for_channel(pool: &sqlx::PgPool, discord_channel_id: u64) -> Result<Vec<ScheduledGame>, WololoError> {
+    Ok(sqlx::query_as!(
+        ScheduledGame,
+        "SELECT scheduled_games.id, scheduled_games.guild_id, organizer_discord_id, discord_channel_id, announcement_message_id, games.name AS \"game_name?\", description, scheduled_for, started_at, cancelled_at FROM scheduled_games
+         LEFT JOIN games ON games.id = scheduled_games.game_id WHERE discord_channel_id = $1 AND started_at IS NULL AND cancelled_at IS NULL ORDER BY scheduled_for",
+        discord_channel_id as i64,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Games that should start at or before `before` and haven't been started or cancelled
+pub(crate) async fn get_due_scheduled_games(pool: &sqlx::PgPool, before: DateTime<Utc>) -> Result<Vec<ScheduledGame>, WololoError> {
+    Ok(sqlx::query_as!(
+        ScheduledGame,
+        "SELECT scheduled_games.id, scheduled_games.guild_id, organizer_discord_id, discord_channel_id, announcement_message_id, games.name AS \"game_name?\", description, scheduled_for, started_at, cancelled_at FROM scheduled_games
+         LEFT JOIN games ON games.id = scheduled_games.game_id WHERE scheduled_for <= $1 AND started_at IS NULL AND cancelled_at IS NULL ORDER BY scheduled_for",
+        before,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Same as `get_due_scheduled_games` but only for games nobody has been reminded about yet
+pub(crate) async fn get_unreminded_scheduled_games(pool: &sqlx::PgPool, before: DateTime<Utc>) -> Result<Vec<ScheduledGame>, WololoError> {
+    Ok(sqlx::query_as!(
+        ScheduledGame,
+        "SELECT scheduled_games.id, scheduled_games.guild_id, organizer_discord_id, discord_channel_id, announcement_message_id, games.name AS \"game_name?\", description, scheduled_for, started_at, cancelled_at FROM scheduled_games
+         LEFT JOIN games ON games.id = scheduled_games.game_id WHERE scheduled_for <= $1 AND reminded_at IS NULL AND started_at IS NULL AND cancelled_at IS NULL ORDER BY scheduled_for",
+        before,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+pub(crate) async fn get_rsvps_for_scheduled_game(pool: &sqlx::PgPool, scheduled_game_id: i32) -> Result<Vec<ScheduledGameRsvp>, WololoError> {
+    Ok(sqlx::query_as!(
+        ScheduledGameRsvp,
+        "SELECT discord_user_id, accepted FROM scheduled_game_rsvps WHERE scheduled_game_id = $1 ORDER BY updated_at, id",
+        scheduled_game_id,
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Channels with rows that were created before rows were scoped by guild
+pub(crate) async fn get_channels_without_guild(pool: &sqlx::PgPool) -> Result<Vec<i64>, WololoError> {
+    Ok(sqlx::query_scalar!(
+        "SELECT discord_channel_id AS \"discord_channel_id!\" FROM ping_list WHERE guild_id IS NULL
+         UNION SELECT discord_channel_id FROM lobbies WHERE guild_id IS NULL
+         UNION SELECT discord_channel_id FROM scheduled_games WHERE guild_id IS NULL
+         UNION SELECT discord_channel_id FROM channel_settings WHERE guild_id IS NULL
+         UNION SELECT parent_channel_id FROM message_children WHERE guild_id IS NULL",
+    )
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Every guild the bot has stored anything for
+pub(crate) async fn get_known_guild_ids(pool: &sqlx::PgPool) -> Result<Vec<i64>, WololoError> {
+    Ok(sqlx::query_scalar!(
+        "SELECT guild_id AS \"guild_id!\" FROM ping_list WHERE guild_id IS NOT NULL
+         UNION SELECT guild_id FROM lobbies WHERE guild_id IS NOT NULL
+         UNION SELECT guild_id FROM scheduled_games WHERE guild_id IS NOT NULL
+         UNION SELECT guild_id FROM channel_settings WHERE guild_id IS NOT NULL",
+    )
+        .fetch_all(pool)
+        .await?)
+}
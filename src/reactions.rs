@@ -0,0 +1,92 @@
+use serenity::all::{ChannelId, MessageId, ReactionType, UserId};
+use tracing::{error, info};
+use crate::{lobbies, scheduled_games};
+use crate::chat::ChatBackend;
+use crate::errors::WololoError;
+use crate::store::Store;
+use crate::structs::ParentMessageChildMessage;
+
+/// Most reactions aren't on notification dms, so only actual database errors get logged
+async fn get_notification_for_reaction(database: &dyn Store, message_id: u64) -> Option<ParentMessageChildMessage> {
+    match database.get_parent_message_id_for_child_message_id(message_id).await {
+        Ok(parent_msg_child_msg) => Some(parent_msg_child_msg),
+        Err(WololoError::NotFound) => None,
+        Err(error) => {
+            error!("Unable to look up reacted message {}: {}", message_id, error);
+            None
+        }
+    }
+}
+
+/// Reacting to a notification dm joins (or declines) the stack or scheduled game it was about
+pub(crate) async fn handle_reaction_add(chat: &dyn ChatBackend, database: &dyn Store, message_id: MessageId, reactor_id: Option<UserId>, emoji: &ReactionType) {
+    let Some(parent_msg_child_msg) = get_notification_for_reaction(database, message_id.get()).await else {
+        return;
+    };
+    // the reaction is on a dm, so the guild it counts for is the one the notification came from
+    if let Some(reactor_id) = reactor_id {
+        let guild_id = parent_msg_child_msg.guild_id.map(|guild_id| guild_id as u64);
+        match database.is_user_blacklisted(guild_id, reactor_id.get()).await {
+            Ok(false) => {}
+            Ok(true) => {
+                info!("Ignoring reaction from blacklisted user {}", reactor_id.get());
+                return;
+            }
+            Err(error) => {
+                error!("Unable to check whether user {} is blacklisted, ignoring their reaction: {}", reactor_id.get(), error);
+                return;
+            }
+        }
+    }
+    info!("Received a reaction for a game notification dm: {} in channel: {}", parent_msg_child_msg.child, parent_msg_child_msg.child_channel_id);
+    match (parent_msg_child_msg.lobby_id, parent_msg_child_msg.scheduled_game_id, reactor_id) {
+        (Some(lobby_id), _, Some(reactor_id)) => {
+            let joined = !lobbies::is_decline_emoji(emoji);
+            if let Err(error) = lobbies::update_roster(chat, database, lobby_id, reactor_id.get(), Some(joined)).await {
+                error!("Unable to update roster for lobby {}: {:?}", lobby_id, error);
+            }
+        }
+        (_, Some(scheduled_game_id), Some(reactor_id)) => {
+            let accepted = !lobbies::is_decline_emoji(emoji);
+            if let Err(error) = scheduled_games::update_rsvp(chat, database, scheduled_game_id, reactor_id.get(), Some(accepted)).await {
+                error!("Unable to update rsvp for scheduled game {}: {:?}", scheduled_game_id, error);
+            }
+        }
+        // dms sent before lobbies existed just get the reaction passed along once
+        (_, _, Some(reactor_id)) => if let Ok(parent_msg) = chat.fetch_message(ChannelId::from(parent_msg_child_msg.parent_channel_id as u64), MessageId::from(parent_msg_child_msg.parent as u64)).await {
+            if let Ok(reactor_name) = chat.user_name(reactor_id).await {
+                if let Err(error) = chat.reply(&parent_msg, format!("@{}: {}", reactor_name, emoji)).await {
+                    error!("Error sending reply message: {:?}", error);
+                }
+                else {
+                    // don't allow further reactions to cause replies
+                    if let Err(error) = database.delete_child_for_message(parent_msg_child_msg).await {
+                        error!("Unable to delete message_children_row: {:?}", error);
+                    }
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Taking the reaction back means they haven't answered anymore
+pub(crate) async fn handle_reaction_remove(chat: &dyn ChatBackend, database: &dyn Store, message_id: MessageId, reactor_id: Option<UserId>) {
+    let Some(reactor_id) = reactor_id else {
+        return;
+    };
+    if let Some(parent_msg_child_msg) = get_notification_for_reaction(database, message_id.get()).await {
+        if let Some(lobby_id) = parent_msg_child_msg.lobby_id {
+            info!("Received a removed reaction for a game notification dm: {} in channel: {}", parent_msg_child_msg.child, parent_msg_child_msg.child_channel_id);
+            if let Err(error) = lobbies::update_roster(chat, database, lobby_id, reactor_id.get(), None).await {
+                error!("Unable to update roster for lobby {}: {:?}", lobby_id, error);
+            }
+        }
+        else if let Some(scheduled_game_id) = parent_msg_child_msg.scheduled_game_id {
+            info!("Received a removed reaction for a scheduled game dm: {} in channel: {}", parent_msg_child_msg.child, parent_msg_child_msg.child_channel_id);
+            if let Err(error) = scheduled_games::update_rsvp(chat, database, scheduled_game_id, reactor_id.get(), None).await {
+                error!("Unable to update rsvp for scheduled game {}: {:?}", scheduled_game_id, error);
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serenity::all::{ButtonStyle, ChannelId, ComponentInteraction, CreateActionRow, CreateAllowedMentions, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage, GuildId, Message, MessageId, UserId};
+use tracing::{error, info};
+use crate::chat::ChatBackend;
+use crate::commands::get_or_create_user;
+use crate::errors::WololoError;
+use crate::messages::{get_guild_locale, get_locale, Messages, Roster};
+use crate::notifications;
+use crate::store::Store;
+use crate::structs::{ScheduledGame, ScheduledGameRsvp};
+
+pub(crate) const BUTTON_PREFIX: &str = "schedule-";
+const ACCEPT_BUTTON_PREFIX: &str = "schedule-accept:";
+const DECLINE_BUTTON_PREFIX: &str = "schedule-decline:";
+
+// how often the scheduler looks for games to remind people about or start
+const SCHEDULER_INTERVAL_SECONDS: u64 = 30;
+const REMINDER_MINUTES: i64 = 15;
+// games that start this late (e.g. because the bot was down) say when they were supposed to start
+const LATE_START_MINUTES: i64 = 5;
+
+/// Discord renders this in the timezone of whoever is reading it
+pub(crate) fn discord_timestamp(timestamp: DateTime<Utc>) -> String {
+    format!("<t:{0}:F> (<t:{0}:R>)", timestamp.timestamp())
+}
+
+pub(crate) fn rsvp_buttons(messages: &dyn Messages, scheduled_game_id: i32) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{ACCEPT_BUTTON_PREFIX}{scheduled_game_id}")).label(messages.coming_button()).style(ButtonStyle::Success),
+        CreateButton::new(format!("{DECLINE_BUTTON_PREFIX}{scheduled_game_id}")).label(messages.not_coming_button()).style(ButtonStyle::Secondary),
+    ])]
+}
+
+fn announcement_text(messages: &dyn Messages, scheduled_game: &ScheduledGame, rsvps: &[ScheduledGameRsvp]) -> String {
+    let mentions = |accepted: bool| rsvps.iter()
+        .filter(|rsvp| rsvp.accepted == accepted)
+        .map(|rsvp| format!("<@{}>", rsvp.discord_user_id))
+        .collect::<Vec<_>>();
+    let roster = Roster {
+        organizer: &format!("<@{}>", scheduled_game.organizer_discord_id),
+        game: scheduled_game.game_name.as_deref(),
+        description: &scheduled_game.description,
+        accepted: &mentions(true),
+        declined: &mentions(false)
+    };
+    messages.announcement(&roster, scheduled_game.id, &discord_timestamp(scheduled_game.scheduled_for), scheduled_game.cancelled_at.is_some())
+}
+
+/// The language of everything posted in the game's channel
+async fn get_channel_messages(database: &dyn Store, scheduled_game: &ScheduledGame) -> &'static dyn Messages {
+    get_guild_locale(database, scheduled_game.guild_id.map(|guild_id| guild_id as u64)).await.messages()
+}
+
+/// Posts the announcement for a new game in the guild's language, the mentions in it are only for display so nobody
+/// gets pinged
+pub(crate) async fn post_announcement(chat: &dyn ChatBackend, messages: &dyn Messages, scheduled_game: &ScheduledGame) -> serenity::Result<Message> {
+    let builder = CreateMessage::new()
+        .content(announcement_text(messages, scheduled_game, &[]))
+        .allowed_mentions(CreateAllowedMentions::new())
+        .components(rsvp_buttons(messages, scheduled_game.id));
+    chat.send_message(ChannelId::new(scheduled_game.discord_channel_id as u64), builder).await
+}
+
+/// Buttons are removed once the game started or was cancelled
+pub(crate) async fn refresh_announcement(chat: &dyn ChatBackend, database: &dyn Store, scheduled_game: &ScheduledGame) {
+    let Some(announcement_message_id) = scheduled_game.announcement_message_id else {
+        return;
+    };
+    let rsvps = database.get_rsvps_for_scheduled_game(scheduled_game.id).await.unwrap_or_default();
+    let messages = get_channel_messages(database, scheduled_game).await;
+    let mut builder = EditMessage::new()
+        .content(announcement_text(messages, scheduled_game, &rsvps))
+        .allowed_mentions(CreateAllowedMentions::new());
+    if scheduled_game.started_at.is_some() || scheduled_game.cancelled_at.is_some() {
+        builder = builder.components(Vec::new());
+    }
+    if let Err(e) = chat.edit_message(ChannelId::new(scheduled_game.discord_channel_id as u64), MessageId::new(announcement_message_id as u64), builder).await {
+        error!("Unable to edit announcement message for scheduled game {}: {:?}", scheduled_game.id, e);
+    }
+}
+
+/// Records that a user is coming (`Some(true)`), isn't coming (`Some(false)`) or took back their answer (`None`)
+pub(crate) async fn update_rsvp(chat: &dyn ChatBackend, database: &dyn Store, scheduled_game_id: i32, discord_user_id: u64, accepted: Option<bool>) -> Result<ScheduledGame, WololoError> {
+    match accepted {
+        Some(accepted) => {
+            database.upsert_scheduled_game_rsvp(scheduled_game_id, discord_user_id, accepted).await?;
+            notifications::record_response(database, None, Some(scheduled_game_id), discord_user_id, accepted).await;
+        }
+        None => {
+            database.delete_scheduled_game_rsvp(scheduled_game_id, discord_user_id).await?;
+        }
+    }
+    let scheduled_game = database.get_scheduled_game(scheduled_game_id).await?;
+    refresh_announcement(chat, database, &scheduled_game).await;
+    Ok(scheduled_game)
+}
+
+/// Pings everyone who is coming in the channel, replying to the announcement if there is one
+async fn ping_attendees(chat: &dyn ChatBackend, database: &dyn Store, scheduled_game: &ScheduledGame, content: impl Fn(&dyn Messages) -> String) {
+    let rsvps = database.get_rsvps_for_scheduled_game(scheduled_game.id).await.unwrap_or_default();
+    let mut mentions = vec![format!("<@{}>", scheduled_game.organizer_discord_id)];
+    mentions.extend(rsvps.iter().filter(|rsvp| rsvp.accepted).map(|rsvp| format!("<@{}>", rsvp.discord_user_id)));
+    let channel_id = ChannelId::new(scheduled_game.discord_channel_id as u64);
+    let content = content(get_channel_messages(database, scheduled_game).await);
+    let mut builder = CreateMessage::new().content(format!("{} {}", mentions.join(" "), content));
+    if let Some(announcement_message_id) = scheduled_game.announcement_message_id {
+        builder = builder.reference_message((channel_id, MessageId::new(announcement_message_id as u64)));
+    }
+    if let Err(e) = chat.send_message(channel_id, builder).await {
+        error!("Error sending message: {:?}", e);
+    }
+}
+
+pub(crate) async fn announce_cancellation(chat: &dyn ChatBackend, database: &dyn Store, scheduled_game: &ScheduledGame) {
+    refresh_announcement(chat, database, scheduled_game).await;
+    ping_attendees(chat, database, scheduled_game, |messages| messages.cancelled_ping(scheduled_game.id)).await;
+}
+
+async fn send_reminders(chat: &dyn ChatBackend, database: &dyn Store, now: DateTime<Utc>) -> Result<(), WololoError> {
+    for scheduled_game in database.get_unreminded_scheduled_games(now + chrono::Duration::minutes(REMINDER_MINUTES)).await? {
+        if !database.update_reminded_at_for_scheduled_game(scheduled_game.id).await? {
+            continue;
+        }
+        let channel_name = chat.channel_name(ChannelId::new(scheduled_game.discord_channel_id as u64)).await.unwrap_or_default();
+        let rsvps = database.get_rsvps_for_scheduled_game(scheduled_game.id).await?;
+        for rsvp in rsvps.iter().filter(|rsvp| rsvp.accepted) {
+            let messages = get_locale(database, scheduled_game.guild_id.map(|guild_id| guild_id as u64), rsvp.discord_user_id as u64).await.messages();
+            let builder = CreateMessage::new().content(messages.reminder(&format!("<@{}>", scheduled_game.organizer_discord_id), &channel_name, &discord_timestamp(scheduled_game.scheduled_for)));
+            if let Err(e) = chat.send_dm(UserId::new(rsvp.discord_user_id as u64), builder).await {
+                error!("Error sending reminder dm to user {}: {:?}", rsvp.discord_user_id, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn start_due_games(chat: &dyn ChatBackend, database: &dyn Store, now: DateTime<Utc>) -> Result<(), WololoError> {
+    for scheduled_game in database.get_due_scheduled_games(now).await? {
+        if !database.update_started_at_for_scheduled_game(scheduled_game.id).await? {
+            continue;
+        }
+        info!("Starting scheduled game {}", scheduled_game.id);
+        let late = (now - scheduled_game.scheduled_for).num_minutes() >= LATE_START_MINUTES;
+        ping_attendees(chat, database, &scheduled_game, |messages| {
+            let mut content = messages.game_time(scheduled_game.id);
+            if late {
+                content.push_str(&format!(" {}", messages.started_late(&discord_timestamp(scheduled_game.scheduled_for))));
+            }
+            content
+        }).await;
+        let scheduled_game = database.get_scheduled_game(scheduled_game.id).await?;
+        refresh_announcement(chat, database, &scheduled_game).await;
+    }
+    Ok(())
+}
+
+/// Runs for as long as the bot does. Everything is read back from the database on every tick, so games scheduled
+/// before a restart are still reminded about and started (late, if they were due while the bot was down).
+pub(crate) async fn run_scheduler(chat: Arc<dyn ChatBackend>, database: Arc<dyn Store>) {
+    info!("Starting the game scheduler");
+    loop {
+        let now = chrono::offset::Utc::now();
+        if let Err(error) = send_reminders(chat.as_ref(), database.as_ref(), now).await {
+            error!("Unable to send reminders for scheduled games: {:?}", error);
+        }
+        if let Err(error) = start_due_games(chat.as_ref(), database.as_ref(), now).await {
+            error!("Unable to start scheduled games: {:?}", error);
+        }
+        tokio::time::sleep(Duration::from_secs(SCHEDULER_INTERVAL_SECONDS)).await;
+    }
+}
+
+/// Handles the rsvp buttons on both the announcement and the notification dms
+pub(crate) async fn handle_component(chat: &dyn ChatBackend, interaction: &ComponentInteraction, database: &dyn Store) {
+    let custom_id = interaction.data.custom_id.as_str();
+    let (scheduled_game_id, accepted) = if let Some(scheduled_game_id) = custom_id.strip_prefix(ACCEPT_BUTTON_PREFIX) {
+        (scheduled_game_id, true)
+    }
+    else if let Some(scheduled_game_id) = custom_id.strip_prefix(DECLINE_BUTTON_PREFIX) {
+        (scheduled_game_id, false)
+    }
+    else {
+        return;
+    };
+    let Ok(scheduled_game_id) = scheduled_game_id.parse::<i32>() else {
+        return;
+    };
+    let user_discord_id = interaction.user.id.get();
+    let messages = get_locale(database, interaction.guild_id.map(GuildId::get), user_discord_id).await.messages();
+    let content = match database.get_scheduled_game(scheduled_game_id).await {
+        // the button can be on a dm, so the guild it counts for is the game's
+        Ok(scheduled_game) => match database.is_user_blacklisted(scheduled_game.guild_id.map(|guild_id| guild_id as u64), user_discord_id).await {
+            Ok(true) => {
+                info!("Ignoring rsvp button from blacklisted user {}", user_discord_id);
+                None
+            }
+            Err(error) => {
+                error!("Unable to check whether user {} is blacklisted: {}", user_discord_id, error);
+                Some(messages.database_trouble())
+            }
+            Ok(false) if scheduled_game.organizer_discord_id == user_discord_id as i64 => Some(messages.organizing_game()),
+            Ok(false) if scheduled_game.started_at.is_some() || scheduled_game.cancelled_at.is_some() => Some(messages.game_over()),
+            Ok(false) => {
+                // scheduled_game_rsvps references users, so people who click the button in the channel get registered
+                if let Err(error) = get_or_create_user(database, user_discord_id).await {
+                    error!("Unable to register user {}: {}", user_discord_id, error);
+                    Some(messages.registration_failed())
+                }
+                else if let Err(error) = update_rsvp(chat, database, scheduled_game_id, user_discord_id, Some(accepted)).await {
+                    error!("Unable to update rsvp for scheduled game {}: {:?}", scheduled_game_id, error);
+                    Some(messages.rsvp_failed())
+                }
+                else if accepted {
+                    Some(messages.see_you(&discord_timestamp(scheduled_game.scheduled_for), REMINDER_MINUTES))
+                }
+                else {
+                    Some(messages.told_organizer(&format!("<@{}>", scheduled_game.organizer_discord_id)))
+                }
+            }
+        },
+        Err(WololoError::NotFound) => Some(messages.game_not_found()),
+        Err(error) => {
+            error!("Unable to get scheduled game {}: {}", scheduled_game_id, error);
+            Some(messages.database_trouble())
+        }
+    };
+    let response = match content {
+        Some(content) => CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(content).ephemeral(true)),
+        None => CreateInteractionResponse::Acknowledge
+    };
+    if let Err(e) = chat.respond_to_interaction(interaction.id, &interaction.token, response).await {
+        error!("Error sending interaction response: {:?}", e);
+    }
+}
@@ -0,0 +1,218 @@
+use serenity::all::{AutocompleteChoice, ChannelType, CommandInteraction, CommandOptionType, CreateAutocompleteResponse, CreateCommand, CreateCommandOption, CreateInteractionResponse, GuildId};
+use tracing::error;
+use crate::chat::ChatBackend;
+use crate::{audit, constants};
+use crate::messages::Locale;
+use crate::store::Store;
+
+// every command that can be used as a slash command, in the order they show up in discord
+const SLASH_CMDS: [&str; 22] = [
+    constants::HELP_CMD,
+    constants::REGISTER_CMD,
+    constants::UNREGISTER_CMD,
+    constants::GAME_NOTIFICATION_ON_CMD,
+    constants::GAME_NOTIFICATION_OFF_CMD,
+    constants::MY_SUBSCRIPTIONS_CMD,
+    constants::ANY_GAMERS_CMD,
+    constants::SCHEDULE_CMD,
+    constants::SCHEDULED_CMD,
+    constants::CANCEL_GAME_CMD,
+    constants::STATS_CMD,
+    constants::GAMES_CMD,
+    constants::ADD_ADMINS_CMD,
+    constants::BLACKLIST_CMD,
+    constants::UNBLACKLIST_CMD,
+    constants::CHANNEL_COOLDOWN_CMD,
+    constants::SERVER_SETTINGS_CMD,
+    constants::AUDIT_CMD,
+    constants::NOTIFICATION_GAP_CMD,
+    constants::TIMEZONE_CMD,
+    constants::QUIET_HOURS_CMD,
+    constants::LANGUAGE_CMD,
+];
+
+/// Slash command names are the text commands without the `!` prefix
+pub(crate) fn slash_name(command: &str) -> &str {
+    command.trim_start_matches('!')
+}
+
+/// Whether the text command is one of the bot's, as opposed to something else that starts with `!`
+pub(crate) fn is_command(command: &str) -> bool {
+    SLASH_CMDS.contains(&command)
+}
+
+/// Maps a slash command name back to the text command it stands for
+pub(crate) fn text_command(slash_name: &str) -> Option<&'static str> {
+    SLASH_CMDS.into_iter().find(|command| self::slash_name(command) == slash_name)
+}
+
+// the text commands take the game as their first word, so this option is always moved to the front
+pub(crate) const GAME_OPTION: &str = "game";
+
+fn game_option(description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, GAME_OPTION, description).set_autocomplete(true)
+}
+
+fn user_option(description: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::User, "user", description).required(true)
+}
+
+fn language_option() -> CreateCommandOption {
+    Locale::ALL.into_iter().fold(
+        CreateCommandOption::new(CommandOptionType::String, "language", "The language, or off to go back to the default"),
+        |option, locale| option.add_string_choice(locale.name(), locale.code())
+    ).add_string_choice("off", "off")
+}
+
+pub(crate) fn create_commands() -> Vec<CreateCommand> {
+    SLASH_CMDS.into_iter().map(|command| {
+        let builder = CreateCommand::new(slash_name(command)).dm_permission(false);
+        match command {
+            constants::HELP_CMD => builder
+                .description("Show my commands")
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "command", "Only show help for this command").set_autocomplete(true)),
+            constants::REGISTER_CMD => builder.description("Add yourself to the list of users I interact with"),
+            constants::UNREGISTER_CMD => builder.description("Delete everything I know about you"),
+            constants::GAME_NOTIFICATION_ON_CMD => builder
+                .description("Enable game search notifications in this channel")
+                .add_option(game_option("Only get notifications for this game")),
+            constants::GAME_NOTIFICATION_OFF_CMD => builder
+                .description("Disable game search notifications in this channel")
+                .add_option(game_option("Only stop notifications for this game")),
+            // works anywhere since it isn't about the channel it is used in
+            constants::MY_SUBSCRIPTIONS_CMD => builder
+                .description("List every channel you get game search notifications for")
+                .dm_permission(true),
+            constants::ANY_GAMERS_CMD => builder
+                .description("Send a dm to everyone who enabled game notifications in this channel")
+                .add_option(game_option("Only notify people who want to play this game"))
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "message", "Anything else you want them to know"))
+                .add_option(CreateCommandOption::new(CommandOptionType::Integer, "size", format!("How many people you need including yourself (default is the game's, or {})", constants::DEFAULT_LOBBY_SIZE))
+                    .min_int_value(2)
+                    .max_int_value(constants::MAX_LOBBY_SIZE as u64)),
+            constants::SCHEDULE_CMD => builder
+                .description("Schedule a game for later and dm everyone who enabled game notifications in this channel")
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "when", "e.g. tonight 9pm, tomorrow 18:30 or in 2h").required(true))
+                .add_option(game_option("Only notify people who want to play this game"))
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "message", "Anything else you want them to know")),
+            constants::SCHEDULED_CMD => builder.description("List the games scheduled in this channel"),
+            constants::CANCEL_GAME_CMD => builder
+                .description("Cancel a game you scheduled")
+                .add_option(CreateCommandOption::new(CommandOptionType::Integer, "id", "The game to cancel").required(true).set_autocomplete(true)),
+            constants::STATS_CMD => builder.description("Show who calls and joins the most games in this server and when"),
+            constants::GAMES_CMD => builder
+                .description("List or manage (admin only) the games people can subscribe to")
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "list", "List the games"))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Add a game (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "The name people type, e.g. aoe2").required(true))
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "size", format!("How many people are in a stack (default {})", constants::DEFAULT_LOBBY_SIZE))
+                        .min_int_value(2)
+                        .max_int_value(constants::MAX_LOBBY_SIZE as u64))
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "emoji", "Shown next to the game's name"))
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "aliases", "Other names for the game, e.g. aoe, age")))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a game and everyone's subscriptions to it (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "The game to remove").required(true).set_autocomplete(true))),
+            constants::ADD_ADMINS_CMD => builder
+                .description("Manage the bot's admins (admin only)")
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Add a user as an admin")
+                    .add_sub_option(user_option("The user to make an admin")))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a user as an admin")
+                    .add_sub_option(user_option("The user to remove as an admin")))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "list", "List the admins")),
+            constants::BLACKLIST_CMD => builder
+                .description("Stop a user from using my commands (admin only)")
+                .add_option(user_option("The user to blacklist")),
+            constants::UNBLACKLIST_CMD => builder
+                .description("Remove a user from the blacklist (admin only)")
+                .add_option(user_option("The user to remove from the blacklist")),
+            constants::CHANNEL_COOLDOWN_CMD => builder
+                .description("Show or set (admin only) how often game notifications can be sent in this channel")
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "duration", "e.g. 10m or 1h")),
+            constants::SERVER_SETTINGS_CMD => builder
+                .description("Show or change (admin only) this server's settings")
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "show", "Show the settings"))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "help-message", "Set the text shown above my commands in help (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "text", "The text, or off to remove it").required(true)))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "cooldown", "Set the notification cooldown for channels without their own (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "duration", "e.g. 5m, or off to use the default").required(true)))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "prefix", "Set what my text commands start with in this server (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "prefix", "e.g. ?, or off to go back to !").required(true)))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "language", "Set the language I use in this server (admin only)")
+                    .add_sub_option(language_option().required(true)))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "invitations", "Have the language model write game notifications as personalized invitations (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "value", "on or off").required(true)
+                        .add_string_choice("on", "on")
+                        .add_string_choice("off", "off")))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "theme", "Set whose voice invitations are written in (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "theme", "e.g. an age of empires monk, or off to remove it").required(true)))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "game-calls", "Offer to notify the subscribers when a message looks like someone looking for players (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "value", "on or off").required(true)
+                        .add_string_choice("on", "on")
+                        .add_string_choice("off", "off")))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "mod-log", "Also post every admin action in a channel (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::Channel, "channel", "The channel, leave it out to stop")
+                        .channel_types(vec![ChannelType::Text])))
+                .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "call-limit", "Set how many stacks everyone but admins can start (admin only)")
+                    .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "limit", "How many per how long, e.g. 3 30m, or off to use the default").required(true))),
+            constants::AUDIT_CMD => builder
+                .description("Show the last admin actions in this server (admin only)")
+                .add_option(CreateCommandOption::new(CommandOptionType::Integer, "count", "How many, 10 by default")
+                    .min_int_value(1)
+                    .max_int_value(audit::MAX_AUDIT_ENTRIES as u64)),
+            constants::NOTIFICATION_GAP_CMD => builder
+                .description("Show or set the minimum time between game notifications I send you")
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "duration", "e.g. 1h, or off to get every notification")),
+            constants::TIMEZONE_CMD => builder
+                .description("Show or set your timezone")
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "timezone", "e.g. America/Toronto").set_autocomplete(true)),
+            constants::QUIET_HOURS_CMD => builder
+                .description("Show or set hours when I shouldn't send you game notifications")
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "hours", "e.g. 23:00-08:00, or off"))
+                .add_option(CreateCommandOption::new(CommandOptionType::String, "timezone", "e.g. America/Toronto").set_autocomplete(true)),
+            constants::LANGUAGE_CMD => builder
+                .description("Show or set the language I talk to you in")
+                .add_option(language_option()),
+            _ => unreachable!("{} has no slash command definition", command)
+        }
+    }).collect()
+}
+
+pub(crate) async fn handle_autocomplete(chat: &dyn ChatBackend, interaction: &CommandInteraction, database: &dyn Store) {
+    let Some(focused) = interaction.data.autocomplete() else {
+        return;
+    };
+    let choices = match text_command(&interaction.data.name) {
+        Some(constants::HELP_CMD) => SLASH_CMDS.into_iter()
+            .map(slash_name)
+            .filter(|name| name.starts_with(focused.value.trim_start_matches('!')))
+            .map(|name| AutocompleteChoice::new(name, name))
+            .collect(),
+        // discord only shows 25 choices, so only suggest timezones once they started typing
+        Some(constants::TIMEZONE_CMD | constants::QUIET_HOURS_CMD) if focused.name == "timezone" && !focused.value.is_empty() => chrono_tz::TZ_VARIANTS.iter()
+            .map(|timezone| timezone.name())
+            .filter(|name| name.to_lowercase().contains(&focused.value.to_lowercase()))
+            .take(25)
+            .map(|name| AutocompleteChoice::new(name, name))
+            .collect(),
+        // /games remove names the game to remove
+        Some(_) if focused.name == GAME_OPTION || focused.name == "name" => database.get_games(interaction.guild_id.map(GuildId::get).unwrap_or_default()).await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|game| game.name.starts_with(&focused.value.to_lowercase()) || game.aliases.iter().any(|alias| alias.starts_with(&focused.value.to_lowercase())))
+            .take(25)
+            .map(|game| AutocompleteChoice::new(game.name.clone(), game.name))
+            .collect(),
+        Some(constants::CANCEL_GAME_CMD) => database.get_upcoming_scheduled_games_for_channel(interaction.channel_id.get()).await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|scheduled_game| scheduled_game.id.to_string().starts_with(focused.value))
+            .take(25)
+            .map(|scheduled_game| AutocompleteChoice::new(format!("#{} {} {}", scheduled_game.id, scheduled_game.scheduled_for.format("%Y-%m-%d %H:%M UTC"), scheduled_game.description).chars().take(100).collect::<String>(), scheduled_game.id))
+            .collect(),
+        _ => Vec::new()
+    };
+    let response = CreateInteractionResponse::Autocomplete(CreateAutocompleteResponse::new().set_choices(choices));
+    if let Err(e) = chat.respond_to_interaction(interaction.id, &interaction.token, response).await {
+        error!("Error sending autocomplete response: {:?}", e);
+    }
+}
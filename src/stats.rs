@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use chrono::Timelike;
+use chrono_tz::Tz;
+use crate::structs::{NotificationDm, NotificationRecord};
+
+// how far back `!stats` looks
+pub(crate) const STATS_DAYS: i64 = 30;
+// a reply has to fit in one discord message
+const MAX_ROWS: usize = 5;
+
+/// What `!stats` shows for a guild, worked out from its notification history
+pub(crate) struct GuildStats {
+    // organizer and how many stacks or games they called, most first
+    pub(crate) organizers: Vec<(i64, usize)>,
+    // recipient, how many of their dms they joined and how many they got, most joined first
+    pub(crate) players: Vec<(i64, usize, usize)>,
+    // channel, the hour of the day most calls start at in the caller's timezone and how many calls, busiest channel first
+    pub(crate) busiest_hours: Vec<(i64, u32, usize)>,
+    // how many of the caller's dms they joined and how many they got
+    pub(crate) own_response: (usize, usize),
+}
+
+/// Ties are broken by id so the same history always gives the same stats
+pub(crate) fn compute(notifications: &[NotificationRecord], dms: &[NotificationDm], caller_discord_id: i64, timezone: Tz) -> GuildStats {
+    let mut calls_by_organizer: HashMap<i64, usize> = HashMap::new();
+    let mut calls_by_channel_and_hour: HashMap<i64, [usize; 24]> = HashMap::new();
+    for notification in notifications {
+        *calls_by_organizer.entry(notification.organizer_discord_id).or_default() += 1;
+        let hour = notification.created_at.with_timezone(&timezone).hour();
+        calls_by_channel_and_hour.entry(notification.discord_channel_id).or_insert([0; 24])[hour as usize] += 1;
+    }
+    let mut organizers = calls_by_organizer.into_iter().collect::<Vec<_>>();
+    organizers.sort_by_key(|(organizer, calls)| (usize::MAX - calls, *organizer));
+    organizers.truncate(MAX_ROWS);
+
+    let mut responses_by_recipient: HashMap<i64, (usize, usize)> = HashMap::new();
+    for dm in dms {
+        let (joined, received) = responses_by_recipient.entry(dm.recipient_discord_id).or_default();
+        if dm.accepted == Some(true) {
+            *joined += 1;
+        }
+        *received += 1;
+    }
+    let own_response = responses_by_recipient.get(&caller_discord_id).copied().unwrap_or_default();
+    let mut players = responses_by_recipient.into_iter()
+        .filter(|(_, (joined, _))| *joined > 0)
+        .map(|(recipient, (joined, received))| (recipient, joined, received))
+        .collect::<Vec<_>>();
+    // joining 3 of 3 beats joining 3 of 10
+    players.sort_by_key(|(recipient, joined, received)| (usize::MAX - joined, *received, *recipient));
+    players.truncate(MAX_ROWS);
+
+    let mut busiest_hours = calls_by_channel_and_hour.into_iter()
+        .map(|(channel, calls_by_hour)| {
+            // the earliest of the busiest hours
+            let (hour, calls) = calls_by_hour.iter().enumerate().rev().max_by_key(|(_, calls)| **calls).map(|(hour, calls)| (hour as u32, *calls)).unwrap_or_default();
+            (channel, hour, calls, calls_by_hour.iter().sum::<usize>())
+        })
+        .collect::<Vec<_>>();
+    busiest_hours.sort_by_key(|(channel, _, _, total)| (usize::MAX - total, *channel));
+    busiest_hours.truncate(MAX_ROWS);
+
+    GuildStats {
+        organizers,
+        players,
+        busiest_hours: busiest_hours.into_iter().map(|(channel, hour, calls, _)| (channel, hour, calls)).collect(),
+        own_response
+    }
+}
@@ -0,0 +1,878 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use chrono::{DateTime, NaiveDate, Utc};
+use serenity::async_trait;
+use crate::constants;
+use crate::errors::WololoError;
+use crate::store::Store;
+use crate::structs::{AdminUser, AuditEntry, BlacklistedUser, ChannelSettings, Game, GuildSettings, Lobby, LobbyMember, NotificationDm, NotificationRecord, ParentMessageChildMessage, Ping, ScheduledGame, ScheduledGameRsvp, UserSettings, WololoUser};
+
+// admins and blacklisted users
+struct GuildUserRow {
+    guild_id: Option<i64>,
+    discord_id: i64,
+}
+
+// the game name of the lobby is looked up when it is read, like the join in queries.rs
+struct LobbyRow {
+    lobby: Lobby,
+    game_id: Option<i32>,
+}
+
+struct LobbyMemberRow {
+    id: i64,
+    lobby_id: i32,
+    member: LobbyMember,
+    updated_at: DateTime<Utc>,
+}
+
+struct ChannelSettingsRow {
+    guild_id: Option<i64>,
+    settings: ChannelSettings,
+}
+
+struct NotificationRow {
+    guild_id: i64,
+    notification: NotificationRecord,
+    lobby_id: Option<i32>,
+    scheduled_game_id: Option<i32>,
+}
+
+// only what the hourly cap, the stats and giving back a claimed dm need
+struct NotificationDmRow {
+    notification_id: Option<i32>,
+    discord_channel_id: i64,
+    recipient_discord_id: i64,
+    sent_at: DateTime<Utc>,
+    accepted: Option<bool>,
+}
+
+struct ScheduledGameRow {
+    scheduled_game: ScheduledGame,
+    game_id: Option<i32>,
+    reminded_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+struct RsvpRow {
+    id: i64,
+    scheduled_game_id: i32,
+    rsvp: ScheduledGameRsvp,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct Tables {
+    // serial ids, shared by every table since nothing depends on them being consecutive
+    last_id: i64,
+    users: Vec<WololoUser>,
+    pings: Vec<Ping>,
+    games: Vec<Game>,
+    admins: Vec<GuildUserRow>,
+    blacklisted_users: Vec<GuildUserRow>,
+    message_children: Vec<ParentMessageChildMessage>,
+    lobbies: Vec<LobbyRow>,
+    lobby_members: Vec<LobbyMemberRow>,
+    channel_settings: HashMap<i64, ChannelSettingsRow>,
+    guild_settings: HashMap<i64, GuildSettings>,
+    language_model_usage: HashMap<(i64, NaiveDate), i64>,
+    audit_log: Vec<AuditEntry>,
+    notifications: Vec<NotificationRow>,
+    notification_dms: Vec<NotificationDmRow>,
+    user_settings: HashMap<i64, UserSettings>,
+    scheduled_games: Vec<ScheduledGameRow>,
+    rsvps: Vec<RsvpRow>,
+}
+
+impl Tables {
+    fn next_id(&mut self) -> i64 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    fn game_name(&self, game_id: Option<i32>) -> Option<String> {
+        game_id.and_then(|game_id| self.games.iter().find(|game| game.id == game_id)).map(|game| game.name.clone())
+    }
+
+    fn lobby(&self, row: &LobbyRow) -> Lobby {
+        Lobby { game_name: self.game_name(row.game_id), ..row.lobby.clone() }
+    }
+
+    fn scheduled_game(&self, row: &ScheduledGameRow) -> ScheduledGame {
+        ScheduledGame { game_name: self.game_name(row.game_id), ..row.scheduled_game.clone() }
+    }
+
+    fn scheduled_games_where(&self, condition: impl Fn(&ScheduledGameRow) -> bool) -> Vec<ScheduledGame> {
+        let mut scheduled_games = self.scheduled_games.iter()
+            .filter(|row| row.scheduled_game.started_at.is_none() && row.scheduled_game.cancelled_at.is_none() && condition(row))
+            .map(|row| self.scheduled_game(row))
+            .collect::<Vec<_>>();
+        scheduled_games.sort_by_key(|scheduled_game| scheduled_game.scheduled_for);
+        scheduled_games
+    }
+
+    fn scheduled_game_mut(&mut self, scheduled_game_id: i32) -> Option<&mut ScheduledGameRow> {
+        self.scheduled_games.iter_mut().find(|row| row.scheduled_game.id == scheduled_game_id)
+    }
+
+    fn delete_lobby(&mut self, lobby_id: i32) {
+        self.lobbies.retain(|row| row.lobby.id != lobby_id);
+        self.lobby_members.retain(|row| row.lobby_id != lobby_id);
+        self.message_children.retain(|child| child.lobby_id != Some(lobby_id));
+    }
+
+    fn delete_scheduled_game(&mut self, scheduled_game_id: i32) {
+        self.scheduled_games.retain(|row| row.scheduled_game.id != scheduled_game_id);
+        self.rsvps.retain(|row| row.scheduled_game_id != scheduled_game_id);
+        self.message_children.retain(|child| child.scheduled_game_id != Some(scheduled_game_id));
+    }
+}
+
+/// Keeps everything in memory, nothing survives a restart
+#[derive(Default)]
+pub(crate) struct InMemoryStore {
+    tables: Mutex<Tables>,
+}
+
+impl InMemoryStore {
+    pub(crate) fn new() -> Self {
+        InMemoryStore::default()
+    }
+
+    // a panic while the lock was held can't leave a table half written, every write is a single push, retain or
+    // assignment
+    fn tables(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn conflict(constraint: &str) -> WololoError {
+    WololoError::Conflict(constraint.to_string())
+}
+
+fn is_same_ping(ping: &Ping, user_discord_id: i64, discord_channel_id: i64, game_id: Option<i32>) -> bool {
+    ping.user_discord_id == user_discord_id && ping.discord_channel_id == discord_channel_id && ping.game_id == game_id
+}
+
+#[async_trait]
+impl Store for InMemoryStore {
+    async fn get_user(&self, discord_id: u64) -> Result<WololoUser, WololoError> {
+        self.tables().users.iter().find(|user| user.discord_id == discord_id as i64).cloned().ok_or(WololoError::NotFound)
+    }
+
+    async fn create_user(&self, discord_id: u64) -> Result<WololoUser, WololoError> {
+        let mut tables = self.tables();
+        if tables.users.iter().any(|user| user.discord_id == discord_id as i64) {
+            return Err(conflict("users_discord_id_key"));
+        }
+        let user = WololoUser {
+            discord_id: discord_id as i64,
+            created_at: Utc::now()
+        };
+        tables.users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn delete_user(&self, discord_id: u64, dm_channel_id: Option<u64>) -> Result<bool, WololoError> {
+        let discord_id = discord_id as i64;
+        let mut tables = self.tables();
+        if let Some(dm_channel_id) = dm_channel_id {
+            tables.message_children.retain(|child| child.child_channel_id != dm_channel_id as i64);
+        }
+        let user_count = tables.users.len();
+        tables.users.retain(|user| user.discord_id != discord_id);
+        if tables.users.len() == user_count {
+            return Ok(false);
+        }
+        tables.pings.retain(|ping| ping.user_discord_id != discord_id);
+        tables.admins.retain(|admin| admin.discord_id != discord_id);
+        tables.lobby_members.retain(|row| row.member.discord_user_id != discord_id);
+        tables.rsvps.retain(|row| row.rsvp.discord_user_id != discord_id);
+        tables.user_settings.remove(&discord_id);
+        tables.notification_dms.retain(|row| row.recipient_discord_id != discord_id);
+        let notification_ids = tables.notifications.iter().filter(|row| row.notification.organizer_discord_id == discord_id).map(|row| row.notification.id).collect::<Vec<_>>();
+        tables.notifications.retain(|row| row.notification.organizer_discord_id != discord_id);
+        tables.notification_dms.retain(|row| row.notification_id.is_none_or(|notification_id| !notification_ids.contains(&notification_id)));
+        let lobby_ids = tables.lobbies.iter().filter(|row| row.lobby.organizer_discord_id == discord_id).map(|row| row.lobby.id).collect::<Vec<_>>();
+        for lobby_id in lobby_ids {
+            tables.delete_lobby(lobby_id);
+        }
+        let scheduled_game_ids = tables.scheduled_games.iter().filter(|row| row.scheduled_game.organizer_discord_id == discord_id).map(|row| row.scheduled_game.id).collect::<Vec<_>>();
+        for scheduled_game_id in scheduled_game_ids {
+            tables.delete_scheduled_game(scheduled_game_id);
+        }
+        Ok(true)
+    }
+
+    async fn get_ping(&self, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Ping, WololoError> {
+        self.tables().pings.iter()
+            .find(|ping| is_same_ping(ping, user_discord_id as i64, discord_channel_id as i64, game_id))
+            .cloned()
+            .ok_or(WololoError::NotFound)
+    }
+
+    async fn get_all_pings_except_for_user(&self, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Vec<Ping>, WololoError> {
+        let mut pings_by_user: HashMap<i64, Ping> = HashMap::new();
+        for ping in self.tables().pings.iter() {
+            let wanted = ping.user_discord_id != user_discord_id as i64
+                && ping.discord_channel_id == discord_channel_id as i64
+                && (game_id.is_none() || ping.game_id.is_none() || ping.game_id == game_id);
+            if !wanted {
+                continue;
+            }
+            // None sorts first, so this keeps the most recent notification
+            match pings_by_user.get(&ping.user_discord_id) {
+                Some(kept) if kept.last_notified >= ping.last_notified => {}
+                _ => {
+                    pings_by_user.insert(ping.user_discord_id, ping.clone());
+                }
+            }
+        }
+        let mut pings = pings_by_user.into_values().collect::<Vec<_>>();
+        pings.sort_by_key(|ping| ping.user_discord_id);
+        Ok(pings)
+    }
+
+    async fn get_pings_for_user(&self, user_discord_id: u64) -> Result<Vec<Ping>, WololoError> {
+        let mut pings = self.tables().pings.iter().filter(|ping| ping.user_discord_id == user_discord_id as i64).cloned().collect::<Vec<_>>();
+        pings.sort_by_key(|ping| (ping.created_at, ping.game_id));
+        Ok(pings)
+    }
+
+    async fn get_last_notified_for_user(&self, discord_user_id: u64) -> Result<Option<DateTime<Utc>>, WololoError> {
+        Ok(self.tables().pings.iter().filter(|ping| ping.user_discord_id == discord_user_id as i64).filter_map(|ping| ping.last_notified).max())
+    }
+
+    async fn create_ping(&self, guild_id: u64, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Ping, WololoError> {
+        let mut tables = self.tables();
+        if tables.pings.iter().any(|ping| is_same_ping(ping, user_discord_id as i64, discord_channel_id as i64, game_id)) {
+            return Err(conflict("uq_ping"));
+        }
+        let ping = Ping {
+            user_discord_id: user_discord_id as i64,
+            guild_id: Some(guild_id as i64),
+            discord_channel_id: discord_channel_id as i64,
+            game_id,
+            created_at: Utc::now(),
+            last_notified: None
+        };
+        tables.pings.push(ping.clone());
+        Ok(ping)
+    }
+
+    async fn delete_ping(&self, ping: Ping) -> Result<bool, WololoError> {
+        let mut tables = self.tables();
+        let count = tables.pings.len();
+        tables.pings.retain(|other| !is_same_ping(other, ping.user_discord_id, ping.discord_channel_id, ping.game_id));
+        Ok(tables.pings.len() < count)
+    }
+
+    async fn delete_pings_for_channel(&self, user_discord_id: u64, discord_channel_id: u64) -> Result<u64, WololoError> {
+        let mut tables = self.tables();
+        let count = tables.pings.len();
+        tables.pings.retain(|ping| ping.user_discord_id != user_discord_id as i64 || ping.discord_channel_id != discord_channel_id as i64);
+        Ok((count - tables.pings.len()) as u64)
+    }
+
+    async fn delete_pings_for_user(&self, user_discord_id: u64) -> Result<u64, WololoError> {
+        let mut tables = self.tables();
+        let count = tables.pings.len();
+        tables.pings.retain(|ping| ping.user_discord_id != user_discord_id as i64);
+        Ok((count - tables.pings.len()) as u64)
+    }
+
+    async fn get_notifications_for_guild(&self, guild_id: u64, since: DateTime<Utc>) -> Result<Vec<NotificationRecord>, WololoError> {
+        Ok(self.tables().notifications.iter()
+            .filter(|row| row.guild_id == guild_id as i64 && row.notification.created_at > since)
+            .map(|row| row.notification.clone())
+            .collect())
+    }
+
+    async fn get_notification_dms_for_guild(&self, guild_id: u64, since: DateTime<Utc>) -> Result<Vec<NotificationDm>, WololoError> {
+        let tables = self.tables();
+        let notification_ids = tables.notifications.iter()
+            .filter(|row| row.guild_id == guild_id as i64 && row.notification.created_at > since)
+            .map(|row| row.notification.id)
+            .collect::<Vec<_>>();
+        Ok(tables.notification_dms.iter()
+            .filter(|row| row.notification_id.is_some_and(|notification_id| notification_ids.contains(&notification_id)))
+            .map(|row| NotificationDm { recipient_discord_id: row.recipient_discord_id, accepted: row.accepted })
+            .collect())
+    }
+
+    async fn count_notification_dms_for_user(&self, recipient_discord_id: u64, since: DateTime<Utc>) -> Result<i64, WololoError> {
+        Ok(self.tables().notification_dms.iter().filter(|row| row.recipient_discord_id == recipient_discord_id as i64 && row.sent_at > since).count() as i64)
+    }
+
+    async fn create_notification(&self, guild_id: u64, discord_channel_id: u64, organizer_discord_id: u64, lobby_id: Option<i32>, scheduled_game_id: Option<i32>, _description: &str) -> Result<NotificationRecord, WololoError> {
+        let mut tables = self.tables();
+        let notification = NotificationRecord {
+            id: tables.next_id() as i32,
+            discord_channel_id: discord_channel_id as i64,
+            organizer_discord_id: organizer_discord_id as i64,
+            created_at: Utc::now()
+        };
+        tables.notifications.push(NotificationRow { guild_id: guild_id as i64, notification: notification.clone(), lobby_id, scheduled_game_id });
+        Ok(notification)
+    }
+
+    async fn reserve_notification_dms(&self, _guild_id: u64, discord_channel_id: u64, notification_id: Option<i32>, recipient_discord_ids: &[u64], channel_cooldown_seconds: i64, now: DateTime<Utc>) -> Result<Vec<u64>, WololoError> {
+        // holding the lock for the whole claim is what the row locks do in postgres
+        let mut tables = self.tables();
+        let notified_within = |tables: &Tables, recipient_discord_id: i64, channel_id: Option<i64>, seconds: i64| tables.pings.iter()
+            .filter(|ping| ping.user_discord_id == recipient_discord_id && channel_id.is_none_or(|channel_id| ping.discord_channel_id == channel_id))
+            .filter_map(|ping| ping.last_notified)
+            .any(|last_notified| last_notified > now - chrono::Duration::seconds(seconds));
+        let mut claimed = Vec::new();
+        for recipient_discord_id in recipient_discord_ids.iter().map(|recipient_discord_id| *recipient_discord_id as i64) {
+            let gap = tables.user_settings.get(&recipient_discord_id).and_then(|settings| settings.min_notification_gap_seconds).filter(|gap| *gap > 0);
+            let dms_last_hour = tables.notification_dms.iter().filter(|row| row.recipient_discord_id == recipient_discord_id && row.sent_at > now - chrono::Duration::hours(1)).count() as i64;
+            if !tables.users.iter().any(|user| user.discord_id == recipient_discord_id)
+                || notified_within(&tables, recipient_discord_id, Some(discord_channel_id as i64), channel_cooldown_seconds)
+                || gap.is_some_and(|gap| notified_within(&tables, recipient_discord_id, None, gap))
+                || dms_last_hour >= constants::MAX_DMS_PER_RECIPIENT_PER_HOUR {
+                continue;
+            }
+            for ping in tables.pings.iter_mut().filter(|ping| ping.discord_channel_id == discord_channel_id as i64 && ping.user_discord_id == recipient_discord_id) {
+                ping.last_notified = Some(now);
+            }
+            tables.notification_dms.push(NotificationDmRow { notification_id, discord_channel_id: discord_channel_id as i64, recipient_discord_id, sent_at: now, accepted: None });
+            claimed.push(recipient_discord_id as u64);
+        }
+        Ok(claimed)
+    }
+
+    async fn release_notification_dm(&self, discord_channel_id: u64, recipient_discord_id: u64, reserved_at: DateTime<Utc>, last_notified: Option<DateTime<Utc>>) -> Result<(), WololoError> {
+        let mut tables = self.tables();
+        tables.notification_dms.retain(|row| row.discord_channel_id != discord_channel_id as i64 || row.recipient_discord_id != recipient_discord_id as i64 || row.sent_at != reserved_at);
+        for ping in tables.pings.iter_mut().filter(|ping| ping.discord_channel_id == discord_channel_id as i64 && ping.user_discord_id == recipient_discord_id as i64 && ping.last_notified == Some(reserved_at)) {
+            ping.last_notified = last_notified;
+        }
+        Ok(())
+    }
+
+    async fn update_response_for_notification_dms(&self, lobby_id: Option<i32>, scheduled_game_id: Option<i32>, recipient_discord_id: u64, accepted: bool) -> Result<u64, WololoError> {
+        let mut tables = self.tables();
+        let notification_ids = tables.notifications.iter()
+            .filter(|row| (lobby_id.is_some() && row.lobby_id == lobby_id) || (scheduled_game_id.is_some() && row.scheduled_game_id == scheduled_game_id))
+            .map(|row| row.notification.id)
+            .collect::<Vec<_>>();
+        let mut updated = 0;
+        for row in tables.notification_dms.iter_mut().filter(|row| row.recipient_discord_id == recipient_discord_id as i64 && row.notification_id.is_some_and(|notification_id| notification_ids.contains(&notification_id))) {
+            row.accepted = Some(accepted);
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn get_games(&self, guild_id: u64) -> Result<Vec<Game>, WololoError> {
+        let mut games = self.tables().games.iter().filter(|game| game.guild_id.is_none_or(|game_guild_id| game_guild_id == guild_id as i64)).cloned().collect::<Vec<_>>();
+        games.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(games)
+    }
+
+    async fn get_game_by_name(&self, guild_id: u64, name: &str) -> Result<Game, WololoError> {
+        let name = name.to_lowercase();
+        let tables = self.tables();
+        let mut games = tables.games.iter()
+            .filter(|game| game.guild_id.is_none_or(|game_guild_id| game_guild_id == guild_id as i64))
+            .filter(|game| game.name == name || game.aliases.contains(&name));
+        // the guild's own game wins over a shared one
+        let game = games.clone().find(|game| game.guild_id.is_some()).or_else(|| games.next());
+        game.cloned().ok_or(WololoError::NotFound)
+    }
+
+    async fn create_game(&self, guild_id: u64, name: &str, aliases: &[String], default_party_size: i32, emoji: Option<&str>) -> Result<Game, WololoError> {
+        let mut tables = self.tables();
+        let name = name.to_lowercase();
+        if tables.games.iter().any(|game| game.guild_id == Some(guild_id as i64) && game.name == name) {
+            return Err(conflict("uq_game_name"));
+        }
+        let game = Game {
+            id: tables.next_id() as i32,
+            guild_id: Some(guild_id as i64),
+            name,
+            aliases: aliases.iter().map(|alias| alias.to_lowercase()).collect(),
+            default_party_size,
+            emoji: emoji.map(str::to_string)
+        };
+        tables.games.push(game.clone());
+        Ok(game)
+    }
+
+    async fn delete_game(&self, game_id: i32) -> Result<bool, WololoError> {
+        let mut tables = self.tables();
+        let count = tables.games.len();
+        tables.games.retain(|game| game.id != game_id);
+        if tables.games.len() == count {
+            return Ok(false);
+        }
+        tables.pings.retain(|ping| ping.game_id != Some(game_id));
+        for row in tables.lobbies.iter_mut().filter(|row| row.game_id == Some(game_id)) {
+            row.game_id = None;
+        }
+        for row in tables.scheduled_games.iter_mut().filter(|row| row.game_id == Some(game_id)) {
+            row.game_id = None;
+        }
+        Ok(true)
+    }
+
+    async fn is_user_admin(&self, guild_id: u64, discord_id: u64) -> Result<bool, WololoError> {
+        Ok(self.tables().admins.iter().any(|admin| admin.guild_id == Some(guild_id as i64) && admin.discord_id == discord_id as i64))
+    }
+
+    async fn get_admin_users(&self, guild_id: u64) -> Result<Vec<AdminUser>, WololoError> {
+        Ok(self.tables().admins.iter()
+            .filter(|admin| admin.guild_id == Some(guild_id as i64))
+            .map(|admin| AdminUser { discord_id: admin.discord_id })
+            .collect())
+    }
+
+    async fn create_admin_user(&self, guild_id: u64, discord_id: u64) -> Result<AdminUser, WololoError> {
+        let mut tables = self.tables();
+        if tables.admins.iter().any(|admin| admin.guild_id == Some(guild_id as i64) && admin.discord_id == discord_id as i64) {
+            return Err(conflict("uq_admin"));
+        }
+        tables.admins.push(GuildUserRow { guild_id: Some(guild_id as i64), discord_id: discord_id as i64 });
+        Ok(AdminUser {
+            discord_id: discord_id as i64
+        })
+    }
+
+    async fn delete_admin_user(&self, guild_id: u64, discord_id: u64) -> Result<bool, WololoError> {
+        let mut tables = self.tables();
+        let count = tables.admins.len();
+        tables.admins.retain(|admin| admin.guild_id != Some(guild_id as i64) || admin.discord_id != discord_id as i64);
+        Ok(tables.admins.len() < count)
+    }
+
+    async fn is_user_blacklisted(&self, guild_id: Option<u64>, discord_id: u64) -> Result<bool, WololoError> {
+        Ok(self.tables().blacklisted_users.iter().any(|blacklisted_user| {
+            blacklisted_user.discord_id == discord_id as i64 && guild_id.is_none_or(|guild_id| blacklisted_user.guild_id == Some(guild_id as i64))
+        }))
+    }
+
+    async fn create_blacklisted_user(&self, guild_id: u64, discord_id: u64) -> Result<BlacklistedUser, WololoError> {
+        let mut tables = self.tables();
+        if tables.blacklisted_users.iter().any(|blacklisted_user| blacklisted_user.guild_id == Some(guild_id as i64) && blacklisted_user.discord_id == discord_id as i64) {
+            return Err(conflict("uq_blacklisted_user"));
+        }
+        tables.blacklisted_users.push(GuildUserRow { guild_id: Some(guild_id as i64), discord_id: discord_id as i64 });
+        Ok(BlacklistedUser {
+            discord_id: discord_id as i64
+        })
+    }
+
+    async fn delete_blacklisted_user(&self, guild_id: u64, discord_id: u64) -> Result<bool, WololoError> {
+        let mut tables = self.tables();
+        let count = tables.blacklisted_users.len();
+        tables.blacklisted_users.retain(|blacklisted_user| blacklisted_user.guild_id != Some(guild_id as i64) || blacklisted_user.discord_id != discord_id as i64);
+        Ok(tables.blacklisted_users.len() < count)
+    }
+
+    async fn get_parent_message_id_for_child_message_id(&self, child_id: u64) -> Result<ParentMessageChildMessage, WololoError> {
+        self.tables().message_children.iter().find(|child| child.child == child_id as i64).cloned().ok_or(WololoError::NotFound)
+    }
+
+    async fn get_latest_child_for_channel(&self, child_channel_id: u64) -> Result<ParentMessageChildMessage, WololoError> {
+        self.tables().message_children.iter()
+            .filter(|child| child.child_channel_id == child_channel_id as i64)
+            .max_by_key(|child| child.child)
+            .cloned()
+            .ok_or(WololoError::NotFound)
+    }
+
+    async fn create_child_for_message(&self, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, WololoError> {
+        let mut tables = self.tables();
+        if tables.message_children.iter().any(|child| child.parent == parent_msg_child_msg.parent && child.child == parent_msg_child_msg.child) {
+            return Err(conflict("uq_parent_child"));
+        }
+        tables.message_children.push(parent_msg_child_msg);
+        Ok(true)
+    }
+
+    async fn delete_child_for_message(&self, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, WololoError> {
+        let mut tables = self.tables();
+        let count = tables.message_children.len();
+        tables.message_children.retain(|child| child.parent != parent_msg_child_msg.parent || child.child != parent_msg_child_msg.child);
+        if tables.message_children.len() == count {
+            return Err(WololoError::NotFound);
+        }
+        Ok(true)
+    }
+
+    async fn get_lobby(&self, lobby_id: i32) -> Result<Lobby, WololoError> {
+        let tables = self.tables();
+        tables.lobbies.iter().find(|row| row.lobby.id == lobby_id).map(|row| tables.lobby(row)).ok_or(WololoError::NotFound)
+    }
+
+    async fn get_lobby_members(&self, lobby_id: i32) -> Result<Vec<LobbyMember>, WololoError> {
+        let tables = self.tables();
+        let mut rows = tables.lobby_members.iter().filter(|row| row.lobby_id == lobby_id).collect::<Vec<_>>();
+        rows.sort_by_key(|row| (row.updated_at, row.id));
+        Ok(rows.into_iter().map(|row| row.member.clone()).collect())
+    }
+
+    async fn create_lobby(&self, guild_id: u64, organizer_discord_id: u64, discord_channel_id: u64, target_size: i32, game: Option<&Game>, description: &str) -> Result<Lobby, WololoError> {
+        let mut tables = self.tables();
+        let lobby = Lobby {
+            id: tables.next_id() as i32,
+            guild_id: Some(guild_id as i64),
+            organizer_discord_id: organizer_discord_id as i64,
+            discord_channel_id: discord_channel_id as i64,
+            summary_message_id: None,
+            target_size,
+            game_name: game.map(|game| game.name.clone()),
+            description: description.to_string(),
+            created_at: Utc::now(),
+            filled_at: None
+        };
+        tables.lobbies.push(LobbyRow { lobby: lobby.clone(), game_id: game.map(|game| game.id) });
+        Ok(lobby)
+    }
+
+    async fn get_call_times_for_organizer(&self, guild_id: u64, organizer_discord_id: u64, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>, WololoError> {
+        let tables = self.tables();
+        let lobbies = tables.lobbies.iter()
+            .map(|row| &row.lobby)
+            .filter(|lobby| lobby.guild_id == Some(guild_id as i64) && lobby.organizer_discord_id == organizer_discord_id as i64)
+            .map(|lobby| lobby.created_at);
+        let scheduled_games = tables.scheduled_games.iter()
+            .filter(|row| row.scheduled_game.guild_id == Some(guild_id as i64) && row.scheduled_game.organizer_discord_id == organizer_discord_id as i64)
+            .map(|row| row.created_at);
+        let mut times = lobbies.chain(scheduled_games).filter(|created_at| *created_at > since).collect::<Vec<_>>();
+        times.sort();
+        Ok(times)
+    }
+
+    async fn update_summary_message_for_lobby(&self, lobby_id: i32, summary_message_id: u64) -> Result<bool, WololoError> {
+        if let Some(row) = self.tables().lobbies.iter_mut().find(|row| row.lobby.id == lobby_id) {
+            row.lobby.summary_message_id = Some(summary_message_id as i64);
+        }
+        Ok(true)
+    }
+
+    async fn update_filled_at_for_lobby(&self, lobby_id: i32) -> Result<bool, WololoError> {
+        match self.tables().lobbies.iter_mut().find(|row| row.lobby.id == lobby_id && row.lobby.filled_at.is_none()) {
+            Some(row) => {
+                row.lobby.filled_at = Some(Utc::now());
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
+    async fn upsert_lobby_member(&self, lobby_id: i32, discord_user_id: u64, joined: bool) -> Result<LobbyMember, WololoError> {
+        let member = LobbyMember {
+            discord_user_id: discord_user_id as i64,
+            joined,
+        };
+        let mut tables = self.tables();
+        match tables.lobby_members.iter_mut().find(|row| row.lobby_id == lobby_id && row.member.discord_user_id == discord_user_id as i64) {
+            Some(row) => {
+                row.member = member.clone();
+                row.updated_at = Utc::now();
+            }
+            None => {
+                let id = tables.next_id();
+                tables.lobby_members.push(LobbyMemberRow { id, lobby_id, member: member.clone(), updated_at: Utc::now() });
+            }
+        }
+        Ok(member)
+    }
+
+    async fn delete_lobby_member(&self, lobby_id: i32, discord_user_id: u64) -> Result<bool, WololoError> {
+        let mut tables = self.tables();
+        let count = tables.lobby_members.len();
+        tables.lobby_members.retain(|row| row.lobby_id != lobby_id || row.member.discord_user_id != discord_user_id as i64);
+        Ok(tables.lobby_members.len() < count)
+    }
+
+    async fn create_audit_entry(&self, guild_id: u64, discord_channel_id: Option<u64>, actor_discord_id: u64, action: &str, target_discord_id: Option<u64>, details: Option<&str>) -> Result<AuditEntry, WololoError> {
+        let mut tables = self.tables();
+        let entry = AuditEntry {
+            guild_id: guild_id as i64,
+            discord_channel_id: discord_channel_id.map(|discord_channel_id| discord_channel_id as i64),
+            actor_discord_id: actor_discord_id as i64,
+            action: action.to_string(),
+            target_discord_id: target_discord_id.map(|target_discord_id| target_discord_id as i64),
+            details: details.map(str::to_string),
+            created_at: Utc::now()
+        };
+        tables.audit_log.push(entry.clone());
+        Ok(entry)
+    }
+
+    async fn get_audit_entries(&self, guild_id: u64, limit: i64) -> Result<Vec<AuditEntry>, WololoError> {
+        Ok(self.tables().audit_log.iter()
+            .rev()
+            .filter(|entry| entry.guild_id == guild_id as i64)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_language_model_tokens(&self, guild_id: u64, day: NaiveDate) -> Result<i64, WololoError> {
+        Ok(self.tables().language_model_usage.get(&(guild_id as i64, day)).copied().unwrap_or_default())
+    }
+
+    async fn add_language_model_tokens(&self, guild_id: u64, day: NaiveDate, tokens: i64) -> Result<i64, WololoError> {
+        let mut tables = self.tables();
+        let used = tables.language_model_usage.entry((guild_id as i64, day)).or_default();
+        *used += tokens;
+        Ok(*used)
+    }
+
+    async fn get_channel_settings(&self, discord_channel_id: u64) -> Result<Option<ChannelSettings>, WololoError> {
+        Ok(self.tables().channel_settings.get(&(discord_channel_id as i64)).map(|row| row.settings.clone()))
+    }
+
+    async fn get_guild_settings(&self, guild_id: u64) -> Result<Option<GuildSettings>, WololoError> {
+        Ok(self.tables().guild_settings.get(&(guild_id as i64)).cloned())
+    }
+
+    async fn get_user_settings(&self, discord_user_id: u64) -> Result<Option<UserSettings>, WololoError> {
+        Ok(self.tables().user_settings.get(&(discord_user_id as i64)).cloned())
+    }
+
+    async fn upsert_notification_cooldown_for_channel(&self, guild_id: u64, discord_channel_id: u64, notification_cooldown_seconds: i64) -> Result<ChannelSettings, WololoError> {
+        let settings = ChannelSettings {
+            notification_cooldown_seconds
+        };
+        let mut tables = self.tables();
+        match tables.channel_settings.get_mut(&(discord_channel_id as i64)) {
+            // like the ON CONFLICT in writes.rs, only the cooldown changes
+            Some(row) => row.settings = settings.clone(),
+            None => {
+                tables.channel_settings.insert(discord_channel_id as i64, ChannelSettingsRow { guild_id: Some(guild_id as i64), settings: settings.clone() });
+            }
+        }
+        Ok(settings)
+    }
+
+    async fn upsert_help_text_for_guild(&self, guild_id: u64, help_text: Option<&str>) -> Result<GuildSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.guild_settings.entry(guild_id as i64).or_default();
+        settings.help_text = help_text.map(str::to_string);
+        Ok(settings.clone())
+    }
+
+    async fn upsert_notification_cooldown_for_guild(&self, guild_id: u64, notification_cooldown_seconds: Option<i64>) -> Result<GuildSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.guild_settings.entry(guild_id as i64).or_default();
+        settings.notification_cooldown_seconds = notification_cooldown_seconds;
+        Ok(settings.clone())
+    }
+
+    async fn upsert_command_prefix_for_guild(&self, guild_id: u64, command_prefix: Option<&str>) -> Result<GuildSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.guild_settings.entry(guild_id as i64).or_default();
+        settings.command_prefix = command_prefix.map(str::to_string);
+        Ok(settings.clone())
+    }
+
+    async fn upsert_locale_for_guild(&self, guild_id: u64, locale: Option<&str>) -> Result<GuildSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.guild_settings.entry(guild_id as i64).or_default();
+        settings.locale = locale.map(str::to_string);
+        Ok(settings.clone())
+    }
+
+    async fn upsert_personalized_invitations_for_guild(&self, guild_id: u64, personalized_invitations: bool) -> Result<GuildSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.guild_settings.entry(guild_id as i64).or_default();
+        settings.personalized_invitations = personalized_invitations;
+        Ok(settings.clone())
+    }
+
+    async fn upsert_game_call_offers_for_guild(&self, guild_id: u64, game_call_offers: bool) -> Result<GuildSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.guild_settings.entry(guild_id as i64).or_default();
+        settings.game_call_offers = game_call_offers;
+        Ok(settings.clone())
+    }
+
+    async fn upsert_invitation_theme_for_guild(&self, guild_id: u64, invitation_theme: Option<&str>) -> Result<GuildSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.guild_settings.entry(guild_id as i64).or_default();
+        settings.invitation_theme = invitation_theme.map(str::to_string);
+        Ok(settings.clone())
+    }
+
+    async fn upsert_call_limit_for_guild(&self, guild_id: u64, call_limit: Option<(i32, i64)>) -> Result<GuildSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.guild_settings.entry(guild_id as i64).or_default();
+        settings.call_limit = call_limit.map(|(limit, _)| limit);
+        settings.call_window_seconds = call_limit.map(|(_, window_seconds)| window_seconds);
+        Ok(settings.clone())
+    }
+
+    async fn upsert_mod_log_channel_for_guild(&self, guild_id: u64, mod_log_channel_id: Option<u64>) -> Result<GuildSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.guild_settings.entry(guild_id as i64).or_default();
+        settings.mod_log_channel_id = mod_log_channel_id.map(|mod_log_channel_id| mod_log_channel_id as i64);
+        Ok(settings.clone())
+    }
+
+    async fn upsert_notification_gap_for_user(&self, discord_user_id: u64, min_notification_gap_seconds: Option<i64>) -> Result<UserSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.user_settings.entry(discord_user_id as i64).or_default();
+        settings.min_notification_gap_seconds = min_notification_gap_seconds;
+        Ok(settings.clone())
+    }
+
+    async fn upsert_timezone_for_user(&self, discord_user_id: u64, timezone: &str) -> Result<UserSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.user_settings.entry(discord_user_id as i64).or_default();
+        settings.timezone = Some(timezone.to_string());
+        Ok(settings.clone())
+    }
+
+    async fn upsert_locale_for_user(&self, discord_user_id: u64, locale: Option<&str>) -> Result<UserSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.user_settings.entry(discord_user_id as i64).or_default();
+        settings.locale = locale.map(str::to_string);
+        Ok(settings.clone())
+    }
+
+    async fn upsert_quiet_hours_for_user(&self, discord_user_id: u64, quiet_minutes: Option<(i32, i32)>) -> Result<UserSettings, WololoError> {
+        let mut tables = self.tables();
+        let settings = tables.user_settings.entry(discord_user_id as i64).or_default();
+        settings.quiet_start_minutes = quiet_minutes.map(|(start, _)| start);
+        settings.quiet_end_minutes = quiet_minutes.map(|(_, end)| end);
+        Ok(settings.clone())
+    }
+
+    async fn get_scheduled_game(&self, scheduled_game_id: i32) -> Result<ScheduledGame, WololoError> {
+        let tables = self.tables();
+        tables.scheduled_games.iter()
+            .find(|row| row.scheduled_game.id == scheduled_game_id)
+            .map(|row| tables.scheduled_game(row))
+            .ok_or(WololoError::NotFound)
+    }
+
+    async fn get_upcoming_scheduled_games_for_channel(&self, discord_channel_id: u64) -> Result<Vec<ScheduledGame>, WololoError> {
+        Ok(self.tables().scheduled_games_where(|row| row.scheduled_game.discord_channel_id == discord_channel_id as i64))
+    }
+
+    async fn get_due_scheduled_games(&self, before: DateTime<Utc>) -> Result<Vec<ScheduledGame>, WololoError> {
+        Ok(self.tables().scheduled_games_where(|row| row.scheduled_game.scheduled_for <= before))
+    }
+
+    async fn get_unreminded_scheduled_games(&self, before: DateTime<Utc>) -> Result<Vec<ScheduledGame>, WololoError> {
+        Ok(self.tables().scheduled_games_where(|row| row.scheduled_game.scheduled_for <= before && row.reminded_at.is_none()))
+    }
+
+    async fn get_rsvps_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<Vec<ScheduledGameRsvp>, WololoError> {
+        let tables = self.tables();
+        let mut rows = tables.rsvps.iter().filter(|row| row.scheduled_game_id == scheduled_game_id).collect::<Vec<_>>();
+        rows.sort_by_key(|row| (row.updated_at, row.id));
+        Ok(rows.into_iter().map(|row| row.rsvp.clone()).collect())
+    }
+
+    async fn create_scheduled_game(&self, guild_id: u64, organizer_discord_id: u64, discord_channel_id: u64, game: Option<&Game>, description: &str, scheduled_for: DateTime<Utc>) -> Result<ScheduledGame, WololoError> {
+        let mut tables = self.tables();
+        let scheduled_game = ScheduledGame {
+            id: tables.next_id() as i32,
+            guild_id: Some(guild_id as i64),
+            organizer_discord_id: organizer_discord_id as i64,
+            discord_channel_id: discord_channel_id as i64,
+            announcement_message_id: None,
+            game_name: game.map(|game| game.name.clone()),
+            description: description.to_string(),
+            scheduled_for,
+            started_at: None,
+            cancelled_at: None
+        };
+        tables.scheduled_games.push(ScheduledGameRow { scheduled_game: scheduled_game.clone(), game_id: game.map(|game| game.id), reminded_at: None, created_at: Utc::now() });
+        Ok(scheduled_game)
+    }
+
+    async fn update_announcement_message_for_scheduled_game(&self, scheduled_game_id: i32, announcement_message_id: u64) -> Result<bool, WololoError> {
+        if let Some(row) = self.tables().scheduled_game_mut(scheduled_game_id) {
+            row.scheduled_game.announcement_message_id = Some(announcement_message_id as i64);
+        }
+        Ok(true)
+    }
+
+    async fn update_reminded_at_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<bool, WololoError> {
+        match self.tables().scheduled_game_mut(scheduled_game_id) {
+            Some(row) if row.reminded_at.is_none() => {
+                row.reminded_at = Some(Utc::now());
+                Ok(true)
+            }
+            _ => Ok(false)
+        }
+    }
+
+    async fn update_started_at_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<bool, WololoError> {
+        match self.tables().scheduled_game_mut(scheduled_game_id) {
+            Some(row) if row.scheduled_game.started_at.is_none() && row.scheduled_game.cancelled_at.is_none() => {
+                row.scheduled_game.started_at = Some(Utc::now());
+                Ok(true)
+            }
+            _ => Ok(false)
+        }
+    }
+
+    async fn update_cancelled_at_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<bool, WololoError> {
+        match self.tables().scheduled_game_mut(scheduled_game_id) {
+            Some(row) if row.scheduled_game.started_at.is_none() && row.scheduled_game.cancelled_at.is_none() => {
+                row.scheduled_game.cancelled_at = Some(Utc::now());
+                Ok(true)
+            }
+            _ => Ok(false)
+        }
+    }
+
+    async fn upsert_scheduled_game_rsvp(&self, scheduled_game_id: i32, discord_user_id: u64, accepted: bool) -> Result<bool, WololoError> {
+        let rsvp = ScheduledGameRsvp {
+            discord_user_id: discord_user_id as i64,
+            accepted
+        };
+        let mut tables = self.tables();
+        match tables.rsvps.iter_mut().find(|row| row.scheduled_game_id == scheduled_game_id && row.rsvp.discord_user_id == discord_user_id as i64) {
+            Some(row) => {
+                row.rsvp = rsvp;
+                row.updated_at = Utc::now();
+            }
+            None => {
+                let id = tables.next_id();
+                tables.rsvps.push(RsvpRow { id, scheduled_game_id, rsvp, updated_at: Utc::now() });
+            }
+        }
+        Ok(true)
+    }
+
+    async fn delete_scheduled_game_rsvp(&self, scheduled_game_id: i32, discord_user_id: u64) -> Result<bool, WololoError> {
+        let mut tables = self.tables();
+        let count = tables.rsvps.len();
+        tables.rsvps.retain(|row| row.scheduled_game_id != scheduled_game_id || row.rsvp.discord_user_id != discord_user_id as i64);
+        Ok(tables.rsvps.len() < count)
+    }
+
+    // everything in memory was created with a guild
+    async fn get_channels_without_guild(&self) -> Result<Vec<i64>, WololoError> {
+        Ok(Vec::new())
+    }
+
+    async fn get_known_guild_ids(&self) -> Result<Vec<i64>, WololoError> {
+        let tables = self.tables();
+        let mut guild_ids = tables.pings.iter().filter_map(|ping| ping.guild_id)
+            .chain(tables.lobbies.iter().filter_map(|row| row.lobby.guild_id))
+            .chain(tables.scheduled_games.iter().filter_map(|row| row.scheduled_game.guild_id))
+            .chain(tables.channel_settings.values().filter_map(|row| row.guild_id))
+            .collect::<Vec<_>>();
+        guild_ids.sort();
+        guild_ids.dedup();
+        Ok(guild_ids)
+    }
+
+    async fn backfill_guild_for_channel(&self, _discord_channel_id: u64, _guild_id: u64) -> Result<u64, WololoError> {
+        Ok(0)
+    }
+
+    async fn backfill_guild_for_unscoped_rows(&self, _guild_id: u64) -> Result<u64, WololoError> {
+        Ok(0)
+    }
+}
@@ -0,0 +1,212 @@
+mod memory;
+mod postgres;
+mod schema;
+
+pub(crate) use memory::InMemoryStore;
+pub(crate) use postgres::PgStore;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serenity::async_trait;
+use crate::errors::WololoError;
+use crate::structs::{AdminUser, AuditEntry, BlacklistedUser, ChannelSettings, Game, GuildSettings, Lobby, LobbyMember, NotificationDm, NotificationRecord, ParentMessageChildMessage, Ping, ScheduledGame, ScheduledGameRsvp, UserSettings, WololoUser};
+
+/// Everything the bot saves. `PgStore` is what the bot normally runs with, `InMemoryStore` is for running without a
+/// database (`--ephemeral`) and for tests. Both have to behave the same, including what the foreign keys in the
+/// migrations do when users, games, lobbies and scheduled games are deleted
+#[async_trait]
+pub(crate) trait Store: Send + Sync {
+    // users
+
+    async fn get_user(&self, discord_id: u64) -> Result<WololoUser, WololoError>;
+
+    async fn create_user(&self, discord_id: u64) -> Result<WololoUser, WololoError>;
+
+    /// Deletes everything that belongs to the user, the notification dms in `dm_channel_id` included
+    async fn delete_user(&self, discord_id: u64, dm_channel_id: Option<u64>) -> Result<bool, WololoError>;
+
+    // pings
+
+    async fn get_ping(&self, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Ping, WololoError>;
+
+    /// One ping per user with their most recent notification, see `queries::get_all_pings_except_for_user`
+    async fn get_all_pings_except_for_user(&self, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Vec<Ping>, WololoError>;
+
+    /// Oldest subscription first
+    async fn get_pings_for_user(&self, user_discord_id: u64) -> Result<Vec<Ping>, WololoError>;
+
+    async fn get_last_notified_for_user(&self, discord_user_id: u64) -> Result<Option<DateTime<Utc>>, WololoError>;
+
+    async fn create_ping(&self, guild_id: u64, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Ping, WololoError>;
+
+    async fn delete_ping(&self, ping: Ping) -> Result<bool, WololoError>;
+
+    async fn delete_pings_for_channel(&self, user_discord_id: u64, discord_channel_id: u64) -> Result<u64, WololoError>;
+
+    async fn delete_pings_for_user(&self, user_discord_id: u64) -> Result<u64, WololoError>;
+
+
+    // notification history, the notifications go away with their organizer and the dms with their recipient
+
+    async fn get_notifications_for_guild(&self, guild_id: u64, since: DateTime<Utc>) -> Result<Vec<NotificationRecord>, WololoError>;
+
+    async fn get_notification_dms_for_guild(&self, guild_id: u64, since: DateTime<Utc>) -> Result<Vec<NotificationDm>, WololoError>;
+
+    async fn count_notification_dms_for_user(&self, recipient_discord_id: u64, since: DateTime<Utc>) -> Result<i64, WololoError>;
+
+    async fn create_notification(&self, guild_id: u64, discord_channel_id: u64, organizer_discord_id: u64, lobby_id: Option<i32>, scheduled_game_id: Option<i32>, description: &str) -> Result<NotificationRecord, WololoError>;
+
+    /// Claims the recipients that the channel's cooldown, their own gap between dms and the hourly cap still allow a dm
+    /// at `now`. Each one gets a notification dm and their subscriptions in the channel are marked as notified before
+    /// anything is sent, all at once so overlapping notifications can't both claim someone. Returns who was claimed
+    async fn reserve_notification_dms(&self, guild_id: u64, discord_channel_id: u64, notification_id: Option<i32>, recipient_discord_ids: &[u64], channel_cooldown_seconds: i64, now: DateTime<Utc>) -> Result<Vec<u64>, WololoError>;
+
+    /// Gives back the claim on a recipient whose dm couldn't be sent, their subscriptions in the channel go back to
+    /// `last_notified` unless another notification claimed them since
+    async fn release_notification_dm(&self, discord_channel_id: u64, recipient_discord_id: u64, reserved_at: DateTime<Utc>, last_notified: Option<DateTime<Utc>>) -> Result<(), WololoError>;
+
+    /// The recipient joined or declined the lobby, or answered the scheduled game
+    async fn update_response_for_notification_dms(&self, lobby_id: Option<i32>, scheduled_game_id: Option<i32>, recipient_discord_id: u64, accepted: bool) -> Result<u64, WololoError>;
+
+    // games
+
+    async fn get_games(&self, guild_id: u64) -> Result<Vec<Game>, WololoError>;
+
+    async fn get_game_by_name(&self, guild_id: u64, name: &str) -> Result<Game, WololoError>;
+
+    async fn create_game(&self, guild_id: u64, name: &str, aliases: &[String], default_party_size: i32, emoji: Option<&str>) -> Result<Game, WololoError>;
+
+    async fn delete_game(&self, game_id: i32) -> Result<bool, WololoError>;
+
+    // admins and blacklisted users
+
+    async fn is_user_admin(&self, guild_id: u64, discord_id: u64) -> Result<bool, WololoError>;
+
+    async fn get_admin_users(&self, guild_id: u64) -> Result<Vec<AdminUser>, WololoError>;
+
+    async fn create_admin_user(&self, guild_id: u64, discord_id: u64) -> Result<AdminUser, WololoError>;
+
+    async fn delete_admin_user(&self, guild_id: u64, discord_id: u64) -> Result<bool, WololoError>;
+
+    async fn is_user_blacklisted(&self, guild_id: Option<u64>, discord_id: u64) -> Result<bool, WololoError>;
+
+    async fn create_blacklisted_user(&self, guild_id: u64, discord_id: u64) -> Result<BlacklistedUser, WololoError>;
+
+    async fn delete_blacklisted_user(&self, guild_id: u64, discord_id: u64) -> Result<bool, WololoError>;
+
+    // message children
+
+    async fn get_parent_message_id_for_child_message_id(&self, child_id: u64) -> Result<ParentMessageChildMessage, WololoError>;
+
+    async fn get_latest_child_for_channel(&self, child_channel_id: u64) -> Result<ParentMessageChildMessage, WololoError>;
+
+    async fn create_child_for_message(&self, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, WololoError>;
+
+    async fn delete_child_for_message(&self, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, WololoError>;
+
+    // lobbies
+
+    async fn get_lobby(&self, lobby_id: i32) -> Result<Lobby, WololoError>;
+
+    async fn get_lobby_members(&self, lobby_id: i32) -> Result<Vec<LobbyMember>, WololoError>;
+
+    async fn create_lobby(&self, guild_id: u64, organizer_discord_id: u64, discord_channel_id: u64, target_size: i32, game: Option<&Game>, description: &str) -> Result<Lobby, WololoError>;
+
+    /// When the organizer started stacks or scheduled games in the guild, oldest first
+    async fn get_call_times_for_organizer(&self, guild_id: u64, organizer_discord_id: u64, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>, WololoError>;
+
+    async fn update_summary_message_for_lobby(&self, lobby_id: i32, summary_message_id: u64) -> Result<bool, WololoError>;
+
+    async fn update_filled_at_for_lobby(&self, lobby_id: i32) -> Result<bool, WololoError>;
+
+    async fn upsert_lobby_member(&self, lobby_id: i32, discord_user_id: u64, joined: bool) -> Result<LobbyMember, WololoError>;
+
+    async fn delete_lobby_member(&self, lobby_id: i32, discord_user_id: u64) -> Result<bool, WololoError>;
+
+    // audit log
+
+    /// Entries are kept when the users involved unregister
+    async fn create_audit_entry(&self, guild_id: u64, discord_channel_id: Option<u64>, actor_discord_id: u64, action: &str, target_discord_id: Option<u64>, details: Option<&str>) -> Result<AuditEntry, WololoError>;
+
+    /// Newest first
+    async fn get_audit_entries(&self, guild_id: u64, limit: i64) -> Result<Vec<AuditEntry>, WololoError>;
+
+    // language model
+
+    /// Tokens the language model used for the guild on `day` (in utc)
+    async fn get_language_model_tokens(&self, guild_id: u64, day: NaiveDate) -> Result<i64, WololoError>;
+
+    /// Returns the guild's new total for the day
+    async fn add_language_model_tokens(&self, guild_id: u64, day: NaiveDate, tokens: i64) -> Result<i64, WololoError>;
+
+    // settings
+
+    async fn get_channel_settings(&self, discord_channel_id: u64) -> Result<Option<ChannelSettings>, WololoError>;
+
+    async fn get_guild_settings(&self, guild_id: u64) -> Result<Option<GuildSettings>, WololoError>;
+
+    async fn get_user_settings(&self, discord_user_id: u64) -> Result<Option<UserSettings>, WololoError>;
+
+    async fn upsert_notification_cooldown_for_channel(&self, guild_id: u64, discord_channel_id: u64, notification_cooldown_seconds: i64) -> Result<ChannelSettings, WololoError>;
+
+    async fn upsert_help_text_for_guild(&self, guild_id: u64, help_text: Option<&str>) -> Result<GuildSettings, WololoError>;
+
+    async fn upsert_notification_cooldown_for_guild(&self, guild_id: u64, notification_cooldown_seconds: Option<i64>) -> Result<GuildSettings, WololoError>;
+
+    async fn upsert_command_prefix_for_guild(&self, guild_id: u64, command_prefix: Option<&str>) -> Result<GuildSettings, WololoError>;
+
+    async fn upsert_locale_for_guild(&self, guild_id: u64, locale: Option<&str>) -> Result<GuildSettings, WololoError>;
+
+    async fn upsert_personalized_invitations_for_guild(&self, guild_id: u64, personalized_invitations: bool) -> Result<GuildSettings, WololoError>;
+
+    async fn upsert_game_call_offers_for_guild(&self, guild_id: u64, game_call_offers: bool) -> Result<GuildSettings, WololoError>;
+
+    async fn upsert_invitation_theme_for_guild(&self, guild_id: u64, invitation_theme: Option<&str>) -> Result<GuildSettings, WololoError>;
+
+    async fn upsert_call_limit_for_guild(&self, guild_id: u64, call_limit: Option<(i32, i64)>) -> Result<GuildSettings, WololoError>;
+
+    async fn upsert_mod_log_channel_for_guild(&self, guild_id: u64, mod_log_channel_id: Option<u64>) -> Result<GuildSettings, WololoError>;
+
+    async fn upsert_notification_gap_for_user(&self, discord_user_id: u64, min_notification_gap_seconds: Option<i64>) -> Result<UserSettings, WololoError>;
+
+    async fn upsert_timezone_for_user(&self, discord_user_id: u64, timezone: &str) -> Result<UserSettings, WololoError>;
+
+    async fn upsert_locale_for_user(&self, discord_user_id: u64, locale: Option<&str>) -> Result<UserSettings, WololoError>;
+
+    async fn upsert_quiet_hours_for_user(&self, discord_user_id: u64, quiet_minutes: Option<(i32, i32)>) -> Result<UserSettings, WololoError>;
+
+    // scheduled games
+
+    async fn get_scheduled_game(&self, scheduled_game_id: i32) -> Result<ScheduledGame, WololoError>;
+
+    async fn get_upcoming_scheduled_games_for_channel(&self, discord_channel_id: u64) -> Result<Vec<ScheduledGame>, WololoError>;
+
+    async fn get_due_scheduled_games(&self, before: DateTime<Utc>) -> Result<Vec<ScheduledGame>, WololoError>;
+
+    async fn get_unreminded_scheduled_games(&self, before: DateTime<Utc>) -> Result<Vec<ScheduledGame>, WololoError>;
+
+    async fn get_rsvps_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<Vec<ScheduledGameRsvp>, WololoError>;
+
+    async fn create_scheduled_game(&self, guild_id: u64, organizer_discord_id: u64, discord_channel_id: u64, game: Option<&Game>, description: &str, scheduled_for: DateTime<Utc>) -> Result<ScheduledGame, WololoError>;
+
+    async fn update_announcement_message_for_scheduled_game(&self, scheduled_game_id: i32, announcement_message_id: u64) -> Result<bool, WololoError>;
+
+    async fn update_reminded_at_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<bool, WololoError>;
+
+    async fn update_started_at_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<bool, WololoError>;
+
+    async fn update_cancelled_at_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<bool, WololoError>;
+
+    async fn upsert_scheduled_game_rsvp(&self, scheduled_game_id: i32, discord_user_id: u64, accepted: bool) -> Result<bool, WololoError>;
+
+    async fn delete_scheduled_game_rsvp(&self, scheduled_game_id: i32, discord_user_id: u64) -> Result<bool, WololoError>;
+
+    // the startup backfill of rows created before everything was scoped by guild
+
+    async fn get_channels_without_guild(&self) -> Result<Vec<i64>, WololoError>;
+
+    async fn get_known_guild_ids(&self) -> Result<Vec<i64>, WololoError>;
+
+    async fn backfill_guild_for_channel(&self, discord_channel_id: u64, guild_id: u64) -> Result<u64, WololoError>;
+
+    async fn backfill_guild_for_unscoped_rows(&self, guild_id: u64) -> Result<u64, WololoError>;
+}
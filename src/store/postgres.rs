@@ -0,0 +1,341 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serenity::async_trait;
+use crate::errors::WololoError;
+use crate::{queries, writes};
+use crate::store::{schema, Store};
+use crate::structs::{AdminUser, AuditEntry, BlacklistedUser, ChannelSettings, Game, GuildSettings, Lobby, LobbyMember, NotificationDm, NotificationRecord, ParentMessageChildMessage, Ping, ScheduledGame, ScheduledGameRsvp, UserSettings, WololoUser};
+
+/// The queries and writes modules run against the database the bot was given
+pub(crate) struct PgStore {
+    pool: sqlx::PgPool,
+}
+
+impl PgStore {
+    pub(crate) fn new(pool: sqlx::PgPool) -> Self {
+        PgStore { pool }
+    }
+
+    /// Runs the migrations first if asked to, then makes sure the schema has everything the queries need
+    pub(crate) async fn prepare(pool: sqlx::PgPool, run_migrations: bool) -> anyhow::Result<Self> {
+        if run_migrations {
+            schema::run_migrations(&pool).await?;
+        }
+        schema::verify_schema(&pool).await?;
+        Ok(PgStore::new(pool))
+    }
+}
+
+#[async_trait]
+impl Store for PgStore {
+    async fn get_user(&self, discord_id: u64) -> Result<WololoUser, WololoError> {
+        queries::get_user(&self.pool, discord_id).await
+    }
+
+    async fn create_user(&self, discord_id: u64) -> Result<WololoUser, WololoError> {
+        writes::create_user(&self.pool, discord_id).await
+    }
+
+    async fn delete_user(&self, discord_id: u64, dm_channel_id: Option<u64>) -> Result<bool, WololoError> {
+        writes::delete_user(&self.pool, discord_id, dm_channel_id).await
+    }
+
+    async fn get_ping(&self, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Ping, WololoError> {
+        queries::get_ping(&self.pool, user_discord_id, discord_channel_id, game_id).await
+    }
+
+    async fn get_all_pings_except_for_user(&self, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Vec<Ping>, WololoError> {
+        queries::get_all_pings_except_for_user(&self.pool, user_discord_id, discord_channel_id, game_id).await
+    }
+
+    async fn get_pings_for_user(&self, user_discord_id: u64) -> Result<Vec<Ping>, WololoError> {
+        queries::get_pings_for_user(&self.pool, user_discord_id).await
+    }
+
+    async fn get_last_notified_for_user(&self, discord_user_id: u64) -> Result<Option<DateTime<Utc>>, WololoError> {
+        queries::get_last_notified_for_user(&self.pool, discord_user_id).await
+    }
+
+    async fn create_ping(&self, guild_id: u64, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Ping, WololoError> {
+        writes::create_ping(&self.pool, guild_id, user_discord_id, discord_channel_id, game_id).await
+    }
+
+    async fn delete_ping(&self, ping: Ping) -> Result<bool, WololoError> {
+        writes::delete_ping(&self.pool, ping).await
+    }
+
+    async fn delete_pings_for_channel(&self, user_discord_id: u64, discord_channel_id: u64) -> Result<u64, WololoError> {
+        writes::delete_pings_for_channel(&self.pool, user_discord_id, discord_channel_id).await
+    }
+
+    async fn delete_pings_for_user(&self, user_discord_id: u64) -> Result<u64, WololoError> {
+        writes::delete_pings_for_user(&self.pool, user_discord_id).await
+    }
+
+    async fn get_notifications_for_guild(&self, guild_id: u64, since: DateTime<Utc>) -> Result<Vec<NotificationRecord>, WololoError> {
+        queries::get_notifications_for_guild(&self.pool, guild_id, since).await
+    }
+
+    async fn get_notification_dms_for_guild(&self, guild_id: u64, since: DateTime<Utc>) -> Result<Vec<NotificationDm>, WololoError> {
+        queries::get_notification_dms_for_guild(&self.pool, guild_id, since).await
+    }
+
+    async fn count_notification_dms_for_user(&self, recipient_discord_id: u64, since: DateTime<Utc>) -> Result<i64, WololoError> {
+        queries::count_notification_dms_for_user(&self.pool, recipient_discord_id, since).await
+    }
+
+    async fn create_notification(&self, guild_id: u64, discord_channel_id: u64, organizer_discord_id: u64, lobby_id: Option<i32>, scheduled_game_id: Option<i32>, description: &str) -> Result<NotificationRecord, WololoError> {
+        writes::create_notification(&self.pool, guild_id, discord_channel_id, organizer_discord_id, lobby_id, scheduled_game_id, description).await
+    }
+
+    async fn reserve_notification_dms(&self, guild_id: u64, discord_channel_id: u64, notification_id: Option<i32>, recipient_discord_ids: &[u64], channel_cooldown_seconds: i64, now: DateTime<Utc>) -> Result<Vec<u64>, WololoError> {
+        writes::reserve_notification_dms(&self.pool, guild_id, discord_channel_id, notification_id, recipient_discord_ids, channel_cooldown_seconds, now).await
+    }
+
+    async fn release_notification_dm(&self, discord_channel_id: u64, recipient_discord_id: u64, reserved_at: DateTime<Utc>, last_notified: Option<DateTime<Utc>>) -> Result<(), WololoError> {
+        writes::release_notification_dm(&self.pool, discord_channel_id, recipient_discord_id, reserved_at, last_notified).await
+    }
+
+    async fn update_response_for_notification_dms(&self, lobby_id: Option<i32>, scheduled_game_id: Option<i32>, recipient_discord_id: u64, accepted: bool) -> Result<u64, WololoError> {
+        writes::update_response_for_notification_dms(&self.pool, lobby_id, scheduled_game_id, recipient_discord_id, accepted).await
+    }
+
+    async fn get_games(&self, guild_id: u64) -> Result<Vec<Game>, WololoError> {
+        queries::get_games(&self.pool, guild_id).await
+    }
+
+    async fn get_game_by_name(&self, guild_id: u64, name: &str) -> Result<Game, WololoError> {
+        queries::get_game_by_name(&self.pool, guild_id, name).await
+    }
+
+    async fn create_game(&self, guild_id: u64, name: &str, aliases: &[String], default_party_size: i32, emoji: Option<&str>) -> Result<Game, WololoError> {
+        writes::create_game(&self.pool, guild_id, name, aliases, default_party_size, emoji).await
+    }
+
+    async fn delete_game(&self, game_id: i32) -> Result<bool, WololoError> {
+        writes::delete_game(&self.pool, game_id).await
+    }
+
+    async fn is_user_admin(&self, guild_id: u64, discord_id: u64) -> Result<bool, WololoError> {
+        queries::is_user_admin(&self.pool, guild_id, discord_id).await
+    }
+
+    async fn get_admin_users(&self, guild_id: u64) -> Result<Vec<AdminUser>, WololoError> {
+        queries::get_admin_users(&self.pool, guild_id).await
+    }
+
+    async fn create_admin_user(&self, guild_id: u64, discord_id: u64) -> Result<AdminUser, WololoError> {
+        writes::create_admin_user(&self.pool, guild_id, discord_id).await
+    }
+
+    async fn delete_admin_user(&self, guild_id: u64, discord_id: u64) -> Result<bool, WololoError> {
+        writes::delete_admin_user(&self.pool, guild_id, discord_id).await
+    }
+
+    async fn is_user_blacklisted(&self, guild_id: Option<u64>, discord_id: u64) -> Result<bool, WololoError> {
+        queries::is_user_blacklisted(&self.pool, guild_id, discord_id).await
+    }
+
+    async fn create_blacklisted_user(&self, guild_id: u64, discord_id: u64) -> Result<BlacklistedUser, WololoError> {
+        writes::create_blacklisted_user(&self.pool, guild_id, discord_id).await
+    }
+
+    async fn delete_blacklisted_user(&self, guild_id: u64, discord_id: u64) -> Result<bool, WololoError> {
+        writes::delete_blacklisted_user(&self.pool, guild_id, discord_id).await
+    }
+
+    async fn get_parent_message_id_for_child_message_id(&self, child_id: u64) -> Result<ParentMessageChildMessage, WololoError> {
+        queries::get_parent_message_id_for_child_message_id(&self.pool, child_id).await
+    }
+
+    async fn get_latest_child_for_channel(&self, child_channel_id: u64) -> Result<ParentMessageChildMessage, WololoError> {
+        queries::get_latest_child_for_channel(&self.pool, child_channel_id).await
+    }
+
+    async fn create_child_for_message(&self, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, WololoError> {
+        writes::create_child_for_message(&self.pool, parent_msg_child_msg).await
+    }
+
+    async fn delete_child_for_message(&self, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, WololoError> {
+        writes::delete_child_for_message(&self.pool, parent_msg_child_msg).await
+    }
+
+    async fn get_lobby(&self, lobby_id: i32) -> Result<Lobby, WololoError> {
+        queries::get_lobby(&self.pool, lobby_id).await
+    }
+
+    async fn get_lobby_members(&self, lobby_id: i32) -> Result<Vec<LobbyMember>, WololoError> {
+        queries::get_lobby_members(&self.pool, lobby_id).await
+    }
+
+    async fn create_lobby(&self, guild_id: u64, organizer_discord_id: u64, discord_channel_id: u64, target_size: i32, game: Option<&Game>, description: &str) -> Result<Lobby, WololoError> {
+        writes::create_lobby(&self.pool, guild_id, organizer_discord_id, discord_channel_id, target_size, game, description).await
+    }
+
+    async fn get_call_times_for_organizer(&self, guild_id: u64, organizer_discord_id: u64, since: DateTime<Utc>) -> Result<Vec<DateTime<Utc>>, WololoError> {
+        queries::get_call_times_for_organizer(&self.pool, guild_id, organizer_discord_id, since).await
+    }
+
+    async fn update_summary_message_for_lobby(&self, lobby_id: i32, summary_message_id: u64) -> Result<bool, WololoError> {
+        writes::update_summary_message_for_lobby(&self.pool, lobby_id, summary_message_id).await
+    }
+
+    async fn update_filled_at_for_lobby(&self, lobby_id: i32) -> Result<bool, WololoError> {
+        writes::update_filled_at_for_lobby(&self.pool, lobby_id).await
+    }
+
+    async fn upsert_lobby_member(&self, lobby_id: i32, discord_user_id: u64, joined: bool) -> Result<LobbyMember, WololoError> {
+        writes::upsert_lobby_member(&self.pool, lobby_id, discord_user_id, joined).await
+    }
+
+    async fn delete_lobby_member(&self, lobby_id: i32, discord_user_id: u64) -> Result<bool, WololoError> {
+        writes::delete_lobby_member(&self.pool, lobby_id, discord_user_id).await
+    }
+
+    async fn create_audit_entry(&self, guild_id: u64, discord_channel_id: Option<u64>, actor_discord_id: u64, action: &str, target_discord_id: Option<u64>, details: Option<&str>) -> Result<AuditEntry, WololoError> {
+        writes::create_audit_entry(&self.pool, guild_id, discord_channel_id, actor_discord_id, action, target_discord_id, details).await
+    }
+
+    async fn get_audit_entries(&self, guild_id: u64, limit: i64) -> Result<Vec<AuditEntry>, WololoError> {
+        queries::get_audit_entries(&self.pool, guild_id, limit).await
+    }
+
+    async fn get_language_model_tokens(&self, guild_id: u64, day: NaiveDate) -> Result<i64, WololoError> {
+        queries::get_language_model_tokens(&self.pool, guild_id, day).await
+    }
+
+    async fn add_language_model_tokens(&self, guild_id: u64, day: NaiveDate, tokens: i64) -> Result<i64, WololoError> {
+        writes::add_language_model_tokens(&self.pool, guild_id, day, tokens).await
+    }
+
+    async fn get_channel_settings(&self, discord_channel_id: u64) -> Result<Option<ChannelSettings>, WololoError> {
+        queries::get_channel_settings(&self.pool, discord_channel_id).await
+    }
+
+    async fn get_guild_settings(&self, guild_id: u64) -> Result<Option<GuildSettings>, WololoError> {
+        queries::get_guild_settings(&self.pool, guild_id).await
+    }
+
+    async fn get_user_settings(&self, discord_user_id: u64) -> Result<Option<UserSettings>, WololoError> {
+        queries::get_user_settings(&self.pool, discord_user_id).await
+    }
+
+    async fn upsert_notification_cooldown_for_channel(&self, guild_id: u64, discord_channel_id: u64, notification_cooldown_seconds: i64) -> Result<ChannelSettings, WololoError> {
+        writes::upsert_notification_cooldown_for_channel(&self.pool, guild_id, discord_channel_id, notification_cooldown_seconds).await
+    }
+
+    async fn upsert_help_text_for_guild(&self, guild_id: u64, help_text: Option<&str>) -> Result<GuildSettings, WololoError> {
+        writes::upsert_help_text_for_guild(&self.pool, guild_id, help_text).await
+    }
+
+    async fn upsert_notification_cooldown_for_guild(&self, guild_id: u64, notification_cooldown_seconds: Option<i64>) -> Result<GuildSettings, WololoError> {
+        writes::upsert_notification_cooldown_for_guild(&self.pool, guild_id, notification_cooldown_seconds).await
+    }
+
+    async fn upsert_command_prefix_for_guild(&self, guild_id: u64, command_prefix: Option<&str>) -> Result<GuildSettings, WololoError> {
+        writes::upsert_command_prefix_for_guild(&self.pool, guild_id, command_prefix).await
+    }
+
+    async fn upsert_locale_for_guild(&self, guild_id: u64, locale: Option<&str>) -> Result<GuildSettings, WololoError> {
+        writes::upsert_locale_for_guild(&self.pool, guild_id, locale).await
+    }
+
+    async fn upsert_personalized_invitations_for_guild(&self, guild_id: u64, personalized_invitations: bool) -> Result<GuildSettings, WololoError> {
+        writes::upsert_personalized_invitations_for_guild(&self.pool, guild_id, personalized_invitations).await
+    }
+
+    async fn upsert_game_call_offers_for_guild(&self, guild_id: u64, game_call_offers: bool) -> Result<GuildSettings, WololoError> {
+        writes::upsert_game_call_offers_for_guild(&self.pool, guild_id, game_call_offers).await
+    }
+
+    async fn upsert_invitation_theme_for_guild(&self, guild_id: u64, invitation_theme: Option<&str>) -> Result<GuildSettings, WololoError> {
+        writes::upsert_invitation_theme_for_guild(&self.pool, guild_id, invitation_theme).await
+    }
+
+    async fn upsert_call_limit_for_guild(&self, guild_id: u64, call_limit: Option<(i32, i64)>) -> Result<GuildSettings, WololoError> {
+        writes::upsert_call_limit_for_guild(&self.pool, guild_id, call_limit).await
+    }
+
+    async fn upsert_mod_log_channel_for_guild(&self, guild_id: u64, mod_log_channel_id: Option<u64>) -> Result<GuildSettings, WololoError> {
+        writes::upsert_mod_log_channel_for_guild(&self.pool, guild_id, mod_log_channel_id).await
+    }
+
+    async fn upsert_notification_gap_for_user(&self, discord_user_id: u64, min_notification_gap_seconds: Option<i64>) -> Result<UserSettings, WololoError> {
+        writes::upsert_notification_gap_for_user(&self.pool, discord_user_id, min_notification_gap_seconds).await
+    }
+
+    async fn upsert_timezone_for_user(&self, discord_user_id: u64, timezone: &str) -> Result<UserSettings, WololoError> {
+        writes::upsert_timezone_for_user(&self.pool, discord_user_id, timezone).await
+    }
+
+    async fn upsert_locale_for_user(&self, discord_user_id: u64, locale: Option<&str>) -> Result<UserSettings, WololoError> {
+        writes::upsert_locale_for_user(&self.pool, discord_user_id, locale).await
+    }
+
+    async fn upsert_quiet_hours_for_user(&self, discord_user_id: u64, quiet_minutes: Option<(i32, i32)>) -> Result<UserSettings, WololoError> {
+        writes::upsert_quiet_hours_for_user(&self.pool, discord_user_id, quiet_minutes).await
+    }
+
+    async fn get_scheduled_game(&self, scheduled_game_id: i32) -> Result<ScheduledGame, WololoError> {
+        queries::get_scheduled_game(&self.pool, scheduled_game_id).await
+    }
+
+    async fn get_upcoming_scheduled_games_for_channel(&self, discord_channel_id: u64) -> Result<Vec<ScheduledGame>, WololoError> {
+        queries::get_upcoming_scheduled_games_for_channel(&self.pool, discord_channel_id).await
+    }
+
+    async fn get_due_scheduled_games(&self, before: DateTime<Utc>) -> Result<Vec<ScheduledGame>, WololoError> {
+        queries::get_due_scheduled_games(&self.pool, before).await
+    }
+
+    async fn get_unreminded_scheduled_games(&self, before: DateTime<Utc>) -> Result<Vec<ScheduledGame>, WololoError> {
+        queries::get_unreminded_scheduled_games(&self.pool, before).await
+    }
+
+    async fn get_rsvps_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<Vec<ScheduledGameRsvp>, WololoError> {
+        queries::get_rsvps_for_scheduled_game(&self.pool, scheduled_game_id).await
+    }
+
+    async fn create_scheduled_game(&self, guild_id: u64, organizer_discord_id: u64, discord_channel_id: u64, game: Option<&Game>, description: &str, scheduled_for: DateTime<Utc>) -> Result<ScheduledGame, WololoError> {
+        writes::create_scheduled_game(&self.pool, guild_id, organizer_discord_id, discord_channel_id, game, description, scheduled_for).await
+    }
+
+    async fn update_announcement_message_for_scheduled_game(&self, scheduled_game_id: i32, announcement_message_id: u64) -> Result<bool, WololoError> {
+        writes::update_announcement_message_for_scheduled_game(&self.pool, scheduled_game_id, announcement_message_id).await
+    }
+
+    async fn update_reminded_at_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<bool, WololoError> {
+        writes::update_reminded_at_for_scheduled_game(&self.pool, scheduled_game_id).await
+    }
+
+    async fn update_started_at_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<bool, WololoError> {
+        writes::update_started_at_for_scheduled_game(&self.pool, scheduled_game_id).await
+    }
+
+    async fn update_cancelled_at_for_scheduled_game(&self, scheduled_game_id: i32) -> Result<bool, WololoError> {
+        writes::update_cancelled_at_for_scheduled_game(&self.pool, scheduled_game_id).await
+    }
+
+    async fn upsert_scheduled_game_rsvp(&self, scheduled_game_id: i32, discord_user_id: u64, accepted: bool) -> Result<bool, WololoError> {
+        writes::upsert_scheduled_game_rsvp(&self.pool, scheduled_game_id, discord_user_id, accepted).await
+    }
+
+    async fn delete_scheduled_game_rsvp(&self, scheduled_game_id: i32, discord_user_id: u64) -> Result<bool, WololoError> {
+        writes::delete_scheduled_game_rsvp(&self.pool, scheduled_game_id, discord_user_id).await
+    }
+
+    async fn get_channels_without_guild(&self) -> Result<Vec<i64>, WololoError> {
+        queries::get_channels_without_guild(&self.pool).await
+    }
+
+    async fn get_known_guild_ids(&self) -> Result<Vec<i64>, WololoError> {
+        queries::get_known_guild_ids(&self.pool).await
+    }
+
+    async fn backfill_guild_for_channel(&self, discord_channel_id: u64, guild_id: u64) -> Result<u64, WololoError> {
+        writes::backfill_guild_for_channel(&self.pool, discord_channel_id, guild_id).await
+    }
+
+    async fn backfill_guild_for_unscoped_rows(&self, guild_id: u64) -> Result<u64, WololoError> {
+        writes::backfill_guild_for_unscoped_rows(&self.pool, guild_id).await
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+use anyhow::Context as _;
+
+/// Every column the queries and writes modules use, by table. Migrations that add something the bot relies on should
+/// add it here too
+const EXPECTED_COLUMNS: &[(&str, &[&str])] = &[
+    ("users", &["id", "discord_id", "created_at"]),
+    ("ping_list", &["id", "discord_channel_id", "discord_user_id", "created_at", "last_notified", "game_id", "guild_id"]),
+    ("admins", &["id", "discord_user_id", "guild_id"]),
+    ("blacklisted_users", &["id", "discord_user_id", "guild_id"]),
+    ("message_children", &["id", "parent", "child", "parent_channel_id", "child_channel_id", "lobby_id", "scheduled_game_id", "guild_id"]),
+    ("lobbies", &["id", "organizer_discord_id", "discord_channel_id", "summary_message_id", "target_size", "created_at", "filled_at", "description", "game_id", "guild_id"]),
+    ("lobby_members", &["id", "lobby_id", "discord_user_id", "joined", "updated_at"]),
+    ("channel_settings", &["id", "discord_channel_id", "notification_cooldown_seconds", "guild_id"]),
+    ("guild_settings", &["id", "guild_id", "help_text", "notification_cooldown_seconds", "command_prefix", "locale", "personalized_invitations", "invitation_theme", "game_call_offers", "mod_log_channel_id", "call_limit", "call_window_seconds"]),
+    ("notification_dms", &["id", "recipient_discord_id", "guild_id", "discord_channel_id", "sent_at", "notification_id", "responded_at", "accepted"]),
+    ("notifications", &["id", "guild_id", "discord_channel_id", "organizer_discord_id", "lobby_id", "scheduled_game_id", "description", "created_at"]),
+    ("audit_log", &["id", "guild_id", "discord_channel_id", "actor_discord_id", "action", "target_discord_id", "details", "created_at"]),
+    ("language_model_usage", &["id", "guild_id", "day", "tokens"]),
+    ("user_settings", &["id", "discord_user_id", "min_notification_gap_seconds", "timezone", "quiet_start_minutes", "quiet_end_minutes", "locale"]),
+    ("scheduled_games", &["id", "organizer_discord_id", "discord_channel_id", "announcement_message_id", "description", "scheduled_for", "created_at", "reminded_at", "started_at", "cancelled_at", "game_id", "guild_id"]),
+    ("scheduled_game_rsvps", &["id", "scheduled_game_id", "discord_user_id", "accepted", "updated_at"]),
+    ("games", &["id", "name", "aliases", "default_party_size", "emoji", "created_at", "guild_id"]),
+];
+
+/// Applies everything in `migrations/` that hasn't been yet, the directory is embedded in the binary
+pub(crate) async fn run_migrations(pool: &sqlx::PgPool) -> anyhow::Result<()> {
+    sqlx::migrate!().run(pool).await.context("Unable to run migrations")
+}
+
+/// Fails with every missing column at once, so a database that wasn't migrated stops the bot from starting instead of
+/// breaking the first command that needs the column
+pub(crate) async fn verify_schema(pool: &sqlx::PgPool) -> anyhow::Result<()> {
+    let columns: Vec<(String, String)> = sqlx::query_as(
+        "SELECT table_name::TEXT, column_name::TEXT FROM information_schema.columns WHERE table_schema = current_schema()"
+    )
+        .fetch_all(pool)
+        .await
+        .context("Unable to read the database schema")?;
+    let columns = columns.into_iter().collect::<HashSet<_>>();
+    let missing = EXPECTED_COLUMNS.iter()
+        .flat_map(|(table, expected)| expected.iter().map(move |column| (*table, *column)))
+        .filter(|(table, column)| !columns.contains(&(table.to_string(), column.to_string())))
+        .map(|(table, column)| format!("{}.{}", table, column))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        anyhow::bail!("The database is missing {}. Run the migrations with `sqlx migrate run` or set RUN_MIGRATIONS = \"true\"", missing.join(", "));
+    }
+    Ok(())
+}
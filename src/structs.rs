@@ -1,26 +1,148 @@
-#[derive(sqlx::FromRow)]
+#[derive(Clone)]
 pub(crate) struct WololoUser {
     pub(crate) discord_id: i64,
     pub(crate) created_at: chrono::DateTime<chrono::Utc>
 }
 
-#[derive(sqlx::FromRow)]
-
+#[derive(Clone)]
 pub(crate) struct AdminUser {
     pub(crate) discord_id: i64,
 }
-#[derive(sqlx::FromRow)]
+#[derive(Clone)]
+pub(crate) struct BlacklistedUser {
+    pub(crate) discord_id: i64,
+}
+#[derive(Clone)]
 pub(crate) struct Ping {
     pub(crate) user_discord_id: i64,
+    // None for subscriptions made before rows were scoped by guild whose channel is gone
+    pub(crate) guild_id: Option<i64>,
     pub(crate) discord_channel_id: i64,
+    // None means every game in the channel
+    pub(crate) game_id: Option<i32>,
     pub(crate) created_at: chrono::DateTime<chrono::Utc>,
     pub(crate) last_notified: Option<chrono::DateTime<chrono::Utc>>
 }
 
-#[derive(sqlx::FromRow)]
+#[derive(Clone)]
+pub(crate) struct Game {
+    pub(crate) id: i32,
+    // None for games shared by every guild
+    pub(crate) guild_id: Option<i64>,
+    pub(crate) name: String,
+    pub(crate) aliases: Vec<String>,
+    pub(crate) default_party_size: i32,
+    pub(crate) emoji: Option<String>,
+}
+
+#[derive(Clone)]
 pub(crate) struct ParentMessageChildMessage {
     pub(crate) parent: i64,
     pub(crate) parent_channel_id: i64,
     pub(crate) child: i64,
     pub(crate) child_channel_id: i64,
-}
\ No newline at end of file
+    pub(crate) guild_id: Option<i64>,
+    pub(crate) lobby_id: Option<i32>,
+    pub(crate) scheduled_game_id: Option<i32>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Lobby {
+    pub(crate) id: i32,
+    pub(crate) guild_id: Option<i64>,
+    pub(crate) organizer_discord_id: i64,
+    pub(crate) discord_channel_id: i64,
+    pub(crate) summary_message_id: Option<i64>,
+    pub(crate) target_size: i32,
+    pub(crate) game_name: Option<String>,
+    pub(crate) description: String,
+    pub(crate) created_at: chrono::DateTime<chrono::Utc>,
+    pub(crate) filled_at: Option<chrono::DateTime<chrono::Utc>>
+}
+
+#[derive(Clone)]
+pub(crate) struct LobbyMember {
+    pub(crate) discord_user_id: i64,
+    pub(crate) joined: bool,
+}
+#[derive(Clone)]
+pub(crate) struct ChannelSettings {
+    pub(crate) notification_cooldown_seconds: i64
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct GuildSettings {
+    pub(crate) help_text: Option<String>,
+    pub(crate) notification_cooldown_seconds: Option<i64>,
+    pub(crate) command_prefix: Option<String>,
+    pub(crate) locale: Option<String>,
+    // notification dms are written by the language model, in the guild's theme if it has one
+    pub(crate) personalized_invitations: bool,
+    pub(crate) invitation_theme: Option<String>,
+    // messages that look like someone looking for players get an offer to notify the channel's subscribers
+    pub(crate) game_call_offers: bool,
+    // every audit log entry is also posted there
+    pub(crate) mod_log_channel_id: Option<i64>,
+    // how many stacks someone can start per window, see `constants::DEFAULT_CALL_LIMIT`
+    pub(crate) call_limit: Option<i32>,
+    pub(crate) call_window_seconds: Option<i64>
+}
+
+#[derive(Clone)]
+pub(crate) struct AuditEntry {
+    pub(crate) guild_id: i64,
+    pub(crate) discord_channel_id: Option<i64>,
+    pub(crate) actor_discord_id: i64,
+    // see `audit::AuditAction`
+    pub(crate) action: String,
+    pub(crate) target_discord_id: Option<i64>,
+    // e.g. the setting and its new value
+    pub(crate) details: Option<String>,
+    pub(crate) created_at: chrono::DateTime<chrono::Utc>
+}
+
+/// A stack or scheduled game people were notified about
+#[derive(Clone)]
+pub(crate) struct NotificationRecord {
+    pub(crate) id: i32,
+    pub(crate) discord_channel_id: i64,
+    pub(crate) organizer_discord_id: i64,
+    pub(crate) created_at: chrono::DateTime<chrono::Utc>
+}
+
+/// One of their dms
+#[derive(Clone)]
+pub(crate) struct NotificationDm {
+    pub(crate) recipient_discord_id: i64,
+    // whether they joined or are coming, None until they answered
+    pub(crate) accepted: Option<bool>
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct UserSettings {
+    pub(crate) min_notification_gap_seconds: Option<i64>,
+    pub(crate) timezone: Option<String>,
+    pub(crate) quiet_start_minutes: Option<i32>,
+    pub(crate) quiet_end_minutes: Option<i32>,
+    pub(crate) locale: Option<String>
+}
+
+#[derive(Clone)]
+pub(crate) struct ScheduledGame {
+    pub(crate) id: i32,
+    pub(crate) guild_id: Option<i64>,
+    pub(crate) organizer_discord_id: i64,
+    pub(crate) discord_channel_id: i64,
+    pub(crate) announcement_message_id: Option<i64>,
+    pub(crate) game_name: Option<String>,
+    pub(crate) description: String,
+    pub(crate) scheduled_for: chrono::DateTime<chrono::Utc>,
+    pub(crate) started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub(crate) cancelled_at: Option<chrono::DateTime<chrono::Utc>>
+}
+
+#[derive(Clone)]
+pub(crate) struct ScheduledGameRsvp {
+    pub(crate) discord_user_id: i64,
+    pub(crate) accepted: bool
+}
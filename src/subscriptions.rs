@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use serenity::all::{ButtonStyle, ChannelId, ComponentInteraction, CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, GuildId};
+use tracing::{error, info};
+use crate::chat::ChatBackend;
+use crate::constants;
+use crate::errors::WololoError;
+use crate::guilds::get_command_prefix;
+use crate::messages::{get_locale, with_prefix, Messages};
+use crate::store::Store;
+use crate::structs::Ping;
+
+pub(crate) const BUTTON_PREFIX: &str = "unsubscribe:";
+
+// discord allows 5 rows of 5 buttons
+const MAX_BUTTONS: usize = 25;
+// button labels can't be longer than 80 characters
+const MAX_LABEL_LENGTH: usize = 80;
+
+struct Subscription {
+    ping: Ping,
+    channel_name: String,
+    guild_name: Option<String>,
+    // None when they are subscribed to every game in the channel
+    game_name: Option<String>,
+}
+
+// the name, channels and games of a guild the user is subscribed in, fetched once per guild instead of once per channel
+struct GuildDetails {
+    name: String,
+    channel_names: HashMap<ChannelId, String>,
+    game_names: HashMap<i32, String>,
+}
+
+async fn get_guild_details(chat: &dyn ChatBackend, database: &dyn Store, messages: &dyn Messages, guild_id: GuildId) -> GuildDetails {
+    let name = chat.guild_name(guild_id).await.unwrap_or_else(|_| messages.unknown_server());
+    // the bot was removed from the server, they can still unsubscribe
+    let channel_names = chat.guild_channel_names(guild_id).await.unwrap_or_else(|e| {
+        error!("Unable to get the channels of guild {}: {:?}", guild_id.get(), e);
+        HashMap::new()
+    });
+    let game_names = match database.get_games(guild_id.get()).await {
+        Ok(games) => games.into_iter().map(|game| (game.id, game.name)).collect(),
+        Err(error) => {
+            error!("Unable to get the games of guild {}: {}", guild_id.get(), error);
+            HashMap::new()
+        }
+    };
+    GuildDetails { name, channel_names, game_names }
+}
+
+async fn get_subscriptions(chat: &dyn ChatBackend, database: &dyn Store, messages: &dyn Messages, pings: Vec<Ping>) -> Vec<Subscription> {
+    let mut guilds: HashMap<GuildId, GuildDetails> = HashMap::new();
+    let mut subscriptions = Vec::new();
+    for ping in pings {
+        // the startup backfill leaves the guild empty when the channel doesn't exist anymore
+        let Some(guild_id) = ping.guild_id.map(|guild_id| GuildId::new(guild_id as u64)) else {
+            subscriptions.push(Subscription { ping, channel_name: messages.deleted_channel(), guild_name: None, game_name: None });
+            continue;
+        };
+        if let Entry::Vacant(entry) = guilds.entry(guild_id) {
+            entry.insert(get_guild_details(chat, database, messages, guild_id).await);
+        }
+        let guild = &guilds[&guild_id];
+        let channel_name = match guild.channel_names.get(&ChannelId::new(ping.discord_channel_id as u64)) {
+            Some(channel_name) => channel_name.clone(),
+            None => messages.deleted_channel()
+        };
+        let game_name = ping.game_id.and_then(|game_id| guild.game_names.get(&game_id).cloned());
+        subscriptions.push(Subscription { ping, channel_name, guild_name: Some(guild.name.clone()), game_name });
+    }
+    subscriptions
+}
+
+fn subscriptions_text(messages: &dyn Messages, prefix: &str, subscriptions: &[Subscription]) -> String {
+    if subscriptions.is_empty() {
+        return messages.no_subscriptions(&with_prefix(constants::GAME_NOTIFICATION_ON_CMD, prefix));
+    }
+    let lines = subscriptions.iter().map(|subscription| {
+        let since = format!("<t:{}:D>", subscription.ping.created_at.timestamp());
+        let last_notified = subscription.ping.last_notified.map(|last_notified| format!("<t:{}:R>", last_notified.timestamp()));
+        messages.subscription_line(&subscription.channel_name, subscription.guild_name.as_deref(), subscription.game_name.as_deref(), &since, last_notified.as_deref())
+    }).collect::<Vec<_>>();
+    let mut text = messages.subscriptions(&lines);
+    if subscriptions.len() > MAX_BUTTONS {
+        text.push_str(&format!("\n{}", messages.too_many_subscriptions(MAX_BUTTONS, &with_prefix(constants::GAME_NOTIFICATION_OFF_CMD, prefix))));
+    }
+    text
+}
+
+/// The user id is part of the button so nobody else can unsubscribe them from a list posted in a channel, the game id
+/// is left off for subscriptions to every game
+fn subscription_buttons(messages: &dyn Messages, user_discord_id: u64, subscriptions: &[Subscription]) -> Vec<CreateActionRow> {
+    let buttons = subscriptions.iter().take(MAX_BUTTONS).map(|subscription| {
+        let label = messages.unsubscribe_button(subscription.game_name.as_deref(), &subscription.channel_name).chars().take(MAX_LABEL_LENGTH).collect::<String>();
+        let game_id = subscription.ping.game_id.map(|game_id| format!(":{game_id}")).unwrap_or_default();
+        CreateButton::new(format!("{BUTTON_PREFIX}{user_discord_id}:{}{}", subscription.ping.discord_channel_id, game_id)).label(label).style(ButtonStyle::Danger)
+    }).collect::<Vec<_>>();
+    buttons.chunks(5).map(|row| CreateActionRow::Buttons(row.to_vec())).collect()
+}
+
+/// The list of a user's subscriptions along with a button to unsubscribe from each one, `prefix` is used for the
+/// commands it mentions
+pub(crate) async fn describe_subscriptions(chat: &dyn ChatBackend, database: &dyn Store, messages: &dyn Messages, prefix: &str, user_discord_id: u64) -> Result<(String, Vec<CreateActionRow>), WololoError> {
+    let pings = database.get_pings_for_user(user_discord_id).await?;
+    let subscriptions = get_subscriptions(chat, database, messages, pings).await;
+    Ok((subscriptions_text(messages, prefix, &subscriptions), subscription_buttons(messages, user_discord_id, &subscriptions)))
+}
+
+/// Handles the unsubscribe buttons, the list is redrawn without the channel they unsubscribed from
+pub(crate) async fn handle_component(chat: &dyn ChatBackend, interaction: &ComponentInteraction, database: &dyn Store) {
+    let Some(ids) = interaction.data.custom_id.strip_prefix(BUTTON_PREFIX) else {
+        return;
+    };
+    let (owner_discord_id, game_id, discord_channel_id) = match ids.split(':').collect::<Vec<_>>()[..] {
+        [owner_discord_id, discord_channel_id] => (owner_discord_id.parse::<u64>(), Ok(None), discord_channel_id.parse::<u64>()),
+        [owner_discord_id, discord_channel_id, game_id] => (owner_discord_id.parse::<u64>(), game_id.parse::<i32>().map(Some), discord_channel_id.parse::<u64>()),
+        _ => return
+    };
+    let (Ok(owner_discord_id), Ok(game_id), Ok(discord_channel_id)) = (owner_discord_id, game_id, discord_channel_id) else {
+        return;
+    };
+    let user_discord_id = interaction.user.id.get();
+    let messages = get_locale(database, interaction.guild_id.map(GuildId::get), user_discord_id).await.messages();
+    let prefix = get_command_prefix(chat, database, interaction.guild_id).await;
+    let response = if owner_discord_id != user_discord_id {
+        CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(messages.not_your_subscriptions(&with_prefix(constants::MY_SUBSCRIPTIONS_CMD, &prefix))).ephemeral(true))
+    }
+    else {
+        let result = match database.get_ping(user_discord_id, discord_channel_id, game_id).await {
+            Ok(ping) => database.delete_ping(ping).await.map(|_| ()),
+            // they already unsubscribed some other way
+            Err(WololoError::NotFound) => Ok(()),
+            Err(error) => Err(error)
+        };
+        // the list is only redrawn once they are unsubscribed
+        let result = match result {
+            Ok(()) => describe_subscriptions(chat, database, messages, &prefix, user_discord_id).await,
+            Err(error) => Err(error)
+        };
+        match result {
+            Ok((content, components)) => {
+                info!("User {} unsubscribed from channel {} from their subscription list", user_discord_id, discord_channel_id);
+                CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new().content(content).components(components))
+            }
+            Err(error) => {
+                error!("Unable to unsubscribe user {} from channel {}: {}", user_discord_id, discord_channel_id, error);
+                CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().content(messages.database_trouble()).ephemeral(true))
+            }
+        }
+    };
+    if let Err(e) = chat.respond_to_interaction(interaction.id, &interaction.token, response).await {
+        error!("Error sending interaction response: {:?}", e);
+    }
+}
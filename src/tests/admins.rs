@@ -0,0 +1,32 @@
+use serenity::all::RoleId;
+use crate::permissions::Permissions;
+use crate::tests::{Harness, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+const CAROL: u64 = 3;
+const MODERATOR: RoleId = RoleId::new(500);
+
+#[tokio::test]
+async fn role_admins_cant_make_admins() {
+    for harness in Harness::all().await {
+        harness.chat.set_permissions(Permissions::new(None, Some("500"))).await;
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        let carol = harness.user(CAROL, "carol");
+        harness.say(&alice, "!register").await;
+        harness.say(&bob, "!register").await;
+        harness.chat.give_role(bob.id, MODERATOR);
+
+        harness.say_mentioning(&bob, "!admin <@3>", &[&carol]).await;
+        assert_eq!(harness.last_reply(), "@bob Admins from a server role can't add admins, ask an admin or the owner.");
+        assert!(!harness.store.is_user_admin(GUILD_ID.get(), CAROL).await.unwrap());
+
+        // admins from the admins table still can
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+        harness.say_mentioning(&alice, "!admin <@3>", &[&carol]).await;
+        assert_eq!(harness.last_reply(), "@alice carol has been added as an admin.");
+
+        harness.finish().await;
+    }
+}
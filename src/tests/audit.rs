@@ -0,0 +1,76 @@
+use serenity::all::ChannelId;
+use crate::tests::{Harness, CHANNEL_ID, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+const CAROL: u64 = 3;
+const MOD_LOG: ChannelId = ChannelId::new(300);
+
+#[tokio::test]
+async fn admin_actions_are_recorded_and_mirrored() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        let carol = harness.user(CAROL, "carol");
+        harness.say(&alice, "!register").await;
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+
+        harness.say(&alice, "!audit").await;
+        assert_eq!(harness.last_reply(), "@alice No admin actions have been recorded in this server yet.");
+
+        harness.say_mentioning(&alice, "!admin <@2>", &[&bob]).await;
+        harness.say(&alice, &format!("!server-settings mod-log <#{}>", MOD_LOG.get())).await;
+        assert_eq!(harness.last_reply(), "@alice Admin actions will now also be posted in <#300>");
+        harness.say_mentioning(&alice, "!blacklist <@3>", &[&carol]).await;
+        harness.say(&alice, "!server-settings prefix ?").await;
+
+        // only what happened after the mod log was set is posted there
+        let mirrored = harness.chat.sent_in(MOD_LOG).into_iter().map(|message| message.content).collect::<Vec<_>>();
+        assert_eq!(mirrored.len(), 3, "{:?}", mirrored);
+        assert!(mirrored[0].ends_with(" <@1> set mod-log <#300> in <#200>"), "{}", mirrored[0]);
+        assert!(mirrored[1].ends_with(" <@1> blacklisted <@3> in <#200>"), "{}", mirrored[1]);
+        assert!(mirrored[2].ends_with(" <@1> set prefix ? in <#200>"), "{}", mirrored[2]);
+
+        harness.say(&alice, "?audit 3").await;
+        let reply = harness.last_reply();
+        let lines = reply.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "@alice The last 3 admin actions, newest first:");
+        assert!(lines[1].ends_with(" <@1> set prefix ? in <#200>"), "{}", reply);
+        assert!(lines[3].ends_with(" <@1> set mod-log <#300> in <#200>"), "{}", reply);
+        harness.say(&alice, "?audit").await;
+        assert!(harness.last_reply().contains("<@1> made <@2> an admin in <#200>"), "{}", harness.last_reply());
+
+        harness.say(&alice, "?games add chess").await;
+        assert!(harness.last_reply().starts_with("@alice I added chess"), "{}", harness.last_reply());
+        harness.say(&alice, "?games remove chess").await;
+        let mirrored = harness.chat.sent_in(MOD_LOG).into_iter().map(|message| message.content).collect::<Vec<_>>();
+        assert_eq!(mirrored.len(), 5, "{:?}", mirrored);
+        assert!(mirrored[3].ends_with(" <@1> added the game chess in <#200>"), "{}", mirrored[3]);
+        assert!(mirrored[4].ends_with(" <@1> removed the game chess in <#200>"), "{}", mirrored[4]);
+
+        // the record stays when the new admin unregisters
+        harness.say(&bob, "!register").await;
+        harness.say(&bob, "?unregister").await;
+        let entries = harness.store.get_audit_entries(GUILD_ID.get(), 10).await.unwrap();
+        assert_eq!(entries.len(), 6);
+        assert_eq!(entries[5].target_discord_id, Some(BOB as i64));
+        assert_eq!(entries[5].discord_channel_id, Some(CHANNEL_ID.get() as i64));
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn only_admins_can_read_the_audit_log() {
+    for harness in Harness::all().await {
+        let bob = harness.user(BOB, "bob");
+        harness.say(&bob, "!register").await;
+
+        harness.say(&bob, "!audit").await;
+        assert!(!harness.last_reply().contains("admin actions"), "{}", harness.last_reply());
+        harness.say(&bob, "!server-settings mod-log <#300>").await;
+        assert!(harness.store.get_guild_settings(GUILD_ID.get()).await.unwrap().is_none_or(|settings| settings.mod_log_channel_id.is_none()));
+
+        harness.finish().await;
+    }
+}
@@ -0,0 +1,66 @@
+use crate::tests::{Harness, CHANNEL_ID, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+
+const DATABASE_TROUBLE: &str = "I'm having database trouble right now, try again later.";
+
+#[tokio::test]
+async fn nobody_gets_past_the_blacklist_when_it_cant_be_read() {
+    let Some(harness) = Harness::postgres().await else {
+        return;
+    };
+    let alice = harness.user(ALICE, "alice");
+    let bob = harness.user(BOB, "bob");
+    harness.say(&alice, "!register").await;
+    harness.say(&bob, "!register").await;
+    harness.say(&bob, "!game-notification-on").await;
+    harness.say(&alice, "!any-gamers").await;
+    assert_eq!(harness.last_reply(), "@alice 1 notified");
+    let summary = harness.chat.sent_in(CHANNEL_ID).into_iter().find(|message| message.content.contains("is looking for a stack")).expect("No summary message");
+    let lobby_id = harness.store.get_latest_child_for_channel(harness.chat.dms_to(bob.id)[0].channel_id.get()).await.unwrap().lobby_id.unwrap();
+
+    // everything but the blacklist still works
+    let pool = &harness.database.as_ref().unwrap().pool;
+    sqlx::query("ALTER TABLE blacklisted_users RENAME TO blacklisted_users_gone").execute(pool).await.unwrap();
+
+    harness.say(&alice, "!any-gamers").await;
+    assert_eq!(harness.last_reply(), format!("@alice {DATABASE_TROUBLE}"));
+    harness.say(&bob, "!game-notification-off").await;
+    harness.say(&bob, "!game-notification-on").await;
+    assert_eq!(harness.last_reply(), format!("@bob {DATABASE_TROUBLE}"));
+
+    harness.click(&bob, summary.id, &format!("lobby-join:{lobby_id}")).await;
+    assert_eq!(harness.chat.interaction_replies().last().map(String::as_str), Some(DATABASE_TROUBLE));
+    harness.react(&bob, harness.chat.dms_to(bob.id)[0].id, "👍").await;
+    assert!(harness.store.get_lobby_members(lobby_id).await.unwrap().is_empty());
+
+    harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+    harness.say_mentioning(&alice, "!unblacklist <@2>", &[&bob]).await;
+    assert_eq!(harness.last_reply(), format!("@alice {DATABASE_TROUBLE}"));
+
+    harness.finish().await;
+}
+
+#[tokio::test]
+async fn blacklisted_users_can_unregister_but_stay_blacklisted() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        harness.say(&alice, "!register").await;
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+        harness.say(&bob, "!register").await;
+        harness.say_mentioning(&alice, "!blacklist <@2>", &[&bob]).await;
+
+        harness.say(&bob, "!unregister").await;
+        assert_eq!(harness.last_reply(), "@bob I deleted all of your data, check your dms for the details.");
+        assert!(harness.store.is_user_blacklisted(Some(GUILD_ID.get()), BOB).await.unwrap());
+
+        // and they can't come back
+        harness.say(&bob, "!register").await;
+        assert_eq!(harness.last_reply(), "@bob I deleted all of your data, check your dms for the details.");
+        assert!(harness.store.get_user(BOB).await.is_err());
+
+        harness.finish().await;
+    }
+}
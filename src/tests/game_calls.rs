@@ -0,0 +1,203 @@
+use std::sync::Arc;
+use serenity::async_trait;
+use crate::chat::ChatBackend;
+use crate::game_calls::{Classification, GameCallClassifier, GameCalls};
+use crate::openai::parse_game_call;
+use crate::tests::{Harness, CHANNEL_ID, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+
+// what the keyword classifier charges for each message
+const TOKENS: i64 = 40;
+
+/// Stands in for the language model, "anyone up for" is a call for the game named in the message if there is one
+struct KeywordClassifier;
+
+#[async_trait]
+impl GameCallClassifier for KeywordClassifier {
+    async fn classify(&self, message: &str, games: &[String]) -> anyhow::Result<Classification> {
+        if !message.contains("anyone up for") {
+            return Ok(Classification { game_call: None, tokens: TOKENS });
+        }
+        let answer = games.iter().find(|game| message.contains(game.as_str())).cloned().unwrap_or_else(|| "ANY".to_string());
+        Ok(Classification { game_call: parse_game_call(&answer, games), tokens: TOKENS })
+    }
+}
+
+async fn with_classifier_and_budget(harness: &Harness, daily_token_budget: i64) {
+    let game_calls = GameCalls { classifier: Arc::new(KeywordClassifier), daily_token_budget };
+    harness.chat.data().write().await.insert::<GameCalls>(Arc::new(game_calls));
+}
+
+/// The classifier with plenty of tokens, and game call offers turned on in the guild
+async fn with_classifier(harness: &Harness) {
+    with_classifier_and_budget(harness, 1000).await;
+    harness.store.upsert_game_call_offers_for_guild(GUILD_ID.get(), true).await.unwrap();
+}
+
+#[tokio::test]
+async fn the_author_of_a_game_call_can_notify_the_subscribers() {
+    for harness in Harness::all().await {
+        with_classifier(&harness).await;
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        for user in [&alice, &bob] {
+            harness.say(user, "!register").await;
+        }
+        harness.say(&bob, "!game-notification-on").await;
+
+        harness.say(&alice, "anyone up for a game in 20?").await;
+        let offer = harness.chat.sent_in(CHANNEL_ID).pop().unwrap();
+        assert_eq!(offer.content, "@alice Looking for a game? 1 person signed up for notifications here, I can let them know like !any-gamers would.");
+        assert!(harness.chat.dms_to(bob.id).is_empty());
+
+        // only alice can send them
+        let button = format!("game-call:1::{}", offer.replied_to.unwrap().get());
+        harness.click(&bob, offer.id, &button).await;
+        assert_eq!(harness.chat.interaction_replies(), ["Only the person who was looking for a game can send the notifications."]);
+        assert!(harness.chat.dms_to(bob.id).is_empty());
+
+        harness.click(&alice, offer.id, &button).await;
+        assert_eq!(harness.chat.interaction_replies().last().unwrap(), "@alice 1 notified");
+        let dms = harness.chat.dms_to(bob.id);
+        assert_eq!(dms.len(), 1);
+        assert!(dms[0].content.starts_with("@alice is trying to get a stack of 5 in #general."), "{}", dms[0].content);
+        // what alice wrote is the description of the stack
+        assert!(dms[0].content.contains("anyone up for a game in 20?"), "{}", dms[0].content);
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn a_game_call_for_a_game_only_counts_its_subscribers() {
+    for harness in Harness::all().await {
+        with_classifier(&harness).await;
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        for user in [&alice, &bob] {
+            harness.say(user, "!register").await;
+        }
+        let game = harness.store.create_game(GUILD_ID.get(), "dota", &[], 5, None).await.unwrap();
+        harness.say(&bob, "!game-notification-on dota").await;
+
+        harness.say(&alice, "anyone up for dota?").await;
+        assert_eq!(harness.last_reply(), "@alice Looking for a game of dota? 1 person signed up for notifications here, I can let them know like !any-gamers dota would.");
+        let offer = harness.chat.sent_in(CHANNEL_ID).pop().unwrap();
+        harness.click(&alice, offer.id, &format!("game-call:1:{}:{}", game.id, offer.replied_to.unwrap().get())).await;
+        assert_eq!(harness.chat.interaction_replies().last().unwrap(), "@alice 1 notified");
+        let dm = &harness.chat.dms_to(bob.id)[0].content;
+        assert!(dm.contains("dota") && dm.contains("anyone up for dota?"), "{}", dm);
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn offers_from_before_the_message_was_in_the_button_still_work() {
+    for harness in Harness::all().await {
+        with_classifier(&harness).await;
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        for user in [&alice, &bob] {
+            harness.say(user, "!register").await;
+        }
+        harness.say(&bob, "!game-notification-on").await;
+
+        harness.say(&alice, "anyone up for a game?").await;
+        let offer = harness.chat.sent_in(CHANNEL_ID).pop().unwrap();
+        harness.click(&alice, offer.id, "game-call:1").await;
+        assert_eq!(harness.chat.interaction_replies().last().unwrap(), "@alice 1 notified");
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn other_messages_are_left_alone() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        harness.say(&bob, "!register").await;
+        harness.say(&bob, "!game-notification-on").await;
+
+        // no language model configured
+        harness.say(&alice, "anyone up for a game?").await;
+        with_classifier(&harness).await;
+        // alice isn't registered
+        harness.say(&alice, "anyone up for a game?").await;
+        harness.say(&alice, "!register").await;
+        let sent = harness.chat.sent().len();
+        harness.say(&alice, "good game everyone").await;
+        // bob is the only subscriber, there is nobody to notify for him
+        harness.say(&bob, "anyone up for a game?").await;
+        assert_eq!(harness.chat.sent().len(), sent);
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn game_call_offers_are_off_until_an_admin_turns_them_on() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        for user in [&alice, &bob] {
+            harness.say(user, "!register").await;
+        }
+        harness.say(&bob, "!game-notification-on").await;
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+
+        harness.say(&alice, "!server-settings game-calls on").await;
+        assert_eq!(harness.last_reply(), "@alice Game call offers need a language model, the bot's owner has to set OPENAI_API_KEY");
+        with_classifier_and_budget(&harness, 1000).await;
+        let sent = harness.chat.sent().len();
+        harness.say(&alice, "anyone up for a game?").await;
+        assert_eq!(harness.chat.sent().len(), sent);
+
+        harness.say(&alice, "!server-settings game-calls on").await;
+        assert_eq!(harness.last_reply(), "@alice Messages that look like someone looking for players will now get an offer to notify the channel's subscribers");
+        harness.say(&alice, "anyone up for a game?").await;
+        assert!(harness.last_reply().starts_with("@alice Looking for a game?"), "{}", harness.last_reply());
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn classifying_counts_against_the_daily_budget() {
+    for harness in Harness::all().await {
+        with_classifier(&harness).await;
+        with_classifier_and_budget(&harness, TOKENS * 2).await;
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        for user in [&alice, &bob] {
+            harness.say(user, "!register").await;
+        }
+        harness.say(&bob, "!game-notification-on").await;
+        let today = chrono::offset::Utc::now().date_naive();
+
+        harness.say(&alice, "good game everyone").await;
+        harness.say(&alice, "anyone up for a game?").await;
+        assert!(harness.last_reply().starts_with("@alice Looking for a game?"), "{}", harness.last_reply());
+        assert_eq!(harness.store.get_language_model_tokens(GUILD_ID.get(), today).await.unwrap(), TOKENS * 2);
+
+        // the guild is out of tokens for today
+        let sent = harness.chat.sent().len();
+        harness.say(&alice, "anyone up for a game?").await;
+        assert_eq!(harness.chat.sent().len(), sent);
+        assert_eq!(harness.store.get_language_model_tokens(GUILD_ID.get(), today).await.unwrap(), TOKENS * 2);
+
+        harness.finish().await;
+    }
+}
+
+#[test]
+fn answers_other_than_a_game_are_no() {
+    let games = ["dota".to_string(), "aoe2".to_string()];
+    assert_eq!(parse_game_call("Dota.", &games).and_then(|call| call.game).as_deref(), Some("dota"));
+    assert!(parse_game_call(" ANY ", &games).is_some_and(|call| call.game.is_none()));
+    assert!(parse_game_call("NO", &games).is_none());
+    assert!(parse_game_call("Sure, dota!", &games).is_none());
+}
@@ -0,0 +1,33 @@
+use crate::tests::{Harness, CHANNEL_ID, GUILD_ID};
+
+const ALICE: u64 = 1;
+
+#[tokio::test]
+async fn help_is_split_into_messages_discord_accepts() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        harness.say(&alice, "!register").await;
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+        harness.say(&alice, "!server-settings help-message Ask in #lfg if nobody answers").await;
+
+        let sent_before = harness.chat.sent_in(CHANNEL_ID).len();
+        harness.say(&alice, "!help").await;
+        let help = harness.chat.sent_in(CHANNEL_ID).into_iter().skip(sent_before).map(|message| message.content).collect::<Vec<_>>();
+        assert!(help.len() > 1, "{:?}", help);
+        assert!(help[0].starts_with("Ask in #lfg if nobody answers\n\nHere are my commands:"), "{}", help[0]);
+        assert!(help.last().unwrap().ends_with("!audit [n]: show the last n admin actions in this server, 10 by default"), "{:?}", help.last());
+
+        harness.say(&alice, "!server-settings language fr").await;
+        let sent_before = harness.chat.sent_in(CHANNEL_ID).len();
+        harness.say(&alice, "!help").await;
+        let help = harness.chat.sent_in(CHANNEL_ID).into_iter().skip(sent_before).map(|message| message.content).collect::<Vec<_>>();
+        assert!(help.len() > 1, "{:?}", help);
+        assert!(help[0].starts_with("Ask in #lfg if nobody answers\n\nVoici mes commandes :"), "{}", help[0]);
+        assert!(help.last().unwrap().ends_with("!audit [n]: affiche les n dernières actions d'admin dans ce serveur, 10 par défaut"), "{:?}", help.last());
+
+        harness.say(&alice, "!help any-gamers").await;
+        assert!(harness.last_reply().starts_with("!any-gamers: envoie un mp"), "{}", harness.last_reply());
+
+        harness.finish().await;
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use std::time::Duration;
+use serenity::async_trait;
+use crate::chat::ChatBackend;
+use crate::invitations::{Invitation, InvitationRequest, InvitationWriter, Invitations};
+use crate::tests::{Harness, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+const CAROL: u64 = 3;
+
+/// Stands in for the language model
+#[derive(Clone, Copy)]
+enum StubWriter {
+    Writes(i64),
+    Fails,
+    TakesLonger,
+}
+
+#[async_trait]
+impl InvitationWriter for StubWriter {
+    async fn write(&self, request: &InvitationRequest<'_>) -> anyhow::Result<Invitation> {
+        let theme = request.theme.map(|theme| format!(" as {theme}")).unwrap_or_default();
+        let text = format!("{} wants you for {}{theme}, {}/{} in!", request.organizer, request.game.unwrap_or("a game"), request.roster.len(), request.target_size.unwrap_or_default());
+        match self {
+            StubWriter::Writes(tokens) => Ok(Invitation { text, tokens: *tokens }),
+            StubWriter::Fails => anyhow::bail!("The model is down"),
+            StubWriter::TakesLonger => {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(Invitation { text, tokens: 0 })
+            }
+        }
+    }
+}
+
+async fn with_writer(harness: &Harness, writer: StubWriter, daily_token_budget: i64) {
+    let invitations = Invitations { writer: Arc::new(writer), timeout: Duration::from_millis(100), daily_token_budget };
+    harness.chat.data().write().await.insert::<Invitations>(Arc::new(invitations));
+}
+
+#[tokio::test]
+async fn subscribers_get_the_invitation_in_the_guild_language() {
+    for harness in Harness::all().await {
+        with_writer(&harness, StubWriter::Writes(120), 1000).await;
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        let carol = harness.user(CAROL, "carol");
+        for user in [&alice, &bob, &carol] {
+            harness.say(user, "!register").await;
+            harness.say(user, "!game-notification-on").await;
+        }
+        harness.say(&carol, "!language fr").await;
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+
+        harness.say(&alice, "!server-settings invitations on").await;
+        assert_eq!(harness.last_reply(), "@alice Game notifications will now be written as personalized invitations, when the language model is available");
+        harness.say(&alice, "!server-settings theme a pirate").await;
+        harness.say(&alice, "!any-gamers size=3").await;
+        let dm = harness.chat.dms_to(bob.id).pop().unwrap();
+        assert!(dm.content.starts_with("alice wants you for a game as a pirate, 1/3 in!\n\n("), "{}", dm.content);
+        // the invitation is in english, carol gets the usual text in french
+        let dm = harness.chat.dms_to(carol.id).pop().unwrap();
+        assert!(dm.content.starts_with("@alice cherche un groupe de 3 dans #general."), "{}", dm.content);
+        let today = chrono::offset::Utc::now().date_naive();
+        assert_eq!(harness.store.get_language_model_tokens(GUILD_ID.get(), today).await.unwrap(), 120);
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn notifications_fall_back_to_the_usual_text() {
+    // the guild already used 500 tokens today, which is all of them for the writer that works. The others are charged
+    // for the longest invitation since the model may have answered anyway
+    for (writer, daily_token_budget, tokens) in [(StubWriter::Writes(120), 500, 500), (StubWriter::Fails, 1000, 650), (StubWriter::TakesLonger, 1000, 650)] {
+        for harness in Harness::all().await {
+            let alice = harness.user(ALICE, "alice");
+            let bob = harness.user(BOB, "bob");
+            harness.say(&alice, "!register").await;
+            harness.say(&bob, "!register").await;
+            harness.say(&bob, "!game-notification-on").await;
+            harness.store.upsert_personalized_invitations_for_guild(GUILD_ID.get(), true).await.unwrap();
+            let today = chrono::offset::Utc::now().date_naive();
+            harness.store.add_language_model_tokens(GUILD_ID.get(), today, 500).await.unwrap();
+            with_writer(&harness, writer, daily_token_budget).await;
+
+            harness.say(&alice, "!any-gamers size=2").await;
+            assert_eq!(harness.last_reply(), "@alice 1 notified");
+            let dm = harness.chat.dms_to(bob.id).pop().unwrap();
+            assert!(dm.content.starts_with("@alice is trying to get a stack of 2 in #general."), "{}", dm.content);
+            assert_eq!(harness.store.get_language_model_tokens(GUILD_ID.get(), today).await.unwrap(), tokens);
+
+            harness.finish().await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn invitations_need_a_language_model() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        harness.say(&alice, "!register").await;
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+
+        harness.say(&alice, "!server-settings invitations on").await;
+        assert_eq!(harness.last_reply(), "@alice Personalized invitations need a language model, the bot's owner has to set OPENAI_API_KEY");
+        assert!(!harness.store.get_guild_settings(GUILD_ID.get()).await.unwrap().unwrap_or_default().personalized_invitations);
+
+        harness.finish().await;
+    }
+}
@@ -0,0 +1,130 @@
+use crate::tests::{Harness, CHANNEL_ID, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+
+#[tokio::test]
+async fn reacting_to_a_notification_fills_the_stack() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+
+        harness.say(&alice, "!register").await;
+        assert_eq!(harness.last_reply(), "@alice I have successfully registered you, or you are already registered!");
+        harness.say(&bob, "!register").await;
+        harness.say(&bob, "!game-notification-on").await;
+        assert_eq!(harness.last_reply(), "@bob You are now signed up for game search notifications in #general");
+
+        harness.say(&alice, "!any-gamers size=2 ranked").await;
+        assert_eq!(harness.last_reply(), "@alice 1 notified");
+        let summary = harness.chat.sent_in(CHANNEL_ID).into_iter().find(|message| message.content.contains("is looking for a stack")).expect("No summary message");
+        assert!(summary.content.starts_with("<@1> is looking for a stack (1/2)."), "{}", summary.content);
+        // the organizer doesn't get notified about their own stack
+        assert!(harness.chat.dms_to(alice.id).is_empty());
+        let dms = harness.chat.dms_to(bob.id);
+        assert_eq!(dms.len(), 1);
+        assert!(dms[0].content.starts_with("@alice is trying to get a stack of 2 in #general."), "{}", dms[0].content);
+
+        harness.react(&bob, dms[0].id, "👍").await;
+        let summary = harness.chat.message(summary.id).unwrap();
+        assert_eq!(summary.edits, 1);
+        assert!(summary.content.starts_with("<@1>'s stack is full (2/2)!"), "{}", summary.content);
+        assert!(summary.content.contains("<@2>"), "{}", summary.content);
+        let full = harness.chat.sent_in(CHANNEL_ID).pop().unwrap();
+        assert_eq!(full.content, "<@1> <@2> your stack is full, time to play!");
+        assert_eq!(full.replied_to, Some(summary.id));
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn declining_leaves_the_stack_open_and_the_cooldown_applies() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        harness.say(&alice, "!register").await;
+        harness.say(&bob, "!register").await;
+        harness.say(&bob, "!game-notification-on").await;
+
+        harness.say(&alice, "!any-gamers size=2").await;
+        let dm = harness.chat.dms_to(bob.id).pop().unwrap();
+        harness.react(&bob, dm.id, "❌").await;
+        let summary = harness.chat.sent_in(CHANNEL_ID).into_iter().find(|message| message.edits > 0).expect("Summary wasn't edited");
+        assert!(summary.content.starts_with("<@1> is looking for a stack (1/2)."), "{}", summary.content);
+        assert!(summary.content.contains("<@2>"), "{}", summary.content);
+        assert!(!harness.last_reply().contains("your stack is full"));
+
+        // bob was just notified, so the second stack skips him
+        harness.say(&alice, "!any-gamers").await;
+        assert_eq!(harness.last_reply(), "@alice 0 notified, 1 skipped (cooldown)");
+        assert_eq!(harness.chat.dms_to(bob.id).len(), 1);
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn everyone_is_notified_and_closed_dms_are_reported() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        harness.say(&alice, "!register").await;
+        let subscribers = (10..30).map(|id| harness.user(id, &format!("user{}", id))).collect::<Vec<_>>();
+        for subscriber in &subscribers {
+            harness.say(subscriber, "!register").await;
+            harness.say(subscriber, "!game-notification-on").await;
+        }
+        harness.chat.close_dms(subscribers[0].id);
+
+        harness.say(&alice, "!any-gamers").await;
+        assert_eq!(harness.last_reply(), "@alice 19 notified, 1 failed (dms closed)");
+        assert!(subscribers[1..].iter().all(|subscriber| harness.chat.dms_to(subscriber.id).len() == 1));
+        // whoever couldn't be reached isn't in their cooldown, everyone else is
+        assert!(harness.store.get_pings_for_user(10).await.unwrap()[0].last_notified.is_none());
+        assert!(harness.store.get_pings_for_user(11).await.unwrap()[0].last_notified.is_some());
+        harness.say(&alice, "!any-gamers").await;
+        assert_eq!(harness.last_reply(), "@alice 0 notified, 19 skipped (cooldown), 1 failed (dms closed)");
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn unregistered_users_cant_start_a_stack() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        harness.say(&alice, "!any-gamers").await;
+        assert_eq!(harness.last_reply(), "@alice You aren't registered, you can register using !register");
+        assert_eq!(harness.chat.sent().len(), 1);
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn dm_buttons_only_count_the_blacklist_of_the_stacks_server() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        harness.say(&alice, "!register").await;
+        harness.say(&bob, "!register").await;
+        harness.say(&bob, "!game-notification-on").await;
+        // another server doesn't want bob, alice's still does
+        harness.store.create_blacklisted_user(101, BOB).await.unwrap();
+
+        harness.say(&alice, "!any-gamers size=3").await;
+        let dm = harness.chat.dms_to(bob.id).pop().unwrap();
+        let lobby_id = harness.store.get_latest_child_for_channel(dm.channel_id.get()).await.unwrap().lobby_id.unwrap();
+        harness.click_in_dm(&bob, dm.id, &format!("lobby-join:{lobby_id}")).await;
+        assert_eq!(harness.chat.interaction_replies().last().unwrap(), "You joined <@1>'s stack!");
+        assert!(harness.store.get_lobby_members(lobby_id).await.unwrap().iter().any(|member| member.discord_user_id == BOB as i64 && member.joined));
+
+        harness.store.create_blacklisted_user(GUILD_ID.get(), BOB).await.unwrap();
+        let replies = harness.chat.interaction_replies().len();
+        harness.click_in_dm(&bob, dm.id, &format!("lobby-decline:{lobby_id}")).await;
+        assert_eq!(harness.chat.interaction_replies().len(), replies);
+        assert!(harness.store.get_lobby_members(lobby_id).await.unwrap().iter().any(|member| member.discord_user_id == BOB as i64 && member.joined));
+
+        harness.finish().await;
+    }
+}
@@ -0,0 +1,189 @@
+//! End to end tests that run the handlers against `FakeChat`. Every scenario runs against `InMemoryStore`, and also
+//! against a throwaway postgres database when `TEST_DATABASE_URL` is set to a server to create it on, e.g.
+//! `TEST_DATABASE_URL=postgres://postgres@localhost/postgres cargo test`
+
+mod admins;
+mod audit;
+mod blacklist;
+mod game_calls;
+mod help;
+mod invitations;
+mod lobbies;
+mod parsing;
+mod rate_limits;
+mod scheduled_games;
+mod stats;
+mod store;
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use serenity::all::{ChannelId, ComponentInteraction, GuildId, Message, MessageId, ReactionType, User, UserId};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use crate::chat::ChatBackend;
+use crate::chat::fake::FakeChat;
+use crate::commands::{handle_message, route_component};
+use crate::permissions::Permissions;
+use crate::reactions::handle_reaction_add;
+use crate::store::{InMemoryStore, PgStore, Store};
+
+pub(crate) const GUILD_ID: GuildId = GuildId::new(100);
+pub(crate) const CHANNEL_ID: ChannelId = ChannelId::new(200);
+pub(crate) const CHANNEL_NAME: &str = "general";
+
+static DATABASE_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// A database created for a single test from `TEST_DATABASE_URL`, with every migration applied
+pub(crate) struct TestDatabase {
+    pub(crate) pool: sqlx::PgPool,
+    server: sqlx::PgPool,
+    name: String,
+}
+
+impl TestDatabase {
+    /// None when `TEST_DATABASE_URL` isn't set
+    pub(crate) async fn create() -> Option<Self> {
+        Self::create_before(i64::MAX).await
+    }
+
+    /// Only applies the migrations older than `version`, to test data written by older versions of the bot
+    pub(crate) async fn create_before(version: i64) -> Option<Self> {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            return None;
+        };
+        let server = PgPoolOptions::new().max_connections(1).connect(&url).await.expect("Unable to connect to TEST_DATABASE_URL");
+        let name = format!("wololo_test_{}_{}", std::process::id(), DATABASE_COUNT.fetch_add(1, Ordering::SeqCst));
+        sqlx::query(&format!("DROP DATABASE IF EXISTS {name}")).execute(&server).await.expect("Unable to drop old test database");
+        sqlx::query(&format!("CREATE DATABASE {name}")).execute(&server).await.expect("Unable to create test database");
+        let options = PgConnectOptions::from_str(&url).expect("TEST_DATABASE_URL isn't a postgres url").database(&name);
+        let pool = PgPoolOptions::new().connect_with(options).await.expect("Unable to connect to test database");
+        let mut migrator = sqlx::migrate!();
+        migrator.migrations = migrator.migrations.iter().filter(|migration| migration.version < version).cloned().collect();
+        migrator.run(&pool).await.expect("Unable to run migrations");
+        Some(TestDatabase { pool, server, name })
+    }
+
+    /// Databases of tests that panicked are left behind, they are dropped the next time a test gets the same name
+    pub(crate) async fn remove(self) {
+        self.pool.close().await;
+        if let Err(e) = sqlx::query(&format!("DROP DATABASE {} WITH (FORCE)", self.name)).execute(&self.server).await {
+            eprintln!("Unable to drop test database {}: {:?}", self.name, e);
+        }
+    }
+}
+
+/// Every store the tests can run against, the in-memory one always and postgres when `TEST_DATABASE_URL` is set
+pub(crate) async fn stores() -> Vec<(Arc<dyn Store>, Option<TestDatabase>)> {
+    let mut stores: Vec<(Arc<dyn Store>, Option<TestDatabase>)> = vec![(Arc::new(InMemoryStore::new()), None)];
+    if let Some(database) = TestDatabase::create().await {
+        stores.push((Arc::new(PgStore::new(database.pool.clone())), Some(database)));
+    }
+    stores
+}
+
+/// The bot in a single guild with a single channel, talking to `FakeChat`
+pub(crate) struct Harness {
+    pub(crate) chat: FakeChat,
+    pub(crate) store: Arc<dyn Store>,
+    database: Option<TestDatabase>,
+}
+
+impl Harness {
+    /// One harness per store, see `stores`
+    pub(crate) async fn all() -> Vec<Self> {
+        stores().await.into_iter().map(|(store, database)| Self::new(store, database)).collect()
+    }
+
+    /// Only the postgres harness, for tests that break its database on purpose. None when `TEST_DATABASE_URL` isn't set
+    pub(crate) async fn postgres() -> Option<Self> {
+        let database = TestDatabase::create().await?;
+        Some(Self::new(Arc::new(PgStore::new(database.pool.clone())), Some(database)))
+    }
+
+    fn new(store: Arc<dyn Store>, database: Option<TestDatabase>) -> Self {
+        let chat = FakeChat::new(Permissions::default());
+        chat.set_channel_name(CHANNEL_ID, CHANNEL_NAME);
+        Harness { chat, store, database }
+    }
+
+    pub(crate) fn user(&self, id: u64, name: &str) -> User {
+        let mut user = User::default();
+        user.id = UserId::new(id);
+        user.name = name.to_string();
+        self.chat.set_user_name(user.id, name);
+        user
+    }
+
+    /// Sends the message to the guild's channel
+    pub(crate) async fn say(&self, author: &User, content: &str) {
+        self.say_mentioning(author, content, &[]).await;
+    }
+
+    /// Sends the message to the guild's channel with the users discord would have resolved from its `<@id>` mentions
+    pub(crate) async fn say_mentioning(&self, author: &User, content: &str, mentions: &[&User]) {
+        self.say_in(GUILD_ID, CHANNEL_ID, author, content, mentions).await;
+    }
+
+    /// Sends the message to a channel of another guild the bot is in
+    pub(crate) async fn say_in(&self, guild_id: GuildId, channel_id: ChannelId, author: &User, content: &str, mentions: &[&User]) {
+        let mut msg = Message::default();
+        msg.id = self.chat.next_message_id();
+        msg.channel_id = channel_id;
+        msg.guild_id = Some(guild_id);
+        msg.author = author.clone();
+        msg.content = content.to_string();
+        msg.mentions = mentions.iter().map(|user| (*user).clone()).collect();
+        self.chat.receive(&msg);
+        handle_message(&self.chat, self.store.as_ref(), &msg).await;
+    }
+
+    pub(crate) async fn react(&self, user: &User, message_id: MessageId, emoji: &str) {
+        handle_reaction_add(&self.chat, self.store.as_ref(), message_id, Some(user.id), &ReactionType::Unicode(emoji.to_string())).await;
+    }
+
+    /// Clicks a button with the given id on one of the bot's messages in the guild's channel, it goes to the same
+    /// handler as in the bot
+    pub(crate) async fn click(&self, user: &User, message_id: MessageId, custom_id: &str) {
+        self.click_in(Some(GUILD_ID), CHANNEL_ID, user, message_id, custom_id).await;
+    }
+
+    /// Clicks a button on one of the bot's dms to the user, discord doesn't say which guild those are about
+    pub(crate) async fn click_in_dm(&self, user: &User, message_id: MessageId, custom_id: &str) {
+        let channel_id = self.chat.dm_channel(user.id).await.expect("No dm channel");
+        self.click_in(None, channel_id, user, message_id, custom_id).await;
+    }
+
+    async fn click_in(&self, guild_id: Option<GuildId>, channel_id: ChannelId, user: &User, message_id: MessageId, custom_id: &str) {
+        let mut message = Message::default();
+        message.id = message_id;
+        message.channel_id = channel_id;
+        let interaction = serde_json::json!({
+            "id": self.chat.next_message_id().get().to_string(),
+            "application_id": "1",
+            "type": 3,
+            "data": { "custom_id": custom_id, "component_type": 2 },
+            "guild_id": guild_id.map(|guild_id| guild_id.get().to_string()),
+            "channel_id": channel_id.get().to_string(),
+            "user": user,
+            "token": format!("token-{}", message_id.get()),
+            "version": 1,
+            "message": message,
+            "locale": "en-US",
+            "entitlements": [],
+            "attachment_size_limit": 0,
+        });
+        let interaction: ComponentInteraction = serde_json::from_value(interaction).expect("Invalid component interaction");
+        route_component(&self.chat, &interaction, self.store.as_ref()).await;
+    }
+
+    /// What the bot last said in the guild's channel
+    pub(crate) fn last_reply(&self) -> String {
+        self.chat.sent_in(CHANNEL_ID).last().map(|message| message.content.clone()).unwrap_or_default()
+    }
+
+    pub(crate) async fn finish(self) {
+        if let Some(database) = self.database {
+            database.remove().await;
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use chrono::Duration;
+use crate::parsing::parse_duration;
+use crate::tests::{Harness, GUILD_ID};
+
+const ALICE: u64 = 1;
+
+#[test]
+fn durations_over_a_year_are_rejected() {
+    assert_eq!(parse_duration("1h30m"), Some(Duration::minutes(90)));
+    assert_eq!(parse_duration("365d"), Some(Duration::days(365)));
+    assert_eq!(parse_duration("365d1s"), None);
+    assert_eq!(parse_duration("90000000000d90000000000d"), None);
+    assert_eq!(parse_duration("100000000000000"), None);
+}
+
+#[tokio::test]
+async fn durations_too_long_for_a_setting_are_not_understood() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        harness.say(&alice, "!register").await;
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+
+        harness.say(&alice, "!channel-cooldown 90000000000d90000000000d").await;
+        assert_eq!(harness.last_reply(), "@alice I don't understand \"90000000000d90000000000d\", try something like 30m or 1h");
+        harness.say(&alice, "!notification-gap 400d").await;
+        assert_eq!(harness.last_reply(), "@alice I don't understand \"400d\", try something like 30m, 1h or off");
+        harness.say(&alice, "!server-settings cooldown 400d").await;
+        assert!(harness.last_reply().starts_with("@alice I don't understand \"400d\""), "{}", harness.last_reply());
+
+        harness.finish().await;
+    }
+}
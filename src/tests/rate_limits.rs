@@ -0,0 +1,96 @@
+use crate::constants;
+use crate::tests::{Harness, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+const CAROL: u64 = 3;
+
+#[tokio::test]
+async fn organizers_can_only_start_so_many_stacks() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        let carol = harness.user(CAROL, "carol");
+        for user in [&alice, &bob, &carol] {
+            harness.say(user, "!register").await;
+        }
+        harness.store.create_admin_user(GUILD_ID.get(), CAROL).await.unwrap();
+
+        for _ in 0..constants::DEFAULT_CALL_LIMIT {
+            harness.say(&alice, "!any-gamers").await;
+            assert_eq!(harness.last_reply(), "@alice 0 notified");
+        }
+        harness.say(&alice, "!any-gamers").await;
+        let reply = harness.last_reply();
+        assert!(reply.starts_with("@alice You already started 3 stack(s) in the last 30m, you can start another one in "), "{}", reply);
+
+        // admins have no limit, and set everyone else's
+        for _ in 0..=constants::DEFAULT_CALL_LIMIT {
+            harness.say(&carol, "!any-gamers").await;
+            assert_eq!(harness.last_reply(), "@carol 0 notified");
+        }
+        harness.say(&carol, "!server-settings call-limit 5 1h").await;
+        assert_eq!(harness.last_reply(), "@carol Everyone but admins can now start 5 stack(s) every 1h");
+        harness.say(&alice, "!any-gamers").await;
+        assert_eq!(harness.last_reply(), "@alice 0 notified");
+        harness.say(&carol, "!server-settings call-limit lots").await;
+        assert!(harness.last_reply().starts_with("@carol \"lots\" isn't a limit"), "{}", harness.last_reply());
+        harness.say(&carol, "!server-settings call-limit 1 100000000d").await;
+        assert_eq!(harness.last_reply(), "@carol \"1 100000000d\" isn't a limit, tell me how many stacks per how long (up to 7d), e.g. 3 30m, or off to go back to the default");
+        harness.say(&carol, "!server-settings call-limit 1 8d").await;
+        assert!(harness.last_reply().starts_with("@carol \"1 8d\" isn't a limit"), "{}", harness.last_reply());
+
+        // a window saved before they were capped still works
+        harness.store.upsert_call_limit_for_guild(GUILD_ID.get(), Some((1, 100_000_000 * 24 * 60 * 60))).await.unwrap();
+        harness.say(&bob, "!any-gamers").await;
+        assert_eq!(harness.last_reply(), "@bob 0 notified");
+        harness.say(&bob, "!any-gamers").await;
+        assert!(harness.last_reply().starts_with("@bob You already started 1 stack(s) in the last "), "{}", harness.last_reply());
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn nobody_gets_more_than_the_hourly_cap_of_dms() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        harness.say(&alice, "!register").await;
+        harness.say(&bob, "!register").await;
+        harness.say(&bob, "!game-notification-on").await;
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+        harness.say(&alice, "!channel-cooldown 0s").await;
+
+        for _ in 0..constants::MAX_DMS_PER_RECIPIENT_PER_HOUR {
+            harness.say(&alice, "!any-gamers").await;
+            assert_eq!(harness.last_reply(), "@alice 1 notified");
+        }
+        harness.say(&alice, "!any-gamers").await;
+        assert_eq!(harness.last_reply(), "@alice 0 notified, 1 skipped (hourly limit)");
+        assert_eq!(harness.chat.dms_to(bob.id).len(), constants::MAX_DMS_PER_RECIPIENT_PER_HOUR as usize);
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn scheduled_games_count_towards_the_call_limit() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        harness.say(&alice, "!register").await;
+
+        for _ in 0..constants::DEFAULT_CALL_LIMIT {
+            harness.say(&alice, "!schedule in 1m").await;
+            assert!(harness.last_reply().starts_with("@alice I scheduled game #"), "{}", harness.last_reply());
+        }
+        harness.say(&alice, "!schedule in 1m").await;
+        let reply = harness.last_reply();
+        assert!(reply.starts_with("@alice You already started 3 stack(s) in the last 30m, you can start another one in "), "{}", reply);
+        harness.say(&alice, "!any-gamers").await;
+        let reply = harness.last_reply();
+        assert!(reply.starts_with("@alice You already started 3 stack(s) in the last 30m, you can start another one in "), "{}", reply);
+
+        harness.finish().await;
+    }
+}
@@ -0,0 +1,79 @@
+use serenity::all::{ChannelId, GuildId};
+use crate::tests::{Harness, CHANNEL_ID, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+
+const OTHER_GUILD_ID: GuildId = GuildId::new(101);
+const OTHER_CHANNEL_ID: ChannelId = ChannelId::new(201);
+
+#[tokio::test]
+async fn admins_can_only_cancel_their_own_servers_games() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        harness.say(&alice, "!register").await;
+        harness.say(&bob, "!register").await;
+        // bob is an admin of another server, not of alice's
+        harness.store.create_admin_user(OTHER_GUILD_ID.get(), BOB).await.unwrap();
+
+        harness.say(&alice, "!schedule in 2h ranked").await;
+        assert!(harness.last_reply().starts_with("@alice I scheduled game #1 for "), "{}", harness.last_reply());
+        let scheduled_game_id = 1;
+
+        let sent_before = harness.chat.sent_in(CHANNEL_ID).len();
+        harness.say_in(OTHER_GUILD_ID, OTHER_CHANNEL_ID, &bob, &format!("!cancel-game {scheduled_game_id}"), &[]).await;
+        let reply = harness.chat.sent_in(OTHER_CHANNEL_ID).last().map(|message| message.content.clone()).unwrap_or_default();
+        assert_eq!(reply, format!("@bob I couldn't find game #{scheduled_game_id}."));
+        // nothing was announced in alice's channel
+        assert_eq!(harness.chat.sent_in(CHANNEL_ID).len(), sent_before);
+        assert!(harness.store.get_scheduled_game(scheduled_game_id).await.unwrap().cancelled_at.is_none());
+
+        harness.store.create_admin_user(GUILD_ID.get(), BOB).await.unwrap();
+        harness.say(&bob, &format!("!cancel-game {scheduled_game_id}")).await;
+        assert!(harness.store.get_scheduled_game(scheduled_game_id).await.unwrap().cancelled_at.is_some());
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn times_too_far_away_are_not_understood() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        harness.say(&alice, "!register").await;
+
+        harness.say(&alice, "!schedule in 100000000d").await;
+        assert_eq!(harness.last_reply(), "@alice I don't understand when \"in 100000000d\" is, try something like !schedule tonight 9pm ranked");
+        assert!(harness.store.get_upcoming_scheduled_games_for_channel(CHANNEL_ID.get()).await.unwrap().is_empty());
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn dm_buttons_only_count_the_blacklist_of_the_games_server() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        harness.say(&alice, "!register").await;
+        harness.say(&bob, "!register").await;
+        harness.say(&bob, "!game-notification-on").await;
+        harness.store.create_blacklisted_user(OTHER_GUILD_ID.get(), BOB).await.unwrap();
+
+        harness.say(&alice, "!schedule in 2h ranked").await;
+        let scheduled_game_id = 1;
+        let dm = harness.chat.dms_to(bob.id).pop().unwrap();
+        harness.click_in_dm(&bob, dm.id, &format!("schedule-accept:{scheduled_game_id}")).await;
+        assert!(harness.chat.interaction_replies().last().unwrap().starts_with("See you "), "{:?}", harness.chat.interaction_replies());
+        assert!(harness.store.get_rsvps_for_scheduled_game(scheduled_game_id).await.unwrap().iter().any(|rsvp| rsvp.discord_user_id == BOB as i64 && rsvp.accepted));
+
+        harness.store.create_blacklisted_user(GUILD_ID.get(), BOB).await.unwrap();
+        let replies = harness.chat.interaction_replies().len();
+        harness.click_in_dm(&bob, dm.id, &format!("schedule-decline:{scheduled_game_id}")).await;
+        assert_eq!(harness.chat.interaction_replies().len(), replies);
+        assert!(harness.store.get_rsvps_for_scheduled_game(scheduled_game_id).await.unwrap().iter().any(|rsvp| rsvp.discord_user_id == BOB as i64 && rsvp.accepted));
+
+        harness.finish().await;
+    }
+}
@@ -0,0 +1,73 @@
+use crate::tests::{Harness, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+const CAROL: u64 = 3;
+
+#[tokio::test]
+async fn stats_show_who_calls_and_who_joins() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        let carol = harness.user(CAROL, "carol");
+        for user in [&alice, &bob, &carol] {
+            harness.say(user, "!register").await;
+        }
+        harness.say(&bob, "!game-notification-on").await;
+        harness.say(&carol, "!game-notification-on").await;
+        harness.store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+        harness.say(&alice, "!channel-cooldown 0s").await;
+
+        harness.say(&alice, "!any-gamers").await;
+        harness.react(&bob, harness.chat.dms_to(bob.id)[0].id, "👍").await;
+        harness.react(&carol, harness.chat.dms_to(carol.id)[0].id, "👍").await;
+        harness.say(&alice, "!any-gamers").await;
+        harness.react(&bob, harness.chat.dms_to(bob.id)[1].id, "👍").await;
+        harness.react(&carol, harness.chat.dms_to(carol.id)[1].id, "❌").await;
+
+        harness.say(&carol, "!stats").await;
+        let reply = harness.last_reply();
+        let lines = reply.lines().collect::<Vec<_>>();
+        assert_eq!(lines[..6], [
+            "@carol Over the last 30 days:",
+            "Most active organizers:",
+            "<@1>: 2 calls",
+            "Most responsive players:",
+            "<@2>: joined 2 of 2",
+            "<@3>: joined 1 of 2",
+        ], "{}", reply);
+        assert_eq!(lines[6], "Busiest hours (UTC):");
+        // the hour is whenever the test runs
+        assert!(lines[7].starts_with("<#200>: ") && lines[7].contains(" call"), "{}", reply);
+        assert_eq!(lines.last(), Some(&"You joined 1 of the 2 calls you were notified about (50%)"));
+
+        harness.say(&alice, "!stats").await;
+        assert!(harness.last_reply().ends_with("You weren't notified about anything"), "{}", harness.last_reply());
+
+        harness.finish().await;
+    }
+}
+
+#[tokio::test]
+async fn unregistering_removes_the_organizers_history() {
+    for harness in Harness::all().await {
+        let alice = harness.user(ALICE, "alice");
+        let bob = harness.user(BOB, "bob");
+        harness.say(&alice, "!register").await;
+        harness.say(&bob, "!register").await;
+        harness.say(&bob, "!game-notification-on").await;
+
+        harness.say(&bob, "!stats").await;
+        assert_eq!(harness.last_reply(), "@bob Nobody started a stack or scheduled a game in this server in the last 30 days.");
+
+        harness.say(&alice, "!any-gamers").await;
+        harness.say(&bob, "!stats").await;
+        assert!(harness.last_reply().starts_with("@bob Over the last 30 days:\nMost active organizers:\n<@1>: 1 call\nMost responsive players:\nNobody joined anything yet"), "{}", harness.last_reply());
+
+        harness.say(&alice, "!unregister").await;
+        harness.say(&bob, "!stats").await;
+        assert_eq!(harness.last_reply(), "@bob Nobody started a stack or scheduled a game in this server in the last 30 days.");
+
+        harness.finish().await;
+    }
+}
@@ -0,0 +1,187 @@
+use crate::errors::WololoError;
+use crate::store::{PgStore, Store};
+use crate::structs::ParentMessageChildMessage;
+use crate::tests::{stores, TestDatabase, CHANNEL_ID, GUILD_ID};
+
+const ALICE: u64 = 1;
+const BOB: u64 = 2;
+const OTHER_GUILD_ID: u64 = 101;
+const DM_CHANNEL_ID: u64 = 300;
+
+#[tokio::test]
+async fn subscribing_twice_is_a_conflict() {
+    for (store, database) in stores().await {
+        store.create_user(ALICE).await.unwrap();
+        store.create_ping(GUILD_ID.get(), ALICE, CHANNEL_ID.get(), None).await.unwrap();
+        assert!(matches!(store.create_ping(GUILD_ID.get(), ALICE, CHANNEL_ID.get(), None).await, Err(WololoError::Conflict(_))));
+        // a game specific subscription is a different one
+        let game = store.create_game(GUILD_ID.get(), "AoE2", &["aoe".to_string()], 8, None).await.unwrap();
+        store.create_ping(GUILD_ID.get(), ALICE, CHANNEL_ID.get(), Some(game.id)).await.unwrap();
+        assert_eq!(store.get_pings_for_user(ALICE).await.unwrap().len(), 2);
+
+        if let Some(database) = database {
+            database.remove().await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn everyone_else_is_notified_once() {
+    for (store, database) in stores().await {
+        for user in [ALICE, BOB] {
+            store.create_user(user).await.unwrap();
+            store.create_ping(GUILD_ID.get(), user, CHANNEL_ID.get(), None).await.unwrap();
+        }
+        let game = store.create_game(GUILD_ID.get(), "aoe2", &[], 8, None).await.unwrap();
+        store.create_ping(GUILD_ID.get(), BOB, CHANNEL_ID.get(), Some(game.id)).await.unwrap();
+
+        let pings = store.get_all_pings_except_for_user(ALICE, CHANNEL_ID.get(), Some(game.id)).await.unwrap();
+        assert_eq!(pings.len(), 1);
+        assert_eq!(pings[0].user_discord_id, BOB as i64);
+        let now = chrono::offset::Utc::now();
+        assert_eq!(store.reserve_notification_dms(GUILD_ID.get(), CHANNEL_ID.get(), None, &[BOB], 60, now).await.unwrap(), [BOB]);
+        // both of bob's subscriptions in the channel count as notified
+        assert!(store.get_pings_for_user(BOB).await.unwrap().iter().all(|ping| ping.last_notified.is_some()));
+        assert!(store.reserve_notification_dms(GUILD_ID.get(), CHANNEL_ID.get(), None, &[BOB], 60, now).await.unwrap().is_empty());
+
+        // the dm couldn't be sent, so it doesn't count
+        store.release_notification_dm(CHANNEL_ID.get(), BOB, now, None).await.unwrap();
+        assert!(store.get_pings_for_user(BOB).await.unwrap().iter().all(|ping| ping.last_notified.is_none()));
+        assert_eq!(store.count_notification_dms_for_user(BOB, now - chrono::Duration::hours(1)).await.unwrap(), 0);
+        assert_eq!(store.reserve_notification_dms(GUILD_ID.get(), CHANNEL_ID.get(), None, &[BOB], 60, now).await.unwrap(), [BOB]);
+
+        if let Some(database) = database {
+            database.remove().await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn overlapping_notifications_claim_someone_once() {
+    for (store, database) in stores().await {
+        store.create_user(BOB).await.unwrap();
+        store.create_ping(GUILD_ID.get(), BOB, CHANNEL_ID.get(), None).await.unwrap();
+
+        let now = chrono::offset::Utc::now();
+        let (first, second) = tokio::join!(
+            store.reserve_notification_dms(GUILD_ID.get(), CHANNEL_ID.get(), None, &[BOB], 60, now),
+            store.reserve_notification_dms(GUILD_ID.get(), CHANNEL_ID.get(), None, &[BOB], 60, now)
+        );
+        assert_eq!(first.unwrap().len() + second.unwrap().len(), 1);
+        assert_eq!(store.count_notification_dms_for_user(BOB, now - chrono::Duration::hours(1)).await.unwrap(), 1);
+
+        if let Some(database) = database {
+            database.remove().await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn games_are_found_by_name_or_alias() {
+    for (store, database) in stores().await {
+        store.create_game(GUILD_ID.get(), "AoE2", &["AoE".to_string()], 8, None).await.unwrap();
+        assert_eq!(store.get_game_by_name(GUILD_ID.get(), "aoe").await.unwrap().name, "aoe2");
+        assert!(matches!(store.get_game_by_name(OTHER_GUILD_ID, "aoe2").await, Err(WololoError::NotFound)));
+        assert!(matches!(store.create_game(GUILD_ID.get(), "aoe2", &[], 4, None).await, Err(WololoError::Conflict(_))));
+        // the same name is fine in another guild
+        store.create_game(OTHER_GUILD_ID, "aoe2", &[], 4, None).await.unwrap();
+        assert_eq!(store.get_game_by_name(OTHER_GUILD_ID, "aoe2").await.unwrap().default_party_size, 4);
+
+        if let Some(database) = database {
+            database.remove().await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn deleting_a_game_keeps_its_lobbies() {
+    for (store, database) in stores().await {
+        store.create_user(ALICE).await.unwrap();
+        let game = store.create_game(GUILD_ID.get(), "aoe2", &[], 8, None).await.unwrap();
+        store.create_ping(GUILD_ID.get(), ALICE, CHANNEL_ID.get(), Some(game.id)).await.unwrap();
+        let lobby = store.create_lobby(GUILD_ID.get(), ALICE, CHANNEL_ID.get(), 8, Some(&game), "").await.unwrap();
+        assert_eq!(store.get_lobby(lobby.id).await.unwrap().game_name.as_deref(), Some("aoe2"));
+
+        assert!(store.delete_game(game.id).await.unwrap());
+        assert!(store.get_lobby(lobby.id).await.unwrap().game_name.is_none());
+        assert!(store.get_pings_for_user(ALICE).await.unwrap().is_empty());
+
+        if let Some(database) = database {
+            database.remove().await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn deleting_a_user_deletes_everything_of_theirs() {
+    for (store, database) in stores().await {
+        for user in [ALICE, BOB] {
+            store.create_user(user).await.unwrap();
+        }
+        store.create_ping(GUILD_ID.get(), ALICE, CHANNEL_ID.get(), None).await.unwrap();
+        store.create_admin_user(GUILD_ID.get(), ALICE).await.unwrap();
+        store.upsert_timezone_for_user(ALICE, "Europe/Paris").await.unwrap();
+        let lobby = store.create_lobby(GUILD_ID.get(), ALICE, CHANNEL_ID.get(), 2, None, "").await.unwrap();
+        store.upsert_lobby_member(lobby.id, BOB, true).await.unwrap();
+        let bobs_lobby = store.create_lobby(GUILD_ID.get(), BOB, CHANNEL_ID.get(), 2, None, "").await.unwrap();
+        store.upsert_lobby_member(bobs_lobby.id, ALICE, true).await.unwrap();
+        store.create_child_for_message(ParentMessageChildMessage {
+            parent: 1,
+            parent_channel_id: CHANNEL_ID.get() as i64,
+            child: 2,
+            child_channel_id: DM_CHANNEL_ID as i64,
+            guild_id: Some(GUILD_ID.get() as i64),
+            lobby_id: Some(bobs_lobby.id),
+            scheduled_game_id: None
+        }).await.unwrap();
+
+        assert!(store.delete_user(ALICE, Some(DM_CHANNEL_ID)).await.unwrap());
+        assert!(!store.delete_user(ALICE, None).await.unwrap());
+        assert!(store.get_pings_for_user(ALICE).await.unwrap().is_empty());
+        assert!(!store.is_user_admin(GUILD_ID.get(), ALICE).await.unwrap());
+        assert!(store.get_user_settings(ALICE).await.unwrap().is_none());
+        assert!(matches!(store.get_lobby(lobby.id).await, Err(WololoError::NotFound)));
+        assert!(store.get_lobby_members(bobs_lobby.id).await.unwrap().is_empty());
+        assert!(matches!(store.get_latest_child_for_channel(DM_CHANNEL_ID).await, Err(WololoError::NotFound)));
+
+        if let Some(database) = database {
+            database.remove().await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn a_missing_column_stops_the_bot_from_starting() {
+    let Some(database) = TestDatabase::create().await else {
+        return;
+    };
+    PgStore::prepare(database.pool.clone(), false).await.unwrap();
+    sqlx::query("ALTER TABLE message_children DROP COLUMN parent_channel_id").execute(&database.pool).await.unwrap();
+    let error = PgStore::prepare(database.pool.clone(), false).await.err().expect("Schema check passed");
+    assert!(error.to_string().starts_with("The database is missing message_children.parent_channel_id."), "{}", error);
+
+    database.remove().await;
+}
+
+#[tokio::test]
+async fn epoch_timestamps_survive_the_timestamptz_migration() {
+    let Some(database) = TestDatabase::create_before(20261018211045).await else {
+        return;
+    };
+    sqlx::query("INSERT INTO users (discord_id, created_at) VALUES ($1, 1700000000)").bind(ALICE as i64).execute(&database.pool).await.unwrap();
+    sqlx::query("INSERT INTO ping_list (discord_user_id, guild_id, discord_channel_id, created_at, last_notified) VALUES ($1, $2, $3, 1700000000, 1700000060)")
+        .bind(ALICE as i64)
+        .bind(GUILD_ID.get() as i64)
+        .bind(CHANNEL_ID.get() as i64)
+        .execute(&database.pool)
+        .await
+        .unwrap();
+    let store = PgStore::prepare(database.pool.clone(), true).await.unwrap();
+
+    assert_eq!(store.get_user(ALICE).await.unwrap().created_at.timestamp(), 1700000000);
+    let ping = store.get_pings_for_user(ALICE).await.unwrap().remove(0);
+    assert_eq!(ping.created_at.timestamp(), 1700000000);
+    assert_eq!(ping.last_notified.map(|last_notified| last_notified.timestamp()), Some(1700000060));
+
+    database.remove().await;
+}
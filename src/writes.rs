@@ -1,106 +1,204 @@
-use sqlx::Error;
-use tracing::error;
-use crate::structs::{AdminUser, ParentMessageChildMessage, Ping, WololoUser};
+use chrono::{DateTime, Utc};
+use crate::constants;
+use crate::errors::WololoError;
+use crate::structs::{AdminUser, AuditEntry, BlacklistedUser, ChannelSettings, Game, GuildSettings, Lobby, LobbyMember, NotificationRecord, ParentMessageChildMessage, Ping, ScheduledGame, UserSettings, WololoUser};
 
-pub(crate) async fn create_user(pool: &sqlx::PgPool, discord_id: u64) -> Option<WololoUser> {
+pub(crate) async fn create_user(pool: &sqlx::PgPool, discord_id: u64) -> Result<WololoUser, WololoError> {
     let user = WololoUser {
         discord_id: discord_id as i64,
         created_at: chrono::offset::Utc::now()
     };
-    let result = sqlx::query(
+    sqlx::query!(
         "INSERT into users (discord_id, created_at) VALUES ($1, $2)",
+        user.discord_id,
+        user.created_at,
     )
-        .bind(user.discord_id)
-        .bind(user.created_at.timestamp())
-        .fetch_one(pool)
-        .await;
+        .execute(pool)
+        .await?;
+    Ok(user)
+}
 
-    match result {
-        Ok(_) => Some(user),
-        Err(e) => {
-            error!("Unable to get user {}: {}", discord_id, e);
-            None
-        },
+/// Everything else that belongs to the user is removed by the foreign keys cascading, except for the notification dms
+/// we sent them which are only tied to their dm channel
+pub(crate) async fn delete_user(pool: &sqlx::PgPool, discord_id: u64, dm_channel_id: Option<u64>) -> Result<bool, WololoError> {
+    let mut transaction = pool.begin().await?;
+    if let Some(dm_channel_id) = dm_channel_id {
+        sqlx::query!(
+            "DELETE FROM message_children WHERE child_channel_id = $1",
+            dm_channel_id as i64,
+        )
+            .execute(&mut *transaction)
+            .await?;
     }
-
+    let result = sqlx::query!(
+        "DELETE FROM users WHERE discord_id = $1",
+        discord_id as i64,
+    )
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(result.rows_affected() > 0)
 }
 
-pub(crate) async fn create_ping(pool: &sqlx::PgPool, user_discord_id: u64, discord_channel_id: u64) -> Option<Ping> {
+pub(crate) async fn create_ping(pool: &sqlx::PgPool, guild_id: u64, user_discord_id: u64, discord_channel_id: u64, game_id: Option<i32>) -> Result<Ping, WololoError> {
     let ping = Ping {
         user_discord_id: user_discord_id as i64,
+        guild_id: Some(guild_id as i64),
         discord_channel_id: discord_channel_id as i64,
+        game_id,
         created_at: chrono::offset::Utc::now(),
         last_notified: None
     };
-    let result = sqlx::query(
-        "INSERT into ping_list (discord_user_id, discord_channel_id, created_at) VALUES ($1, $2, $3) RETURNING *",
+    sqlx::query!(
+        "INSERT into ping_list (discord_user_id, guild_id, discord_channel_id, game_id, created_at) VALUES ($1, $2, $3, $4, $5)",
+        ping.user_discord_id,
+        ping.guild_id,
+        ping.discord_channel_id,
+        ping.game_id,
+        ping.created_at,
     )
-        .bind(ping.user_discord_id)
-        .bind(ping.discord_channel_id)
-        .bind(ping.created_at.timestamp())
-        .fetch_one(pool)
-        .await;
+        .execute(pool)
+        .await?;
+    Ok(ping)
+}
 
-    match result {
-        Ok(_) => Some(ping),
-        Err(e) => {
-            error!("Unable to create ping for user {} channel {}: {}", user_discord_id, discord_channel_id, e);
-            None
-        },
-    }
+pub(crate) async fn delete_ping(pool: &sqlx::PgPool, ping: Ping) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "DELETE FROM ping_list WHERE discord_user_id = $1 AND discord_channel_id = $2 AND game_id IS NOT DISTINCT FROM $3",
+        ping.user_discord_id,
+        ping.discord_channel_id,
+        ping.game_id,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
 }
 
+/// Unsubscribes the user from every game in the channel, returns how many subscriptions were removed
+pub(crate) async fn delete_pings_for_channel(pool: &sqlx::PgPool, user_discord_id: u64, discord_channel_id: u64) -> Result<u64, WololoError> {
+    let result = sqlx::query!(
+        "DELETE FROM ping_list WHERE discord_user_id = $1 AND discord_channel_id = $2",
+        user_discord_id as i64,
+        discord_channel_id as i64,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
 
-pub(crate) async fn delete_ping(pool: &sqlx::PgPool, ping: Ping) -> bool {
+/// Unsubscribes the user from every channel, returns how many subscriptions were removed
+pub(crate) async fn delete_pings_for_user(pool: &sqlx::PgPool, user_discord_id: u64) -> Result<u64, WololoError> {
+    let result = sqlx::query!(
+        "DELETE FROM ping_list WHERE discord_user_id = $1",
+        user_discord_id as i64,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
 
-    let result = sqlx::query(
-        "DELETE FROM ping_list WHERE discord_user_id = $1 AND discord_channel_id = $2 RETURNING *",
+pub(crate) async fn create_notification(pool: &sqlx::PgPool, guild_id: u64, discord_channel_id: u64, organizer_discord_id: u64, lobby_id: Option<i32>, scheduled_game_id: Option<i32>, description: &str) -> Result<NotificationRecord, WololoError> {
+    Ok(sqlx::query_as!(
+        NotificationRecord,
+        "INSERT into notifications (guild_id, discord_channel_id, organizer_discord_id, lobby_id, scheduled_game_id, description) VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, discord_channel_id, organizer_discord_id, created_at",
+        guild_id as i64,
+        discord_channel_id as i64,
+        organizer_discord_id as i64,
+        lobby_id,
+        scheduled_game_id,
+        description,
     )
-        .bind(ping.user_discord_id)
-        .bind(ping.discord_channel_id)
         .fetch_one(pool)
-        .await;
-
-    match result {
-        Ok(_) => true,
-        Err(e) => {
-            error!("Unable to delete ping for user {} channel {}: {}", ping.user_discord_id, ping.discord_channel_id, e);
-            false
-        },
-    }
+        .await?)
 }
 
+/// Claims the recipients for a notification before their dms are sent, see `Store::reserve_notification_dms`. The dms
+/// count towards the recipients' hourly cap and the notification's history, returns who was claimed
+pub(crate) async fn reserve_notification_dms(pool: &sqlx::PgPool, guild_id: u64, discord_channel_id: u64, notification_id: Option<i32>, recipient_discord_ids: &[u64], channel_cooldown_seconds: i64, now: DateTime<Utc>) -> Result<Vec<u64>, WololoError> {
+    let recipient_discord_ids = recipient_discord_ids.iter().map(|recipient_discord_id| *recipient_discord_id as i64).collect::<Vec<_>>();
+    let mut transaction = pool.begin().await?;
+    // overlapping notifications wait for each other here, so the checks below see who the other one claimed
+    sqlx::query!(
+        "SELECT discord_id FROM users WHERE discord_id = ANY($1) ORDER BY discord_id FOR UPDATE",
+        &recipient_discord_ids,
+    )
+        .fetch_all(&mut *transaction)
+        .await?;
+    let claimed = sqlx::query_scalar!(
+        "WITH claimed AS (
+             SELECT users.discord_id FROM users LEFT JOIN user_settings ON user_settings.discord_user_id = users.discord_id
+             WHERE users.discord_id = ANY($4)
+             AND NOT EXISTS (SELECT 1 FROM ping_list WHERE discord_user_id = users.discord_id AND discord_channel_id = $2 AND last_notified > $6::TIMESTAMPTZ - make_interval(secs => $5))
+             AND NOT EXISTS (SELECT 1 FROM ping_list WHERE discord_user_id = users.discord_id AND user_settings.min_notification_gap_seconds > 0
+                 AND last_notified > $6::TIMESTAMPTZ - make_interval(secs => user_settings.min_notification_gap_seconds))
+             AND (SELECT COUNT(*) FROM notification_dms WHERE recipient_discord_id = users.discord_id AND sent_at > $6::TIMESTAMPTZ - INTERVAL '1 hour') < $7
+         ), notified AS (
+             UPDATE ping_list SET last_notified = $6 WHERE discord_channel_id = $2 AND discord_user_id IN (SELECT discord_id FROM claimed)
+         )
+         INSERT into notification_dms (guild_id, discord_channel_id, notification_id, recipient_discord_id, sent_at) SELECT $1, $2, $3, discord_id, $6 FROM claimed
+         RETURNING recipient_discord_id",
+        guild_id as i64,
+        discord_channel_id as i64,
+        notification_id,
+        &recipient_discord_ids,
+        channel_cooldown_seconds as f64,
+        now,
+        constants::MAX_DMS_PER_RECIPIENT_PER_HOUR,
+    )
+        .fetch_all(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(claimed.into_iter().map(|recipient_discord_id| recipient_discord_id as u64).collect())
+}
 
-pub(crate) async fn update_notified_at_for_ping(pool: &sqlx::PgPool, ping: Ping) -> Result<Ping, Error> {
-    let now = chrono::offset::Utc::now();
+/// Takes back the dm that couldn't be sent, and the recipient's notified time in the channel unless another
+/// notification claimed them since
+pub(crate) async fn release_notification_dm(pool: &sqlx::PgPool, discord_channel_id: u64, recipient_discord_id: u64, reserved_at: DateTime<Utc>, last_notified: Option<DateTime<Utc>>) -> Result<(), WololoError> {
     let mut transaction = pool.begin().await?;
-    sqlx::query(
-        "SELECT FROM users WHERE discord_id = $1"
-    ).bind(ping.user_discord_id)
+    sqlx::query!(
+        "DELETE FROM notification_dms WHERE discord_channel_id = $1 AND recipient_discord_id = $2 AND sent_at = $3",
+        discord_channel_id as i64,
+        recipient_discord_id as i64,
+        reserved_at,
+    )
         .execute(&mut *transaction)
         .await?;
-    let _ = sqlx::query(
-        "UPDATE ping_list SET last_notified = $3 WHERE discord_user_id = $1 AND discord_channel_id = $2  RETURNING *",
+    sqlx::query!(
+        "UPDATE ping_list SET last_notified = $4 WHERE discord_channel_id = $1 AND discord_user_id = $2 AND last_notified = $3",
+        discord_channel_id as i64,
+        recipient_discord_id as i64,
+        reserved_at,
+        last_notified,
     )
-        .bind(ping.user_discord_id)
-        .bind(ping.discord_channel_id)
-        .bind(now.timestamp())
         .execute(&mut *transaction)
         .await?;
     transaction.commit().await?;
-    Ok(Ping {
-        user_discord_id: ping.user_discord_id,
-        discord_channel_id: ping.discord_channel_id,
-        created_at: ping.created_at,
-        last_notified: Option::from(now)
-    })
+    Ok(())
+}
 
+/// Saves the recipient's latest answer to the dms about the lobby or scheduled game, returns how many rows were updated
+pub(crate) async fn update_response_for_notification_dms(pool: &sqlx::PgPool, lobby_id: Option<i32>, scheduled_game_id: Option<i32>, recipient_discord_id: u64, accepted: bool) -> Result<u64, WololoError> {
+    let result = sqlx::query!(
+        "UPDATE notification_dms SET responded_at = COALESCE(responded_at, $4), accepted = $5 FROM notifications
+         WHERE notifications.id = notification_dms.notification_id AND (notifications.lobby_id = $1 OR notifications.scheduled_game_id = $2) AND recipient_discord_id = $3",
+        lobby_id,
+        scheduled_game_id,
+        recipient_discord_id as i64,
+        chrono::offset::Utc::now(),
+        accepted,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
 }
 
-pub(crate) async fn create_admin_user(pool: &sqlx::PgPool, discord_id: u64) -> Result<AdminUser, Error> {
-    let _ = sqlx::query(
-        "INSERT into admins (discord_user_id) VALUES ($1) RETURNING * ",
-    ).bind(discord_id as i64)
+pub(crate) async fn create_admin_user(pool: &sqlx::PgPool, guild_id: u64, discord_id: u64) -> Result<AdminUser, WololoError> {
+    let _ = sqlx::query!(
+        "INSERT into admins (guild_id, discord_user_id) VALUES ($1, $2) RETURNING * ",
+        guild_id as i64,
+        discord_id as i64,
+    )
         .fetch_one(pool)
         .await?;
     Ok(AdminUser {
@@ -108,24 +206,580 @@ pub(crate) async fn create_admin_user(pool: &sqlx::PgPool, discord_id: u64) -> R
     })
 }
 
-pub(crate) async fn create_child_for_message(pool: &sqlx::PgPool, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, Error> {
-    let _ = sqlx::query(
-        "INSERT into message_children (parent, child, parent_channel_id, child_channel_id) VALUES ($1, $2, $3, $4) RETURNING * ",
-    ).bind(parent_msg_child_msg.parent)
-        .bind(parent_msg_child_msg.child)
-        .bind(parent_msg_child_msg.parent_channel_id)
-        .bind(parent_msg_child_msg.child_channel_id)
+pub(crate) async fn delete_admin_user(pool: &sqlx::PgPool, guild_id: u64, discord_id: u64) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "DELETE from admins WHERE guild_id = $1 AND discord_user_id = $2",
+        guild_id as i64,
+        discord_id as i64,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub(crate) async fn create_blacklisted_user(pool: &sqlx::PgPool, guild_id: u64, discord_id: u64) -> Result<BlacklistedUser, WololoError> {
+    let _ = sqlx::query!(
+        "INSERT into blacklisted_users (guild_id, discord_user_id) VALUES ($1, $2) RETURNING * ",
+        guild_id as i64,
+        discord_id as i64,
+    )
+        .fetch_one(pool)
+        .await?;
+    Ok(BlacklistedUser {
+        discord_id: discord_id as i64
+    })
+}
+
+pub(crate) async fn delete_blacklisted_user(pool: &sqlx::PgPool, guild_id: u64, discord_id: u64) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "DELETE from blacklisted_users WHERE guild_id = $1 AND discord_user_id = $2",
+        guild_id as i64,
+        discord_id as i64,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub(crate) async fn create_child_for_message(pool: &sqlx::PgPool, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, WololoError> {
+    let _ = sqlx::query!(
+        "INSERT into message_children (parent, child, parent_channel_id, child_channel_id, guild_id, lobby_id, scheduled_game_id) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING * ",
+        parent_msg_child_msg.parent,
+        parent_msg_child_msg.child,
+        parent_msg_child_msg.parent_channel_id,
+        parent_msg_child_msg.child_channel_id,
+        parent_msg_child_msg.guild_id,
+        parent_msg_child_msg.lobby_id,
+        parent_msg_child_msg.scheduled_game_id,
+    )
         .fetch_one(pool)
         .await?;
     Ok(true)
 }
 
-pub(crate) async fn delete_child_for_message(pool: &sqlx::PgPool, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, Error> {
-    let _ = sqlx::query(
+pub(crate) async fn delete_child_for_message(pool: &sqlx::PgPool, parent_msg_child_msg: ParentMessageChildMessage) -> Result<bool, WololoError> {
+    let _ = sqlx::query!(
         "DELETE from message_children WHERE parent=$1 AND child=$2 RETURNING *",
-    ).bind(parent_msg_child_msg.parent)
-        .bind(parent_msg_child_msg.child)
+        parent_msg_child_msg.parent,
+        parent_msg_child_msg.child,
+    )
         .fetch_one(pool)
         .await?;
     Ok(true)
-}
\ No newline at end of file
+}
+
+pub(crate) async fn create_lobby(pool: &sqlx::PgPool, guild_id: u64, organizer_discord_id: u64, discord_channel_id: u64, target_size: i32, game: Option<&Game>, description: &str) -> Result<Lobby, WololoError> {
+    let created_at = chrono::offset::Utc::now();
+    let id: i32 = sqlx::query_scalar!(
+        "INSERT into lobbies (guild_id, organizer_discord_id, discord_channel_id, target_size, game_id, description, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+        guild_id as i64,
+        organizer_discord_id as i64,
+        discord_channel_id as i64,
+        target_size,
+        game.map(|game| game.id),
+        description,
+        created_at,
+    )
+        .fetch_one(pool)
+        .await?;
+    Ok(Lobby {
+        id,
+        guild_id: Some(guild_id as i64),
+        organizer_discord_id: organizer_discord_id as i64,
+        discord_channel_id: discord_channel_id as i64,
+        summary_message_id: None,
+        target_size,
+        game_name: game.map(|game| game.name.clone()),
+        description: description.to_string(),
+        created_at,
+        filled_at: None
+    })
+}
+
+pub(crate) async fn update_summary_message_for_lobby(pool: &sqlx::PgPool, lobby_id: i32, summary_message_id: u64) -> Result<bool, WololoError> {
+    let _ = sqlx::query!(
+        "UPDATE lobbies SET summary_message_id = $2 WHERE id = $1",
+        lobby_id,
+        summary_message_id as i64,
+    )
+        .execute(pool)
+        .await?;
+    Ok(true)
+}
+
+/// Only returns true for the caller that actually filled the lobby, so the stack is announced once
+pub(crate) async fn update_filled_at_for_lobby(pool: &sqlx::PgPool, lobby_id: i32) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "UPDATE lobbies SET filled_at = $2 WHERE id = $1 AND filled_at IS NULL",
+        lobby_id,
+        chrono::offset::Utc::now(),
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub(crate) async fn upsert_lobby_member(pool: &sqlx::PgPool, lobby_id: i32, discord_user_id: u64, joined: bool) -> Result<LobbyMember, WololoError> {
+    let updated_at = chrono::offset::Utc::now();
+    let _ = sqlx::query!(
+        "INSERT into lobby_members (lobby_id, discord_user_id, joined, updated_at) VALUES ($1, $2, $3, $4)
+         ON CONFLICT (lobby_id, discord_user_id) DO UPDATE SET joined = EXCLUDED.joined, updated_at = EXCLUDED.updated_at",
+        lobby_id,
+        discord_user_id as i64,
+        joined,
+        updated_at,
+    )
+        .execute(pool)
+        .await?;
+    Ok(LobbyMember {
+        discord_user_id: discord_user_id as i64,
+        joined,
+    })
+}
+
+pub(crate) async fn delete_lobby_member(pool: &sqlx::PgPool, lobby_id: i32, discord_user_id: u64) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "DELETE from lobby_members WHERE lobby_id = $1 AND discord_user_id = $2",
+        lobby_id,
+        discord_user_id as i64,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub(crate) async fn upsert_notification_cooldown_for_channel(pool: &sqlx::PgPool, guild_id: u64, discord_channel_id: u64, notification_cooldown_seconds: i64) -> Result<ChannelSettings, WololoError> {
+    let _ = sqlx::query!(
+        "INSERT into channel_settings (guild_id, discord_channel_id, notification_cooldown_seconds) VALUES ($1, $2, $3)
+         ON CONFLICT (discord_channel_id) DO UPDATE SET notification_cooldown_seconds = EXCLUDED.notification_cooldown_seconds",
+        guild_id as i64,
+        discord_channel_id as i64,
+        notification_cooldown_seconds,
+    )
+        .execute(pool)
+        .await?;
+    Ok(ChannelSettings {
+        notification_cooldown_seconds
+    })
+}
+
+/// `None` clears the help text
+pub(crate) async fn upsert_help_text_for_guild(pool: &sqlx::PgPool, guild_id: u64, help_text: Option<&str>) -> Result<GuildSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "INSERT into guild_settings (guild_id, help_text) VALUES ($1, $2)
+         ON CONFLICT (guild_id) DO UPDATE SET help_text = EXCLUDED.help_text
+         RETURNING help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds",
+        guild_id as i64,
+        help_text,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// `None` goes back to the default cooldown
+pub(crate) async fn upsert_notification_cooldown_for_guild(pool: &sqlx::PgPool, guild_id: u64, notification_cooldown_seconds: Option<i64>) -> Result<GuildSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "INSERT into guild_settings (guild_id, notification_cooldown_seconds) VALUES ($1, $2)
+         ON CONFLICT (guild_id) DO UPDATE SET notification_cooldown_seconds = EXCLUDED.notification_cooldown_seconds
+         RETURNING help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds",
+        guild_id as i64,
+        notification_cooldown_seconds,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// `None` goes back to the default prefix
+pub(crate) async fn upsert_command_prefix_for_guild(pool: &sqlx::PgPool, guild_id: u64, command_prefix: Option<&str>) -> Result<GuildSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "INSERT into guild_settings (guild_id, command_prefix) VALUES ($1, $2)
+         ON CONFLICT (guild_id) DO UPDATE SET command_prefix = EXCLUDED.command_prefix
+         RETURNING help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds",
+        guild_id as i64,
+        command_prefix,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// `None` goes back to english
+pub(crate) async fn upsert_locale_for_guild(pool: &sqlx::PgPool, guild_id: u64, locale: Option<&str>) -> Result<GuildSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "INSERT into guild_settings (guild_id, locale) VALUES ($1, $2)
+         ON CONFLICT (guild_id) DO UPDATE SET locale = EXCLUDED.locale
+         RETURNING help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds",
+        guild_id as i64,
+        locale,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+pub(crate) async fn upsert_personalized_invitations_for_guild(pool: &sqlx::PgPool, guild_id: u64, personalized_invitations: bool) -> Result<GuildSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "INSERT into guild_settings (guild_id, personalized_invitations) VALUES ($1, $2)
+         ON CONFLICT (guild_id) DO UPDATE SET personalized_invitations = EXCLUDED.personalized_invitations
+         RETURNING help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds",
+        guild_id as i64,
+        personalized_invitations,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+pub(crate) async fn upsert_game_call_offers_for_guild(pool: &sqlx::PgPool, guild_id: u64, game_call_offers: bool) -> Result<GuildSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "INSERT into guild_settings (guild_id, game_call_offers) VALUES ($1, $2)
+         ON CONFLICT (guild_id) DO UPDATE SET game_call_offers = EXCLUDED.game_call_offers
+         RETURNING help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds",
+        guild_id as i64,
+        game_call_offers,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// `None` goes back to the model's own friendly voice
+pub(crate) async fn upsert_invitation_theme_for_guild(pool: &sqlx::PgPool, guild_id: u64, invitation_theme: Option<&str>) -> Result<GuildSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "INSERT into guild_settings (guild_id, invitation_theme) VALUES ($1, $2)
+         ON CONFLICT (guild_id) DO UPDATE SET invitation_theme = EXCLUDED.invitation_theme
+         RETURNING help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds",
+        guild_id as i64,
+        invitation_theme,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// `None` goes back to `constants::DEFAULT_CALL_LIMIT`, otherwise it's the limit and the window in seconds
+pub(crate) async fn upsert_call_limit_for_guild(pool: &sqlx::PgPool, guild_id: u64, call_limit: Option<(i32, i64)>) -> Result<GuildSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "INSERT into guild_settings (guild_id, call_limit, call_window_seconds) VALUES ($1, $2, $3)
+         ON CONFLICT (guild_id) DO UPDATE SET call_limit = EXCLUDED.call_limit, call_window_seconds = EXCLUDED.call_window_seconds
+         RETURNING help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds",
+        guild_id as i64,
+        call_limit.map(|(limit, _)| limit),
+        call_limit.map(|(_, window_seconds)| window_seconds),
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// `None` stops posting audit log entries anywhere
+pub(crate) async fn upsert_mod_log_channel_for_guild(pool: &sqlx::PgPool, guild_id: u64, mod_log_channel_id: Option<u64>) -> Result<GuildSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        GuildSettings,
+        "INSERT into guild_settings (guild_id, mod_log_channel_id) VALUES ($1, $2)
+         ON CONFLICT (guild_id) DO UPDATE SET mod_log_channel_id = EXCLUDED.mod_log_channel_id
+         RETURNING help_text, notification_cooldown_seconds, command_prefix, locale, personalized_invitations, invitation_theme, game_call_offers, mod_log_channel_id, call_limit, call_window_seconds",
+        guild_id as i64,
+        mod_log_channel_id.map(|mod_log_channel_id| mod_log_channel_id as i64),
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+pub(crate) async fn create_audit_entry(pool: &sqlx::PgPool, guild_id: u64, discord_channel_id: Option<u64>, actor_discord_id: u64, action: &str, target_discord_id: Option<u64>, details: Option<&str>) -> Result<AuditEntry, WololoError> {
+    Ok(sqlx::query_as!(
+        AuditEntry,
+        "INSERT into audit_log (guild_id, discord_channel_id, actor_discord_id, action, target_discord_id, details) VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING guild_id, discord_channel_id, actor_discord_id, action, target_discord_id, details, created_at",
+        guild_id as i64,
+        discord_channel_id.map(|discord_channel_id| discord_channel_id as i64),
+        actor_discord_id as i64,
+        action,
+        target_discord_id.map(|target_discord_id| target_discord_id as i64),
+        details,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// Adds to the tokens the guild used on `day` and returns the new total
+pub(crate) async fn add_language_model_tokens(pool: &sqlx::PgPool, guild_id: u64, day: chrono::NaiveDate, tokens: i64) -> Result<i64, WololoError> {
+    Ok(sqlx::query_scalar!(
+        "INSERT into language_model_usage (guild_id, day, tokens) VALUES ($1, $2, $3)
+         ON CONFLICT (guild_id, day) DO UPDATE SET tokens = language_model_usage.tokens + EXCLUDED.tokens
+         RETURNING tokens",
+        guild_id as i64,
+        day,
+        tokens,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+pub(crate) async fn upsert_notification_gap_for_user(pool: &sqlx::PgPool, discord_user_id: u64, min_notification_gap_seconds: Option<i64>) -> Result<UserSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        UserSettings,
+        "INSERT into user_settings (discord_user_id, min_notification_gap_seconds) VALUES ($1, $2)
+         ON CONFLICT (discord_user_id) DO UPDATE SET min_notification_gap_seconds = EXCLUDED.min_notification_gap_seconds
+         RETURNING min_notification_gap_seconds, timezone, quiet_start_minutes, quiet_end_minutes, locale",
+        discord_user_id as i64,
+        min_notification_gap_seconds,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+pub(crate) async fn upsert_timezone_for_user(pool: &sqlx::PgPool, discord_user_id: u64, timezone: &str) -> Result<UserSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        UserSettings,
+        "INSERT into user_settings (discord_user_id, timezone) VALUES ($1, $2)
+         ON CONFLICT (discord_user_id) DO UPDATE SET timezone = EXCLUDED.timezone
+         RETURNING min_notification_gap_seconds, timezone, quiet_start_minutes, quiet_end_minutes, locale",
+        discord_user_id as i64,
+        timezone,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// `None` goes back to the language of whichever guild they are in
+pub(crate) async fn upsert_locale_for_user(pool: &sqlx::PgPool, discord_user_id: u64, locale: Option<&str>) -> Result<UserSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        UserSettings,
+        "INSERT into user_settings (discord_user_id, locale) VALUES ($1, $2)
+         ON CONFLICT (discord_user_id) DO UPDATE SET locale = EXCLUDED.locale
+         RETURNING min_notification_gap_seconds, timezone, quiet_start_minutes, quiet_end_minutes, locale",
+        discord_user_id as i64,
+        locale,
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+/// Passing `None` as the window turns quiet hours off
+pub(crate) async fn upsert_quiet_hours_for_user(pool: &sqlx::PgPool, discord_user_id: u64, quiet_minutes: Option<(i32, i32)>) -> Result<UserSettings, WololoError> {
+    Ok(sqlx::query_as!(
+        UserSettings,
+        "INSERT into user_settings (discord_user_id, quiet_start_minutes, quiet_end_minutes) VALUES ($1, $2, $3)
+         ON CONFLICT (discord_user_id) DO UPDATE SET quiet_start_minutes = EXCLUDED.quiet_start_minutes, quiet_end_minutes = EXCLUDED.quiet_end_minutes
+         RETURNING min_notification_gap_seconds, timezone, quiet_start_minutes, quiet_end_minutes, locale",
+        discord_user_id as i64,
+        quiet_minutes.map(|(start, _)| start),
+        quiet_minutes.map(|(_, end)| end),
+    )
+        .fetch_one(pool)
+        .await?)
+}
+
+pub(crate) async fn create_scheduled_game(pool: &sqlx::PgPool, guild_id: u64, organizer_discord_id: u64, discord_channel_id: u64, game: Option<&Game>, description: &str, scheduled_for: chrono::DateTime<chrono::Utc>) -> Result<ScheduledGame, WololoError> {
+    let id: i32 = sqlx::query_scalar!(
+        "INSERT into scheduled_games (guild_id, organizer_discord_id, discord_channel_id, game_id, description, scheduled_for, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id",
+        guild_id as i64,
+        organizer_discord_id as i64,
+        discord_channel_id as i64,
+        game.map(|game| game.id),
+        description,
+        scheduled_for,
+        chrono::offset::Utc::now(),
+    )
+        .fetch_one(pool)
+        .await?;
+    Ok(ScheduledGame {
+        id,
+        guild_id: Some(guild_id as i64),
+        organizer_discord_id: organizer_discord_id as i64,
+        discord_channel_id: discord_channel_id as i64,
+        announcement_message_id: None,
+        game_name: game.map(|game| game.name.clone()),
+        description: description.to_string(),
+        scheduled_for,
+        started_at: None,
+        cancelled_at: None
+    })
+}
+
+pub(crate) async fn update_announcement_message_for_scheduled_game(pool: &sqlx::PgPool, scheduled_game_id: i32, announcement_message_id: u64) -> Result<bool, WololoError> {
+    let _ = sqlx::query!(
+        "UPDATE scheduled_games SET announcement_message_id = $2 WHERE id = $1",
+        scheduled_game_id,
+        announcement_message_id as i64,
+    )
+        .execute(pool)
+        .await?;
+    Ok(true)
+}
+
+/// Only returns true for the caller that actually marked the game as reminded, so reminders go out once
+pub(crate) async fn update_reminded_at_for_scheduled_game(pool: &sqlx::PgPool, scheduled_game_id: i32) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "UPDATE scheduled_games SET reminded_at = $2 WHERE id = $1 AND reminded_at IS NULL",
+        scheduled_game_id,
+        chrono::offset::Utc::now(),
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Only returns true for the caller that actually started the game, a cancelled game can't be started
+pub(crate) async fn update_started_at_for_scheduled_game(pool: &sqlx::PgPool, scheduled_game_id: i32) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "UPDATE scheduled_games SET started_at = $2 WHERE id = $1 AND started_at IS NULL AND cancelled_at IS NULL",
+        scheduled_game_id,
+        chrono::offset::Utc::now(),
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Returns false if the game already started or was already cancelled
+pub(crate) async fn update_cancelled_at_for_scheduled_game(pool: &sqlx::PgPool, scheduled_game_id: i32) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "UPDATE scheduled_games SET cancelled_at = $2 WHERE id = $1 AND started_at IS NULL AND cancelled_at IS NULL",
+        scheduled_game_id,
+        chrono::offset::Utc::now(),
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub(crate) async fn upsert_scheduled_game_rsvp(pool: &sqlx::PgPool, scheduled_game_id: i32, discord_user_id: u64, accepted: bool) -> Result<bool, WololoError> {
+    let _ = sqlx::query!(
+        "INSERT into scheduled_game_rsvps (scheduled_game_id, discord_user_id, accepted, updated_at) VALUES ($1, $2, $3, $4)
+         ON CONFLICT (scheduled_game_id, discord_user_id) DO UPDATE SET accepted = EXCLUDED.accepted, updated_at = EXCLUDED.updated_at",
+        scheduled_game_id,
+        discord_user_id as i64,
+        accepted,
+        chrono::offset::Utc::now(),
+    )
+        .execute(pool)
+        .await?;
+    Ok(true)
+}
+
+pub(crate) async fn delete_scheduled_game_rsvp(pool: &sqlx::PgPool, scheduled_game_id: i32, discord_user_id: u64) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "DELETE from scheduled_game_rsvps WHERE scheduled_game_id = $1 AND discord_user_id = $2",
+        scheduled_game_id,
+        discord_user_id as i64,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub(crate) async fn create_game(pool: &sqlx::PgPool, guild_id: u64, name: &str, aliases: &[String], default_party_size: i32, emoji: Option<&str>) -> Result<Game, WololoError> {
+    let id: i32 = sqlx::query_scalar!(
+        "INSERT into games (guild_id, name, aliases, default_party_size, emoji, created_at) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+        guild_id as i64,
+        name.to_lowercase(),
+        &aliases.iter().map(|alias| alias.to_lowercase()).collect::<Vec<_>>(),
+        default_party_size,
+        emoji,
+        chrono::offset::Utc::now(),
+    )
+        .fetch_one(pool)
+        .await?;
+    Ok(Game {
+        id,
+        guild_id: Some(guild_id as i64),
+        name: name.to_lowercase(),
+        aliases: aliases.iter().map(|alias| alias.to_lowercase()).collect(),
+        default_party_size,
+        emoji: emoji.map(str::to_string)
+    })
+}
+
+/// Subscriptions to the game are deleted with it, stacks and scheduled games just lose their game
+pub(crate) async fn delete_game(pool: &sqlx::PgPool, game_id: i32) -> Result<bool, WololoError> {
+    let result = sqlx::query!(
+        "DELETE FROM games WHERE id = $1",
+        game_id,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Fills in the guild for everything in the channel that was created before rows were scoped by guild, returns how
+/// many rows were updated
+pub(crate) async fn backfill_guild_for_channel(pool: &sqlx::PgPool, discord_channel_id: u64, guild_id: u64) -> Result<u64, WololoError> {
+    let mut transaction = pool.begin().await?;
+    let mut updated = 0;
+    updated += sqlx::query!(
+        "UPDATE ping_list SET guild_id = $1 WHERE discord_channel_id = $2 AND guild_id IS NULL",
+        guild_id as i64,
+        discord_channel_id as i64,
+    )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    updated += sqlx::query!(
+        "UPDATE lobbies SET guild_id = $1 WHERE discord_channel_id = $2 AND guild_id IS NULL",
+        guild_id as i64,
+        discord_channel_id as i64,
+    )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    updated += sqlx::query!(
+        "UPDATE scheduled_games SET guild_id = $1 WHERE discord_channel_id = $2 AND guild_id IS NULL",
+        guild_id as i64,
+        discord_channel_id as i64,
+    )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    updated += sqlx::query!(
+        "UPDATE channel_settings SET guild_id = $1 WHERE discord_channel_id = $2 AND guild_id IS NULL",
+        guild_id as i64,
+        discord_channel_id as i64,
+    )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    updated += sqlx::query!(
+        "UPDATE message_children SET guild_id = $1 WHERE parent_channel_id = $2 AND guild_id IS NULL",
+        guild_id as i64,
+        discord_channel_id as i64,
+    )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    transaction.commit().await?;
+    Ok(updated)
+}
+
+/// Admins, blacklisted users and games don't belong to a channel, so they can only be given a guild when the bot was
+/// only ever used in one
+pub(crate) async fn backfill_guild_for_unscoped_rows(pool: &sqlx::PgPool, guild_id: u64) -> Result<u64, WololoError> {
+    let mut transaction = pool.begin().await?;
+    let mut updated = 0;
+    updated += sqlx::query!(
+        "UPDATE admins SET guild_id = $1 WHERE guild_id IS NULL",
+        guild_id as i64,
+    )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    updated += sqlx::query!(
+        "UPDATE blacklisted_users SET guild_id = $1 WHERE guild_id IS NULL",
+        guild_id as i64,
+    )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    updated += sqlx::query!(
+        "UPDATE games SET guild_id = $1 WHERE guild_id IS NULL",
+        guild_id as i64,
+    )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    transaction.commit().await?;
+    Ok(updated)
+}